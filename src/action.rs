@@ -1,16 +1,29 @@
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, io};
 
-mod delta;
+pub mod delta;
+
+pub mod history;
+
+pub mod undo;
 
 #[derive(Debug)]
 pub enum ActionError {
     InvalidTimestamp,
+    Io(io::Error),
+    /// A history file that couldn't be read back.
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
 }
 
 impl fmt::Display for ActionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ActionError::InvalidTimestamp => write!(f, "Invalid timestamp"),
+            ActionError::Io(err) => write!(f, "{}", err),
+            ActionError::Parse(err) => {
+                write!(f, "Undo history: {}", err.message())
+            }
+            ActionError::Serialize(err) => write!(f, "Undo history: {}", err),
         }
     }
 }
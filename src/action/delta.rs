@@ -1,7 +1,8 @@
 use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 
-#[allow(dead_code)]
-enum BufferDelta {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BufferDelta {
     Insert {
         position: usize,
         text: Box<str>,
@@ -17,22 +18,25 @@ enum BufferDelta {
     },
 }
 
-enum AppDelta {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppDelta {
     Grapple,
 }
 
-enum Delta {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Delta {
     Buffer(BufferDelta),
     App(AppDelta),
 }
 
-struct Action {
-    timestamp: DateTime<Local>,
-    deltas: Vec<Delta>,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Action {
+    pub timestamp: DateTime<Local>,
+    pub deltas: Vec<Delta>,
 }
 
 impl Action {
-    fn new(deltas: Vec<Delta>) -> Self {
+    pub fn new(deltas: Vec<Delta>) -> Self {
         return Self {
             timestamp: Local::now(),
             deltas,
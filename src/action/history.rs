@@ -0,0 +1,112 @@
+use super::{undo::UndoTree, ActionError, ActionResult};
+use crate::{config, state};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Where the undo history of each file is kept between sessions, to pick up
+/// again when the file is next opened as it was left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+/// What is kept for a file: its undo tree, and a hash of the text the tree
+/// is at.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    /// In hex, as TOML integers don't go up to a `u64`.
+    hash: String,
+    tree: UndoTree,
+}
+
+impl HistoryStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `undo` under the state directory.
+    pub fn default_dir() -> Option<Self> {
+        config::state_dir().map(|dir| Self::new(dir.join("undo")))
+    }
+
+    /// The history file of `file`, named after its full path.
+    pub fn path(&self, file: &Path) -> io::Result<PathBuf> {
+        Ok(self.dir.join(state::flat_name(file)? + ".toml"))
+    }
+
+    /// Keep the history of `file`, whose tree is at the text `contents`.
+    pub fn save(
+        &self,
+        file: &Path,
+        contents: &str,
+        tree: &UndoTree,
+    ) -> ActionResult<()> {
+        let record = Record {
+            hash: format!("{:016x}", state::stable_hash(contents)),
+            tree: tree.clone(),
+        };
+        let path = self.path(file)?;
+        let partial = path.with_extension("toml.partial");
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&partial, toml::to_string(&record)?)?;
+
+        Ok(fs::rename(partial, path)?)
+    }
+
+    /// The history of `file`, when it was kept with the text as `contents`
+    /// is now. One kept before the file changed elsewhere no longer applies,
+    /// and is dropped, as is one that can't be read back.
+    pub fn load(&self, file: &Path, contents: &str) -> ActionResult<UndoTree> {
+        let path = self.path(file)?;
+        let src = match fs::read_to_string(&path) {
+            Ok(src) => src,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(UndoTree::default())
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let hash = format!("{:016x}", state::stable_hash(contents));
+        match toml::from_str::<Record>(&src) {
+            Ok(record) if record.hash == hash && record.tree.is_valid() => {
+                Ok(record.tree)
+            }
+            Ok(_) => {
+                self.remove(file)?;
+                Ok(UndoTree::default())
+            }
+            Err(err) => {
+                self.remove(file)?;
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Drop the history of `file`, if it has one.
+    pub fn remove(&self, file: &Path) -> io::Result<()> {
+        match fs::remove_file(self.path(file)?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<io::Error> for ActionError {
+    fn from(err: io::Error) -> Self {
+        ActionError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ActionError {
+    fn from(err: toml::de::Error) -> Self {
+        ActionError::Parse(err)
+    }
+}
+
+impl From<toml::ser::Error> for ActionError {
+    fn from(err: toml::ser::Error) -> Self {
+        ActionError::Serialize(err)
+    }
+}
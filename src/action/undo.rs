@@ -0,0 +1,172 @@
+use super::delta::{Action, BufferDelta, Delta};
+use serde::{Deserialize, Serialize};
+use std::mem;
+
+/// Every state the text of a buffer went through, as a tree of the steps of
+/// edits between them. Editing after an undo starts a new branch, keeping
+/// the one undone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoTree {
+    /// The first is the text as first loaded, no step leading to it. Others
+    /// are in the order they were made.
+    nodes: Vec<Node>,
+    /// The node the text is at.
+    current: usize,
+    /// The edits of the step still being made, each with what takes it
+    /// back, oldest first.
+    #[serde(skip)]
+    step: Vec<(BufferDelta, BufferDelta)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Node {
+    parent: usize,
+    /// The child redo goes to: the one made or gone back from last.
+    next: Option<usize>,
+    /// The step from the parent.
+    action: Action,
+    /// What takes the step back, in the order it applies.
+    inverse: Vec<Delta>,
+}
+
+impl Default for UndoTree {
+    fn default() -> Self {
+        let root = Node {
+            parent: 0,
+            next: None,
+            action: Action::new(vec![]),
+            inverse: vec![],
+        };
+
+        Self {
+            nodes: vec![root],
+            current: 0,
+            step: vec![],
+        }
+    }
+}
+
+impl UndoTree {
+    /// Without any step to undo or redo.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1 && self.step.is_empty()
+    }
+
+    /// Does every node lead back to the first, as a tree read back from disk
+    /// must before it's walked.
+    pub fn is_valid(&self) -> bool {
+        let len = self.nodes.len();
+        let linked = self.nodes.iter().enumerate().all(|(idx, node)| {
+            let parent = idx == 0 || node.parent < idx;
+            let next = node.next.is_none_or(|next| {
+                next < len && next > 0 && self.nodes[next].parent == idx
+            });
+
+            parent && next
+        });
+
+        len > 0 && self.current < len && linked
+    }
+
+    /// Note an edit just made, and the delta that takes it back.
+    pub fn record(&mut self, delta: BufferDelta, inverse: BufferDelta) {
+        self.step.push((delta, inverse));
+    }
+
+    /// End the step being made, so what's edited next is undone apart from
+    /// it. It branches off the state the text is at.
+    pub fn end_step(&mut self) {
+        if self.step.is_empty() {
+            return;
+        }
+        let (deltas, inverse): (Vec<_>, Vec<_>) =
+            mem::take(&mut self.step).into_iter().unzip();
+        let node = Node {
+            parent: self.current,
+            next: None,
+            action: Action::new(
+                deltas.into_iter().map(Delta::Buffer).collect(),
+            ),
+            // The latest edit is taken back first.
+            inverse: inverse.into_iter().rev().map(Delta::Buffer).collect(),
+        };
+        self.nodes.push(node);
+        let idx = self.nodes.len() - 1;
+        self.nodes[self.current].next = Some(idx);
+        self.current = idx;
+    }
+
+    /// Go back to the state before the latest step, returning the deltas
+    /// that take the text there.
+    pub fn undo(&mut self) -> Option<Vec<Delta>> {
+        self.end_step();
+        if self.current == 0 {
+            return None;
+        }
+        let parent = self.nodes[self.current].parent;
+        Some(self.goto(parent))
+    }
+
+    /// Go forward along the branch last taken, returning the deltas that
+    /// take the text there.
+    pub fn redo(&mut self) -> Option<Vec<Delta>> {
+        self.end_step();
+        let next = self.nodes[self.current].next?;
+        Some(self.goto(next))
+    }
+
+    /// Go to the state made before this one, whichever branch it's on.
+    pub fn earlier(&mut self) -> Option<Vec<Delta>> {
+        self.end_step();
+        let target = self.current.checked_sub(1)?;
+        Some(self.goto(target))
+    }
+
+    /// Go to the state made after this one, whichever branch it's on.
+    pub fn later(&mut self) -> Option<Vec<Delta>> {
+        self.end_step();
+        let target = self.current + 1;
+        (target < self.nodes.len()).then(|| self.goto(target))
+    }
+
+    /// Move to the node, up the tree to where its branch parts from this
+    /// one then down to it. Redo follows the way taken down after.
+    fn goto(&mut self, target: usize) -> Vec<Delta> {
+        let mut down = vec![];
+        let mut idx = target;
+        while !self.is_ancestor(idx, self.current) {
+            down.push(idx);
+            idx = self.nodes[idx].parent;
+        }
+
+        let mut deltas = vec![];
+        let mut up = self.current;
+        while up != idx {
+            let parent = self.nodes[up].parent;
+            self.nodes[parent].next = Some(up);
+            deltas.extend(self.nodes[up].inverse.iter().cloned());
+            up = parent;
+        }
+        for &child in down.iter().rev() {
+            let parent = self.nodes[child].parent;
+            self.nodes[parent].next = Some(child);
+            deltas.extend(self.nodes[child].action.deltas.iter().cloned());
+        }
+        self.current = target;
+
+        deltas
+    }
+
+    /// Is `ancestor` on the way from `idx` up to the first node.
+    fn is_ancestor(&self, ancestor: usize, mut idx: usize) -> bool {
+        loop {
+            if idx == ancestor {
+                return true;
+            }
+            if idx == 0 {
+                return false;
+            }
+            idx = self.nodes[idx].parent;
+        }
+    }
+}
@@ -1,40 +1,2084 @@
-use crate::buffer::{self, Buffer};
-use ratatui::style::{Color, Style};
-use std::{char, fmt::Display};
+use crate::{
+    action::{history::HistoryStore, ActionError},
+    buffer::{self, Buffer, Cursor, LineEnding},
+    completion::{self, Completion, Context, Item, Snippet, Sources},
+    config::{self, Config},
+    diagnostic::{Diagnostic, Diagnostics, Pos},
+    diff::{self, Hunk},
+    event::Waker,
+    explorer::Explorer,
+    git::{self, Blame, Repo},
+    grep::{self, Grep, Match},
+    lsp::{
+        self, CompletionItem, Diagnostic as LspDiagnostic, Event, Location,
+        Position, Session, TextEdit,
+    },
+    picker::Picker,
+    sign::{Markers, SignColumn, SignKind},
+    swap::SwapDir,
+    theme::{self, Theme},
+    watch::{self, Watcher},
+    window::{self, Split, Window, Windows},
+};
+use std::{
+    char,
+    collections::HashMap,
+    fmt::Display,
+    fs, io, mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Number of commits read for the git summary.
+const GIT_LOG_COUNT: usize = 20;
+
+/// Provider name of the diagnostics from the language server.
+const LSP_DIAGNOSTICS: &str = "lsp";
 
 #[derive(Debug, Default)]
 pub struct App {
     running_state: RunningState,
     pub buffer: Buffer,
+    /// Buffers open besides the one being edited, most recently left last.
+    buffers: Vec<Buffer>,
+    /// What belonged to each of `buffers` when it was left, by file name.
+    /// Missing for one that has to start over.
+    file_states: HashMap<Box<str>, FileState>,
     mode: Mode,
     notifications: Vec<Notification>,
+    /// Notifications pushed since they were last taken to be logged.
+    unlogged: Vec<(NotificationType, Box<str>)>,
+    theme: Theme,
+    config: Config,
+    /// Index of the first line on screen when the layout isn't centered.
+    scroll: usize,
+    /// `None` when the file isn't in a repository.
+    git: Option<git::Summary>,
+    /// Show the `HEAD` version of the hunk under the cursor.
+    hunk_preview: bool,
+    blame: BlameState,
+    /// The language server for the file, when one is configured.
+    lsp: Option<Session>,
+    popup: Option<Popup>,
+    prompt: Option<Prompt>,
+    diagnostics: Diagnostics,
+    /// Show the diagnostic list in place of the logs.
+    diagnostic_list: bool,
+    completion: Option<Completion>,
+    completion_sources: Sources,
+    /// Tab stops left of the snippet being filled in.
+    tab_stops: Option<TabStops>,
+    picker: Option<Picker>,
+    /// The last search, kept after its list is closed to step through.
+    grep: Option<Grep>,
+    /// Show the search results in place of the logs.
+    grep_list: bool,
+    explorer: Option<Explorer>,
+    /// Do keys go to the explorer rather than the buffer.
+    explorer_focused: bool,
+    windows: Windows,
+    history: Option<History>,
+    /// Where the selection started, in visual mode.
+    anchor: Option<Cursor>,
+    /// Columns and rows of the terminal.
+    size: (u16, u16),
+    /// Handed to work started in the background, to wake the main loop once
+    /// it has something.
+    waker: Waker,
+    /// Watches the open files for changes made to them by other programs.
+    watcher: Option<Watcher>,
+    /// `None` keeps swap files off.
+    swap: Option<SwapDir>,
+    /// When the swap files were last written.
+    swapped: Option<Instant>,
+    /// `None` keeps the undo history of files to the session.
+    history_store: Option<HistoryStore>,
+}
+
+/// Where the tab stops of a snippet start, as of when the buffer was `len`
+/// chars long. Stops are after whatever is typed into the one before them,
+/// so they move by how much the buffer grew since.
+#[derive(Debug)]
+struct TabStops {
+    starts: Vec<usize>,
+    len: usize,
+}
+
+/// What depends on the file being edited, set aside with its buffer while
+/// another is, so switching back needn't start it over.
+#[derive(Debug, Default)]
+struct FileState {
+    git: Option<git::Summary>,
+    hunk_preview: bool,
+    blame: BlameState,
+    lsp: Option<Session>,
+    diagnostics: Diagnostics,
+}
+
+impl FileState {
+    fn close(mut self) {
+        if let Some(session) = self.lsp.as_mut() {
+            session.close();
+        }
+    }
+}
+
+/// Blame is read on another thread, as it can take a while on big files.
+#[derive(Debug, Default)]
+enum BlameState {
+    #[default]
+    Off,
+    Loading(Receiver<git::Result<Blame>>),
+    Ready(Blame),
 }
 
 impl App {
     pub fn new(buffer: buffer::Buffer) -> Self {
         Self {
+            windows: Windows::new(Window::new(buffer.file_name())),
             buffer,
             ..Default::default()
         }
     }
 
-    pub fn mode(&self) -> Mode {
-        self.mode
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn running_state(&self) -> RunningState {
+        self.running_state
+    }
+
+    pub fn notifs(&self) -> &[Notification] {
+        self.notifications.as_slice()
+    }
+
+    /// Push a notification to the status line and the history. With
+    /// `dedup`, one pushed right after itself is counted instead.
+    pub fn push_notif(&mut self, mut msg: Notification) {
+        msg.pushed = Some(Instant::now());
+        self.unlogged.push((msg.message_type, msg.text.clone()));
+        let last = self.notifications.last_mut();
+        match last.filter(|last| {
+            self.config.notifications.dedup && last.repeats(&msg)
+        }) {
+            Some(last) => {
+                last.repeats += 1;
+                last.pushed = msg.pushed;
+            }
+            None => self.notifications.push(msg),
+        }
+    }
+
+    /// Notifications pushed since the last call, oldest first, each with
+    /// its type, for the log.
+    pub fn take_unlogged(&mut self) -> Vec<(NotificationType, Box<str>)> {
+        mem::take(&mut self.unlogged)
+    }
+
+    /// The notification shown in the status line, until it times out.
+    pub fn current_notif(&self) -> Option<&Notification> {
+        let last = self.notifications.last()?;
+
+        match self.notif_remaining() {
+            Some(Duration::ZERO) => None,
+            _ => Some(last),
+        }
+    }
+
+    /// Time left before the notification in the status line times out.
+    /// `None` when there is none, or it doesn't time out.
+    pub fn notif_remaining(&self) -> Option<Duration> {
+        let timeout = self.config.notifications.timeout;
+        let pushed = self.notifications.last()?.pushed?;
+        if timeout == 0 {
+            return None;
+        }
+
+        Some(Duration::from_millis(timeout).saturating_sub(pushed.elapsed()))
+    }
+
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    pub fn history_mut(&mut self) -> Option<&mut History> {
+        self.history.as_mut()
+    }
+
+    /// Open the list of every notification of the session.
+    pub fn open_history(&mut self) {
+        self.history = Some(History::default())
+    }
+
+    pub fn close_history(&mut self) {
+        self.history = None
+    }
+
+    /// The notifications matching the history's query, newest first.
+    pub fn history_matches(&self) -> Vec<&Notification> {
+        let query = self
+            .history
+            .as_ref()
+            .map(|history| history.query.to_lowercase())
+            .unwrap_or_default();
+
+        self.notifications
+            .iter()
+            .rev()
+            .filter(|notif| notif.text.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Forget every notification so far.
+    pub fn clear_notifications(&mut self) {
+        self.notifications.clear();
+        if let Some(history) = self.history.as_mut() {
+            history.selected = 0;
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Apply a config, loading the theme it names.
+    pub fn configure(&mut self, config: Config) -> config::Result<()> {
+        self.theme = Theme::find(&config.theme)?;
+        self.config = config;
+
+        Ok(())
+    }
+
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Scroll so the cursor stays `scrolloff` lines away from the top and
+    /// bottom of a text area `height` lines tall.
+    pub fn scroll_to_cursor(&mut self, height: usize) {
+        let scrolloff = self.config.scrolloff.min(height.saturating_sub(1) / 2);
+        let line_idx = self.buffer.cursor.y;
+        if line_idx < self.scroll + scrolloff {
+            self.scroll = line_idx.saturating_sub(scrolloff);
+        } else if line_idx + scrolloff >= self.scroll + height {
+            self.scroll = line_idx + scrolloff + 1 - height;
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Take the new size of the terminal, keeping the cursor in view.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.size = (width, height);
+        // Less the status line.
+        self.scroll_to_cursor(height.saturating_sub(1) as usize);
+    }
+
+    /// The terminal has focus again. The repository may have changed while
+    /// it was away.
+    pub fn focus_gained(&mut self) {
+        if let Err(err) = self.refresh_git() {
+            self.push_notif(Notification::from(&err));
+        }
+    }
+
+    /// The terminal lost focus, save the buffer if `auto_save` is on.
+    pub fn focus_lost(&mut self) -> Result<()> {
+        if self.config.auto_save && self.buffer.is_modified() {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme
+    }
+
+    /// Switch to the next built-in theme, wrapping around after the last.
+    pub fn cycle_theme(&mut self) -> theme::Result<()> {
+        let names: Vec<&str> = Theme::builtin_names().collect();
+        let next_idx = names
+            .iter()
+            .position(|name| *name == self.theme.name())
+            .map_or(0, |idx| (idx + 1) % names.len());
+        self.theme = Theme::builtin(names[next_idx])?;
+        self.push_notif(Notification::new(
+            NotificationType::Info,
+            format!("theme {}", self.theme.name()),
+        ));
+
+        Ok(())
+    }
+
+    pub fn markers(&self) -> &Markers {
+        self.buffer.markers()
+    }
+
+    /// Toggle a mark or breakpoint on the cursor line.
+    pub fn toggle_marker(&mut self, kind: SignKind) {
+        let line_idx = self.buffer.cursor.y;
+        self.buffer.toggle_marker(line_idx, kind)
+    }
+
+    /// The signs of every provider, for the sign column.
+    pub fn sign_column(&self) -> SignColumn<'_> {
+        let signs = SignColumn::new()
+            .with(self.buffer.markers())
+            .with(&self.diagnostics);
+        match self.buffer.diff() {
+            Some(diff) => signs.with(diff),
+            None => signs,
+        }
+    }
+
+    pub fn git(&self) -> Option<&git::Summary> {
+        self.git.as_ref()
+    }
+
+    /// Re-read the git state of the repository holding the file, and diff
+    /// the buffer against the file at `HEAD`.
+    pub fn refresh_git(&mut self) -> git::Result<()> {
+        let repo = match Repo::discover(self.buffer.file_name()) {
+            Ok(repo) => repo,
+            // Without git there's no repository to show.
+            Err(git::Error::NotARepo(_) | git::Error::NotInstalled) => {
+                self.git = None;
+                self.buffer.set_diff_base(None);
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
+        self.git = Some(repo.summary(GIT_LOG_COUNT)?);
+        // A file that was never committed is all new lines.
+        let base = repo.head_contents(self.buffer.file_name())?;
+        self.buffer
+            .set_diff_base(Some(base.as_deref().unwrap_or_default()));
+
+        Ok(())
+    }
+
+    pub fn hunk_preview(&self) -> bool {
+        self.hunk_preview
+    }
+
+    pub fn toggle_hunk_preview(&mut self) {
+        self.hunk_preview = !self.hunk_preview
+    }
+
+    /// Move to the start of the next hunk below the cursor.
+    pub fn move_next_hunk(&mut self) -> Result<()> {
+        let line_idx = self
+            .buffer
+            .diff()
+            .and_then(|diff| diff.next_hunk(self.buffer.cursor.y))
+            .ok_or(Error::NoMoreHunks)?
+            .anchor();
+        self.buffer.cursor = (0, line_idx).into();
+
+        Ok(())
+    }
+
+    /// Move to the start of the previous hunk above the cursor.
+    pub fn move_prev_hunk(&mut self) -> Result<()> {
+        let line_idx = self
+            .buffer
+            .diff()
+            .and_then(|diff| diff.prev_hunk(self.buffer.cursor.y))
+            .ok_or(Error::NoMoreHunks)?
+            .anchor();
+        self.buffer.cursor = (0, line_idx).into();
+
+        Ok(())
+    }
+
+    fn hunk_under_cursor(&self) -> Result<Hunk> {
+        self.buffer
+            .diff()
+            .and_then(|diff| diff.hunk_at(self.buffer.cursor.y))
+            .cloned()
+            .ok_or(Error::NoHunk)
+    }
+
+    /// Replace the hunk under the cursor with the lines from `HEAD`.
+    pub fn revert_hunk(&mut self) -> Result<()> {
+        let hunk = self.hunk_under_cursor()?;
+        let base = match self.buffer.diff() {
+            Some(diff) => diff.base()[hunk.before.clone()].to_vec(),
+            None => return Err(Error::NoHunk),
+        };
+
+        // The last line has no '\n', so hunks at the end of the buffer put
+        // theirs before each line instead of after.
+        let at_end = hunk.after.end == self.buffer.len_lines();
+        let (start, end, text) = if !at_end {
+            let text: String =
+                base.iter().map(|l| format!("{}\n", l)).collect();
+            (
+                self.buffer.char_idx_line_start(hunk.after.start)?,
+                self.buffer.char_idx_line_start(hunk.after.end)?,
+                text,
+            )
+        } else if hunk.after.is_empty() {
+            let text: String =
+                base.iter().map(|l| format!("\n{}", l)).collect();
+            (self.buffer.len(), self.buffer.len(), text)
+        } else {
+            let mut start =
+                self.buffer.char_idx_line_start(hunk.after.start)?;
+            if base.is_empty() {
+                // Take the '\n' ending the line above too.
+                start = start.saturating_sub(1);
+                (start, self.buffer.len(), String::new())
+            } else {
+                (start, self.buffer.len(), base.join("\n"))
+            }
+        };
+        self.buffer.remove(start..end)?;
+        self.buffer.insert(start, text)?;
+
+        let line_idx = hunk.after.start.min(self.buffer.len_lines() - 1);
+        self.buffer.cursor = (0, line_idx).into();
+
+        Ok(())
+    }
+
+    /// Add the hunk under the cursor to the index.
+    pub fn stage_hunk(&mut self) -> Result<()> {
+        let hunk = self.hunk_under_cursor()?;
+        let repo = Repo::discover(self.buffer.file_name())?;
+        let path = repo.relative_path(self.buffer.file_name())?;
+        let patch =
+            self.buffer.hunk_patch(&hunk, &path).ok_or(Error::NoHunk)?;
+
+        repo.stage_patch(&patch)?;
+        self.git = Some(repo.summary(GIT_LOG_COUNT)?);
+        self.push_notif(Notification::new(
+            NotificationType::Success,
+            "staged hunk",
+        ));
+
+        Ok(())
+    }
+
+    /// Blame of the buffer, once it has been read.
+    pub fn blame(&self) -> Option<&Blame> {
+        match &self.blame {
+            BlameState::Ready(blame) => Some(blame),
+            _ => None,
+        }
+    }
+
+    pub fn blame_loading(&self) -> bool {
+        matches!(self.blame, BlameState::Loading(_))
+    }
+
+    pub fn toggle_blame(&mut self) {
+        match self.blame {
+            BlameState::Off => self.start_blame(),
+            _ => self.blame = BlameState::Off,
+        }
+    }
+
+    /// Blame the buffer as it is now, in the background.
+    fn start_blame(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        let path = self.buffer.file_name().to_string();
+        let contents = self.buffer.contents();
+        let waker = self.waker.clone();
+        thread::spawn(move || {
+            let blame = Repo::discover(&path)
+                .and_then(|repo| repo.blame(&path, &contents));
+            _ = sender.send(blame);
+            waker.wake();
+        });
+        self.blame = BlameState::Loading(receiver);
+    }
+
+    /// Pick up a blame that finished in the background.
+    pub fn update_blame(&mut self) {
+        let BlameState::Loading(receiver) = &self.blame else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(blame)) => self.blame = BlameState::Ready(blame),
+            Ok(Err(err)) => {
+                self.push_notif(Notification::from(&err));
+                self.blame = BlameState::Off;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.blame = BlameState::Off,
+        }
+    }
+
+    /// Start the language server configured for the file's extension, if
+    /// there is one.
+    pub fn start_lsp(&mut self) -> lsp::Result<()> {
+        let file_name = self.buffer.file_name();
+        let Some((language_id, server)) =
+            lsp::server_for(&self.config.lsp, file_name)
+        else {
+            return Ok(());
+        };
+
+        // Servers find the project from its root, the repository is the best
+        // guess at it.
+        let path = Path::new(file_name);
+        let root = match Repo::discover(path) {
+            Ok(repo) => repo.root().to_path_buf(),
+            Err(_) => path
+                .canonicalize()?
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+        let session = Session::open(
+            server,
+            language_id,
+            path,
+            &root,
+            &self.buffer.contents(),
+            self.waker.clone(),
+        )?;
+        self.buffer.track_changes(true);
+        self.lsp = Some(session);
+
+        Ok(())
+    }
+
+    /// Is a request to the language server waiting on an answer.
+    pub fn lsp_waiting(&self) -> bool {
+        self.lsp.as_ref().is_some_and(Session::is_waiting)
+    }
+
+    /// The language server, told about any edits not sent yet.
+    fn lsp(&mut self) -> Result<&mut Session> {
+        let session = self.lsp.as_mut().ok_or(Error::NoLanguageServer)?;
+        session.did_change(self.buffer.take_changes())?;
+
+        Ok(session)
+    }
+
+    fn cursor_lsp_position(&self) -> Position {
+        let cursor = self.buffer.cursor;
+        let line_start = self
+            .buffer
+            .char_idx_line_start(cursor.y)
+            .unwrap_or_default();
+
+        self.buffer.lsp_position(line_start + cursor.x)
+    }
+
+    pub fn goto_definition(&mut self) -> Result<()> {
+        let position = self.cursor_lsp_position();
+        self.lsp()?.definition(position)?;
+
+        Ok(())
+    }
+
+    pub fn hover(&mut self) -> Result<()> {
+        let position = self.cursor_lsp_position();
+        self.lsp()?.hover(position)?;
+
+        Ok(())
+    }
+
+    pub fn references(&mut self) -> Result<()> {
+        let position = self.cursor_lsp_position();
+        self.lsp()?.references(position)?;
+
+        Ok(())
+    }
+
+    pub fn rename(&mut self, new_name: &str) -> Result<()> {
+        let position = self.cursor_lsp_position();
+        self.lsp()?.rename(position, new_name)?;
+
+        Ok(())
+    }
+
+    pub fn format(&mut self) -> Result<()> {
+        let tab_width = self.config.tab_width;
+        self.lsp()?.formatting(tab_width)?;
+
+        Ok(())
+    }
+
+    /// Send edits to the language server and act on what it sent back. A
+    /// server that stops working is dropped.
+    pub fn update_lsp(&mut self) {
+        let events = match self.lsp().map(Session::poll) {
+            Ok(Ok(events)) => events,
+            Err(Error::NoLanguageServer) => return,
+            Ok(Err(err)) | Err(Error::Lsp(err)) => {
+                self.push_notif(Notification::from(&err));
+                self.lsp = None;
+                self.buffer.track_changes(false);
+                self.diagnostics.set(LSP_DIAGNOSTICS, vec![]);
+                return;
+            }
+            Err(err) => return self.push_notif(Notification::from(&err)),
+        };
+
+        for event in events {
+            if let Err(err) = self.apply_lsp_event(event) {
+                self.push_notif(Notification::from(&err));
+            }
+        }
+    }
+
+    fn apply_lsp_event(&mut self, event: Event) -> Result<()> {
+        match event {
+            Event::Definition(locations) => {
+                let location =
+                    locations.first().ok_or(Error::NoDefinition)?.clone();
+                if !self.in_buffer(&location) {
+                    self.open_file(&location.path)?;
+                }
+                let pos = self.pos_from_lsp(location.range.start);
+                self.buffer.cursor = (pos.col, pos.line).into();
+            }
+            Event::Hover(Some(text)) => {
+                self.popup = Some(Popup {
+                    title: " hover ".into(),
+                    lines: text.lines().map(String::from).collect(),
+                })
+            }
+            Event::Hover(None) => self.push_notif(Notification::new(
+                NotificationType::Info,
+                "no hover information",
+            )),
+            Event::References(locations) => {
+                self.popup = Some(Popup {
+                    title: format!(" references ({}) ", locations.len()).into(),
+                    lines: locations
+                        .iter()
+                        .map(|location| self.location_label(location))
+                        .collect(),
+                })
+            }
+            Event::Rename(workspace_edit) => {
+                let files = workspace_edit.len();
+                for (path, edits) in workspace_edit {
+                    let in_buffer = self
+                        .lsp
+                        .as_ref()
+                        .is_some_and(|session| session.path() == path);
+                    match in_buffer {
+                        true => self.apply_text_edits(edits)?,
+                        false => lsp::edit_file(&path, edits)?,
+                    }
+                }
+                self.push_notif(Notification::new(
+                    NotificationType::Success,
+                    format!("renamed in {} files", files),
+                ));
+            }
+            Event::Formatting(edits) => self.apply_text_edits(edits)?,
+            Event::Message(message_type, text) => {
+                self.push_notif(Notification::new(message_type, text))
+            }
+            Event::Completion(items) => {
+                let Some(completion) = &self.completion else {
+                    return Ok(());
+                };
+                let line_idx = completion.line_idx;
+                let line = self.line_before_cursor();
+                let lsp_items = items
+                    .into_iter()
+                    .map(|item| self.item_from_lsp(item, line_idx, &line))
+                    .collect();
+                let items = self.complete_from_sources(&line);
+                if let Some(completion) = self.completion.as_mut() {
+                    completion.lsp_items = lsp_items;
+                    completion.waiting = false;
+                    completion.rank(&line, items);
+                    if completion.items.is_empty() {
+                        self.completion = None;
+                    }
+                }
+            }
+            Event::Diagnostics(diagnostics) => {
+                let diagnostics = diagnostics
+                    .into_iter()
+                    .map(|diagnostic| self.diagnostic_from_lsp(diagnostic))
+                    .collect();
+                self.diagnostics.set(LSP_DIAGNOSTICS, diagnostics);
+            }
+            Event::Failed(_, err) => return Err(err.into()),
+        }
+
+        Ok(())
+    }
+
+    /// The buffer position of a position from the language server.
+    fn pos_from_lsp(&self, position: Position) -> Pos {
+        let char_idx = self.buffer.char_idx_from_lsp(position);
+        let line = self.buffer.lsp_position(char_idx).line;
+        let line_start =
+            self.buffer.char_idx_line_start(line).unwrap_or_default();
+
+        Pos::new(line, char_idx - line_start)
+    }
+
+    fn diagnostic_from_lsp(&self, diagnostic: LspDiagnostic) -> Diagnostic {
+        Diagnostic {
+            start: self.pos_from_lsp(diagnostic.range.start),
+            end: self.pos_from_lsp(diagnostic.range.end),
+            severity: diagnostic.severity,
+            message: diagnostic.message.into(),
+            source: diagnostic.source,
+        }
+    }
+
+    fn in_buffer(&self, location: &Location) -> bool {
+        self.lsp
+            .as_ref()
+            .is_some_and(|session| session.path() == location.path)
+    }
+
+    /// `path:line:column`, the path relative to the working dir when it can
+    /// be.
+    fn location_label(&self, location: &Location) -> String {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let path = location.path.strip_prefix(&cwd).unwrap_or(&location.path);
+
+        format!(
+            "{}:{}:{}",
+            path.display(),
+            location.range.start.line + 1,
+            location.range.start.character + 1
+        )
+    }
+
+    /// Make edits from the language server, all given against the buffer as
+    /// it is now.
+    fn apply_text_edits(&mut self, mut edits: Vec<TextEdit>) -> Result<()> {
+        // Last first, so the positions of the rest still hold.
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        for edit in edits {
+            let start = self.buffer.char_idx_from_lsp(edit.range.start);
+            let end = self.buffer.char_idx_from_lsp(edit.range.end).max(start);
+            // The server's copy has the final '\n' the buffer drops.
+            let past_end = edit.range.end.line >= self.buffer.len_lines();
+            let text = match past_end {
+                true => edit.text.strip_suffix('\n').unwrap_or(&edit.text),
+                false => &edit.text,
+            };
+
+            if start < end {
+                self.buffer.remove(start..end)?;
+            }
+            if !text.is_empty() {
+                self.buffer.insert(start, text)?;
+            }
+        }
+
+        let y = self.buffer.cursor.y.min(self.buffer.len_lines() - 1);
+        let line_len = self.buffer.line(y)?.len();
+        self.buffer.cursor =
+            (self.buffer.cursor.x.min(line_len.saturating_sub(1)), y).into();
+
+        Ok(())
+    }
+
+    fn item_from_lsp(
+        &self,
+        item: CompletionItem,
+        line_idx: usize,
+        line: &str,
+    ) -> Item {
+        let start = match item.range {
+            Some(range) if range.start.line == line_idx => {
+                self.pos_from_lsp(range.start).col
+            }
+            _ => completion::word_start(line),
+        };
+
+        Item {
+            label: item.label.into(),
+            text: item.text.into(),
+            snippet: item.snippet,
+            start,
+            kind: completion::Kind::Lsp,
+            detail: item.detail.map(Into::into),
+        }
+    }
+
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Replace the diagnostics from `provider`, such as an external checker.
+    pub fn set_diagnostics(
+        &mut self,
+        provider: &str,
+        diagnostics: Vec<Diagnostic>,
+    ) {
+        self.diagnostics.set(provider, diagnostics)
+    }
+
+    fn cursor_pos(&self) -> Pos {
+        Pos::new(self.buffer.cursor.y, self.buffer.cursor.x)
+    }
+
+    /// The most severe diagnostic the cursor is on.
+    pub fn diagnostic_under_cursor(&self) -> Option<&Diagnostic> {
+        self.diagnostics.at(self.cursor_pos())
+    }
+
+    /// Move to the start of the next diagnostic after the cursor.
+    pub fn move_next_diagnostic(&mut self) -> Result<()> {
+        let start = self
+            .diagnostics
+            .next(self.cursor_pos())
+            .ok_or(Error::NoMoreDiagnostics)?
+            .start;
+        self.move_to_pos(start)
+    }
+
+    /// Move to the start of the previous diagnostic before the cursor.
+    pub fn move_prev_diagnostic(&mut self) -> Result<()> {
+        let start = self
+            .diagnostics
+            .prev(self.cursor_pos())
+            .ok_or(Error::NoMoreDiagnostics)?
+            .start;
+        self.move_to_pos(start)
+    }
+
+    /// Move the cursor to `pos`, or as close as the buffer allows when the
+    /// buffer changed since the position was taken.
+    fn move_to_pos(&mut self, pos: Pos) -> Result<()> {
+        let y = pos.line.min(self.buffer.len_lines() - 1);
+        let line_len = self.buffer.line(y)?.len();
+        self.buffer.cursor =
+            (pos.col.min(line_len.saturating_sub(1)), y).into();
+
+        Ok(())
+    }
+
+    pub fn diagnostic_list(&self) -> bool {
+        self.diagnostic_list
+    }
+
+    pub fn toggle_diagnostic_list(&mut self) {
+        self.grep_list = false;
+        self.diagnostic_list = !self.diagnostic_list
+    }
+
+    pub fn completion(&self) -> Option<&Completion> {
+        self.completion.as_ref()
+    }
+
+    /// Complete from `source` too, on top of the buffer words, file paths
+    /// and language server.
+    pub fn add_completion_source(
+        &mut self,
+        source: Box<dyn completion::Source>,
+    ) {
+        self.completion_sources.add(source)
+    }
+
+    /// The cursor line up to the cursor.
+    fn line_before_cursor(&self) -> String {
+        let Ok(line) = self.buffer.line(self.buffer.cursor.y) else {
+            return String::new();
+        };
+
+        line.to_string()
+            .chars()
+            .take(self.buffer.cursor.x)
+            .filter(|char| *char != '\n')
+            .collect()
+    }
+
+    fn complete_from_sources(&self, line: &str) -> Vec<Item> {
+        let dir = Path::new(self.buffer.file_name())
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let buffers: Vec<&Buffer> =
+            std::iter::once(&self.buffer).chain(&self.buffers).collect();
+        let ctx = Context {
+            line,
+            buffers: &buffers,
+            dir,
+        };
+
+        self.completion_sources.complete(&ctx)
+    }
+
+    /// Open the completion menu for the text before the cursor, asking the
+    /// language server too if there is one.
+    pub fn open_completion(&mut self) -> Result<()> {
+        let line = self.line_before_cursor();
+        let mut completion = Completion::new(self.buffer.cursor.y);
+        if self.lsp.is_some() {
+            let position = self.cursor_lsp_position();
+            self.lsp()?.completion(position)?;
+            completion.waiting = true;
+        }
+        completion.rank(&line, self.complete_from_sources(&line));
+        if completion.items.is_empty() && !completion.waiting {
+            return Err(Error::NoCompletions);
+        }
+        self.completion = Some(completion);
+
+        Ok(())
+    }
+
+    /// Open the menu when `char`, just typed, is one that starts a
+    /// completion.
+    pub fn trigger_completion(&mut self, char: char) {
+        let is_trigger = char == '/'
+            || self
+                .lsp
+                .as_ref()
+                .is_some_and(|lsp| lsp.trigger_characters().contains(&char));
+        if self.completion.is_none() && is_trigger {
+            // Nothing to complete is fine when nobody asked.
+            _ = self.open_completion();
+        }
+    }
+
+    /// Select the next item, opening the menu if it isn't open.
+    pub fn complete_next(&mut self) -> Result<()> {
+        match self.completion.as_mut() {
+            Some(completion) => completion.select_next(),
+            None => self.open_completion()?,
+        }
+
+        Ok(())
+    }
+
+    /// Select the previous item, opening the menu on the last item if it
+    /// isn't open.
+    pub fn complete_prev(&mut self) -> Result<()> {
+        if self.completion.is_none() {
+            self.open_completion()?;
+        }
+        if let Some(completion) = self.completion.as_mut() {
+            completion.select_prev();
+        }
+
+        Ok(())
+    }
+
+    pub fn close_completion(&mut self) {
+        self.completion = None
+    }
+
+    /// Rank the menu again for what was typed since, closing it once the
+    /// cursor leaves the line or nothing matches.
+    pub fn update_completion(&mut self) {
+        let Some(completion) = &self.completion else {
+            return;
+        };
+        if self.mode != Mode::Insert
+            || self.buffer.cursor.y != completion.line_idx
+        {
+            self.completion = None;
+            return;
+        }
+        let line = self.line_before_cursor();
+        if completion.is_ranked_for(&line) {
+            return;
+        }
+
+        let items = self.complete_from_sources(&line);
+        if let Some(completion) = self.completion.as_mut() {
+            completion.rank(&line, items);
+            if completion.items.is_empty() && !completion.waiting {
+                self.completion = None;
+            }
+        }
+    }
+
+    /// Replace what was typed with the selected item. Snippets leave the
+    /// cursor on their first tab stop.
+    pub fn accept_completion(&mut self) -> Result<()> {
+        let Some(item) = self
+            .completion
+            .take()
+            .and_then(|completion| completion.selected().cloned())
+        else {
+            return Ok(());
+        };
+
+        let line_start =
+            self.buffer.char_idx_line_start(self.buffer.cursor.y)?;
+        let start = line_start + item.start;
+        let end = line_start + self.buffer.cursor.x;
+        let snippet = match item.snippet {
+            true => Snippet::parse(&item.text),
+            false => Snippet {
+                text: item.text.into(),
+                stops: vec![],
+            },
+        };
+        if start < end {
+            self.buffer.remove(start..end)?;
+        }
+        self.buffer.insert(start, &snippet.text)?;
+
+        let mut starts = snippet.stops.iter().map(|stop| start + stop.start);
+        self.tab_stops = None;
+        match starts.next() {
+            Some(first) => {
+                self.move_to_char(first)?;
+                let starts: Vec<usize> = starts.collect();
+                if !starts.is_empty() {
+                    self.tab_stops = Some(TabStops {
+                        starts,
+                        len: self.buffer.len(),
+                    });
+                }
+            }
+            None => self.move_to_char(start + snippet.text.chars().count())?,
+        }
+
+        Ok(())
+    }
+
+    /// Move to the next tab stop of the snippet being filled in, or type a
+    /// tab when there is none.
+    pub fn next_tab_stop(&mut self) -> Result<()> {
+        let Some(mut tab_stops) = self.tab_stops.take() else {
+            self.insert_char_before('\t')?;
+            self.move_right(1);
+            return Ok(());
+        };
+
+        let grown = self.buffer.len() as isize - tab_stops.len as isize;
+        let next = tab_stops.starts.remove(0).saturating_add_signed(grown);
+        if !tab_stops.starts.is_empty() {
+            for start in tab_stops.starts.iter_mut() {
+                *start = start.saturating_add_signed(grown);
+            }
+            tab_stops.len = self.buffer.len();
+            self.tab_stops = Some(tab_stops);
+        }
+
+        self.move_to_char(next)
+    }
+
+    fn move_to_char(&mut self, char_idx: usize) -> Result<()> {
+        let line_idx = self.buffer.lsp_position(char_idx).line;
+        let line_start = self.buffer.char_idx_line_start(line_idx)?;
+        self.buffer.cursor = (char_idx - line_start, line_idx).into();
+
+        Ok(())
+    }
+
+    pub fn popup(&self) -> Option<&Popup> {
+        self.popup.as_ref()
+    }
+
+    pub fn close_popup(&mut self) {
+        self.popup = None
+    }
+
+    pub fn prompt(&self) -> Option<&Prompt> {
+        self.prompt.as_ref()
+    }
+
+    /// Ask for a line of input in the message bar, starting from `input`.
+    pub fn open_prompt(&mut self, action: PromptAction, input: String) {
+        self.prompt = Some(Prompt { action, input })
+    }
+
+    pub fn prompt_mut(&mut self) -> Option<&mut Prompt> {
+        self.prompt.as_mut()
+    }
+
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None
+    }
+
+    /// Run the prompt's action with what was typed.
+    pub fn submit_prompt(&mut self) -> Result<()> {
+        let Some(prompt) = self.prompt.take() else {
+            return Ok(());
+        };
+        match prompt.action {
+            PromptAction::Rename => self.rename(&prompt.input),
+            PromptAction::Grep => {
+                self.start_grep(&prompt.input, Path::new("."))
+            }
+            // Commands are run by the handler, which knows them.
+            PromptAction::Command => Ok(()),
+            PromptAction::CreateFile => self.create_file(&prompt.input),
+            PromptAction::RenameFile => self.rename_file(&prompt.input),
+            PromptAction::DeleteFile => match prompt.input.trim() {
+                "y" | "Y" => self.delete_file(),
+                _ => Ok(()),
+            },
+            PromptAction::Recover => self.recover(prompt.input.trim()),
+            PromptAction::FileChanged => {
+                let disk = fs::read_to_string(self.buffer.file_name())?;
+                match prompt.input.trim() {
+                    "r" | "R" => self.reload_file(&disk),
+                    "m" | "M" => self.merge_file(&disk),
+                    "d" | "D" => {
+                        self.show_changes(&disk);
+                        Ok(())
+                    }
+                    // The edits stay, to write over the file when saved.
+                    _ => {
+                        self.buffer.set_saved(&disk);
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rename the symbol under the cursor, asking for the new name.
+    pub fn start_rename(&mut self) -> Result<()> {
+        if self.lsp.is_none() {
+            return Err(Error::NoLanguageServer);
+        }
+        let word = self.word_under_cursor();
+        self.open_prompt(PromptAction::Rename, word);
+
+        Ok(())
+    }
+
+    /// The identifier the cursor is on, or an empty string.
+    fn word_under_cursor(&self) -> String {
+        let Ok(line) = self.buffer.line(self.buffer.cursor.y) else {
+            return String::new();
+        };
+        let chars: Vec<char> = line.to_string().chars().collect();
+        let is_word = |char: &char| char.is_alphanumeric() || *char == '_';
+        let x = self.buffer.cursor.x;
+        if !chars.get(x).is_some_and(is_word) {
+            return String::new();
+        }
+        let start = chars[..x]
+            .iter()
+            .rposition(|char| !is_word(char))
+            .map_or(0, |idx| idx + 1);
+        let end = chars[x..]
+            .iter()
+            .position(|char| !is_word(char))
+            .map_or(chars.len(), |idx| x + idx);
+
+        chars[start..end].iter().collect()
+    }
+
+    pub fn picker(&self) -> Option<&Picker> {
+        self.picker.as_ref()
+    }
+
+    pub fn picker_mut(&mut self) -> Option<&mut Picker> {
+        self.picker.as_mut()
+    }
+
+    /// Find a file under `root` to open.
+    pub fn open_picker(&mut self, root: &Path) {
+        self.picker = Some(Picker::new(root, self.waker.clone()))
+    }
+
+    pub fn close_picker(&mut self) {
+        self.picker = None
+    }
+
+    /// Open the file selected in the picker.
+    pub fn submit_picker(&mut self) -> Result<()> {
+        let Some(picker) = self.picker.take() else {
+            return Ok(());
+        };
+        let Some(path) = picker.selected() else {
+            return Err(Error::NoFileSelected);
+        };
+
+        self.open_file(&picker.root().join(path))
+    }
+
+    /// Buffers open besides the one being edited.
+    pub fn buffers(&self) -> &[Buffer] {
+        &self.buffers
+    }
+
+    /// Edit the file at `path`, switching to its buffer when it is already
+    /// open so unsaved edits are kept.
+    pub fn open_file(&mut self, path: &Path) -> Result<()> {
+        if is_file_of(&self.buffer, path) {
+            return Ok(());
+        }
+        let open = self
+            .buffers
+            .iter()
+            .position(|buffer| is_file_of(buffer, path));
+        let buffer = match open {
+            Some(idx) => self.buffers.remove(idx),
+            None => {
+                let buffer = Buffer::from_file(path.to_string_lossy().into())?;
+                self.watch_file(path)?;
+                buffer
+            }
+        };
+        self.stash_file_state();
+        let prev = mem::replace(&mut self.buffer, buffer);
+        self.buffers.push(prev);
+
+        match self.file_states.remove(self.buffer.file_name()) {
+            Some(state) => self.restore_file_state(state),
+            None => self.reload_file_state()?,
+        }
+        if open.is_none() {
+            if let Err(err) = self.load_history() {
+                self.push_notif(Notification::from(&err));
+            }
+        }
+        // Changes on disk to a buffer with edits are only checked once
+        // it's switched to.
+        match open {
+            Some(_) => self.check_file(),
+            None => self.check_swap(),
+        }
+    }
+
+    /// Start over what depends on the file being edited, after it changed.
+    fn reload_file_state(&mut self) -> Result<()> {
+        self.reset_file_state();
+        if let Err(err) = self.refresh_git() {
+            self.push_notif(Notification::from(&err));
+        }
+        self.start_lsp()?;
+
+        Ok(())
+    }
+
+    /// Set what belongs to the file being edited aside with its buffer, to
+    /// be restored when it's switched back to. Its language server is kept
+    /// running, and told about edits made meanwhile once it's back.
+    fn stash_file_state(&mut self) {
+        let state = FileState {
+            git: self.git.take(),
+            hunk_preview: self.hunk_preview,
+            blame: mem::take(&mut self.blame),
+            lsp: self.lsp.take(),
+            diagnostics: mem::take(&mut self.diagnostics),
+        };
+        self.file_states
+            .insert(self.buffer.file_name().into(), state);
+        self.reset_file_state();
+    }
+
+    fn restore_file_state(&mut self, state: FileState) {
+        let FileState {
+            git,
+            hunk_preview,
+            blame,
+            lsp,
+            diagnostics,
+        } = state;
+        self.git = git;
+        self.hunk_preview = hunk_preview;
+        self.blame = blame;
+        self.lsp = lsp;
+        self.diagnostics = diagnostics;
+    }
+
+    /// Drop everything that belonged to the file that was being edited.
+    fn reset_file_state(&mut self) {
+        if let Some(session) = self.lsp.as_mut() {
+            session.close();
+        }
+        self.lsp = None;
+        self.diagnostics = Diagnostics::default();
+        self.blame = BlameState::Off;
+        self.hunk_preview = false;
+        self.popup = None;
+        self.completion = None;
+        self.tab_stops = None;
+        self.scroll = 0;
+        if self.mode == Mode::Visual {
+            self.enter_mode(Mode::Normal);
+        }
+    }
+
+    pub fn grep(&self) -> Option<&Grep> {
+        self.grep.as_ref()
+    }
+
+    pub fn grep_mut(&mut self) -> Option<&mut Grep> {
+        self.grep.as_mut()
+    }
+
+    /// Search the files under `root` for `pattern`, listing the matches as
+    /// they are found.
+    pub fn start_grep(&mut self, pattern: &str, root: &Path) -> Result<()> {
+        self.grep = Some(Grep::new(pattern, root, self.waker.clone())?);
+        self.grep_list = true;
+        self.diagnostic_list = false;
+
+        Ok(())
+    }
+
+    pub fn grep_list(&self) -> bool {
+        self.grep_list
+    }
+
+    pub fn toggle_grep_list(&mut self) -> Result<()> {
+        if self.grep.is_none() {
+            return Err(Error::NoSearch);
+        }
+        self.grep_list = !self.grep_list;
+        self.diagnostic_list = false;
+
+        Ok(())
+    }
+
+    pub fn close_grep_list(&mut self) {
+        self.grep_list = false
+    }
+
+    /// Open the file of the match selected in the list, at the match.
+    pub fn open_selected_match(&mut self) -> Result<()> {
+        let grep = self.grep.as_mut().ok_or(Error::NoSearch)?;
+        let found = grep.visit_selected().ok_or(Error::NoMoreMatches)?;
+        let found = found.clone();
+        self.grep_list = false;
+
+        self.open_match(&found)
+    }
+
+    /// Jump to the match after the one last jumped to.
+    pub fn move_next_match(&mut self) -> Result<()> {
+        let grep = self.grep.as_mut().ok_or(Error::NoSearch)?;
+        let found = grep.visit_next().ok_or(Error::NoMoreMatches)?.clone();
+
+        self.open_match(&found)
+    }
+
+    /// Jump to the match before the one last jumped to.
+    pub fn move_prev_match(&mut self) -> Result<()> {
+        let grep = self.grep.as_mut().ok_or(Error::NoSearch)?;
+        let found = grep.visit_prev().ok_or(Error::NoMoreMatches)?.clone();
+
+        self.open_match(&found)
+    }
+
+    fn open_match(&mut self, found: &Match) -> Result<()> {
+        let root = self.grep.as_ref().map(|grep| grep.root().to_path_buf());
+        self.open_file(&root.unwrap_or_default().join(&*found.path))?;
+
+        self.move_to_pos(Pos::new(found.line_idx, found.col))
+    }
+
+    pub fn explorer(&self) -> Option<&Explorer> {
+        self.explorer.as_ref()
+    }
+
+    pub fn explorer_mut(&mut self) -> Option<&mut Explorer> {
+        self.explorer.as_mut()
+    }
+
+    pub fn explorer_focused(&self) -> bool {
+        self.explorer.is_some() && self.explorer_focused
+    }
+
+    /// Open the explorer on the dir of the file, focus it when it is open,
+    /// and close it when it has focus.
+    pub fn toggle_explorer(&mut self) -> Result<()> {
+        match (&self.explorer, self.explorer_focused) {
+            (Some(_), true) => self.explorer = None,
+            (Some(_), false) => self.explorer_focused = true,
+            (None, _) => {
+                let file = Path::new(self.buffer.file_name());
+                let dir = file
+                    .parent()
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                let mut explorer = Explorer::new(dir)?;
+                // A buffer that was never saved has no file to show.
+                _ = explorer.reveal(file);
+                self.explorer = Some(explorer);
+                self.explorer_focused = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Give the keys back to the buffer, leaving the explorer open.
+    pub fn unfocus_explorer(&mut self) {
+        self.explorer_focused = false
+    }
+
+    /// Open the selected file, or expand or collapse the selected dir.
+    pub fn explorer_open(&mut self) -> Result<()> {
+        let Some(explorer) = self.explorer.as_mut() else {
+            return Ok(());
+        };
+        let Some(entry) = explorer.selected() else {
+            return Ok(());
+        };
+        if entry.is_dir {
+            return Ok(explorer.toggle()?);
+        }
+        let path = entry.path.clone();
+        self.open_file(&path)?;
+        self.explorer_focused = false;
+
+        Ok(())
+    }
+
+    /// Ask for the name of a file to create next to the selected entry.
+    pub fn start_create_file(&mut self) {
+        self.open_prompt(PromptAction::CreateFile, String::new())
+    }
+
+    /// Ask for the new name of the selected entry.
+    pub fn start_rename_file(&mut self) {
+        let name = self
+            .explorer
+            .as_ref()
+            .and_then(Explorer::selected)
+            .map(|entry| entry.name())
+            .unwrap_or_default();
+        self.open_prompt(PromptAction::RenameFile, name)
+    }
+
+    /// Ask to confirm deleting the selected entry.
+    pub fn start_delete_file(&mut self) {
+        self.open_prompt(PromptAction::DeleteFile, String::new())
+    }
+
+    fn create_file(&mut self, name: &str) -> Result<()> {
+        let Some(explorer) = self.explorer.as_mut() else {
+            return Ok(());
+        };
+        let path = explorer.create(name)?;
+        self.push_notif(Notification::new(
+            NotificationType::Success,
+            format!("created {}", path.display()),
+        ));
+
+        Ok(self.refresh_git()?)
+    }
+
+    /// Rename the selected entry. Open buffers of the files moved follow
+    /// them.
+    fn rename_file(&mut self, new_name: &str) -> Result<()> {
+        let Some(explorer) = self.explorer.as_mut() else {
+            return Ok(());
+        };
+        // Once moved, the old paths can't be resolved.
+        let buffers =
+            std::iter::once(&mut self.buffer).chain(&mut self.buffers);
+        let paths: Vec<(&mut Buffer, Option<PathBuf>)> = buffers
+            .map(|buffer| {
+                let path = Path::new(buffer.file_name()).canonicalize().ok();
+                (buffer, path)
+            })
+            .collect();
+        let windows: Vec<(&mut Window, Option<PathBuf>)> = self
+            .windows
+            .iter_mut()
+            .map(|window| {
+                let path = Path::new(&*window.file_name).canonicalize().ok();
+                (window, path)
+            })
+            .collect();
+        let (from, to) = explorer.rename(new_name)?;
+
+        let mut moved_current = false;
+        let mut moved = vec![];
+        for (idx, (buffer, path)) in paths.into_iter().enumerate() {
+            if let Some(path) = moved_path(path.as_deref(), &from, &to) {
+                // Its language server knows it by the old path.
+                if let Some(state) = self.file_states.remove(buffer.file_name())
+                {
+                    state.close();
+                    buffer.track_changes(false);
+                }
+                buffer.set_file_name(path.to_string_lossy().into());
+                moved_current |= idx == 0;
+                moved.push(path);
+            }
+        }
+        for (window, path) in windows {
+            if let Some(path) = moved_path(path.as_deref(), &from, &to) {
+                window.file_name = path.to_string_lossy().into();
+            }
+        }
+        for path in moved {
+            self.watch_file(&path)?;
+        }
+        self.push_notif(Notification::new(
+            NotificationType::Success,
+            format!("renamed to {}", to.display()),
+        ));
+        match moved_current {
+            true => self.reload_file_state(),
+            false => Ok(self.refresh_git()?),
+        }
+    }
+
+    fn delete_file(&mut self) -> Result<()> {
+        let Some(explorer) = self.explorer.as_mut() else {
+            return Ok(());
+        };
+        let path = explorer.delete()?;
+        self.push_notif(Notification::new(
+            NotificationType::Success,
+            format!("deleted {}", path.display()),
+        ));
+
+        Ok(self.refresh_git()?)
+    }
+
+    pub fn windows(&self) -> &Windows {
+        &self.windows
+    }
+
+    /// The buffer a window shows.
+    pub fn window_buffer(&self, window: &Window) -> Option<&Buffer> {
+        std::iter::once(&self.buffer)
+            .chain(&self.buffers)
+            .find(|buffer| buffer.file_name() == &*window.file_name)
+    }
+
+    /// Split the focused window in two, both onto its buffer, and focus the
+    /// new one.
+    pub fn split_window(&mut self, split: Split) {
+        self.store_window();
+        let window = self.windows.window(self.windows.focused()).cloned();
+        let id = self.windows.split(split, window.unwrap_or_default());
+        self.windows.focus(id);
+    }
+
+    /// Focus the window next to the focused one in `direction`.
+    pub fn focus_window(&mut self, direction: window::Direction) -> Result<()> {
+        let id = self.windows.neighbour(direction).ok_or(Error::NoWindow)?;
+        self.store_window();
+        self.windows.focus(id);
+
+        self.load_window()
+    }
+
+    /// Close the focused window, unless it is the last.
+    pub fn close_window(&mut self) -> Result<()> {
+        self.store_window();
+        self.windows.close().ok_or(Error::LastWindow)?;
+
+        self.load_window()
+    }
+
+    /// Grow the focused window by `delta` percent of its split, shrinking
+    /// it when negative.
+    pub fn resize_window(&mut self, split: Split, delta: i16) -> Result<()> {
+        match self.windows.resize(split, delta) {
+            true => Ok(()),
+            false => Err(Error::NoSplit),
+        }
+    }
+
+    /// Keep the cursor and scroll of the focused window for when it is
+    /// focused again.
+    fn store_window(&mut self) {
+        let id = self.windows.focused();
+        if let Some(window) = self.windows.window_mut(id) {
+            window.file_name = self.buffer.file_name().into();
+            window.cursor = self.buffer.cursor;
+            window.scroll = self.scroll;
+        }
+    }
+
+    /// Switch to the buffer, cursor and scroll of the focused window.
+    fn load_window(&mut self) -> Result<()> {
+        let id = self.windows.focused();
+        let Some(window) = self.windows.window(id).cloned() else {
+            return Ok(());
+        };
+        if window.file_name != self.buffer.file_name().into() {
+            self.open_file(Path::new(&*window.file_name))?;
+        }
+        self.buffer.cursor = self.buffer.clamp(window.cursor);
+        self.scroll = window.scroll.min(self.buffer.cursor.y);
+
+        Ok(())
+    }
+
+    /// Focus the window `id` and put its cursor as close to `pos` as it can
+    /// go, as clicked. A selection is dropped.
+    pub fn click(&mut self, id: usize, pos: Cursor) -> Result<()> {
+        if self.mode != Mode::Insert {
+            self.enter_mode(Mode::Normal);
+        }
+        if id != self.windows.focused() {
+            self.store_window();
+            self.windows.focus(id);
+            self.load_window()?;
+        }
+        self.buffer.cursor = self.clamp_to_text(pos);
+
+        Ok(())
+    }
+
+    /// Select from the cursor to `pos`, as dragged to, entering visual mode.
+    pub fn drag(&mut self, pos: Cursor) {
+        let pos = self.clamp_to_text(pos);
+        if self.mode != Mode::Visual {
+            // A click that didn't move selects nothing.
+            if pos == self.buffer.cursor {
+                return;
+            }
+            self.enter_mode(Mode::Visual);
+        }
+        self.buffer.cursor = pos;
+    }
+
+    /// Scroll the window `id` by `delta` lines, down when positive. The
+    /// cursor is taken along so it stays on screen.
+    pub fn scroll_window(&mut self, id: usize, delta: isize) {
+        if id == self.windows.focused() {
+            let last = self.buffer.len_lines() - 1;
+            let y = self.buffer.cursor.y.saturating_add_signed(delta).min(last);
+            self.scroll = self.scroll.saturating_add_signed(delta).min(y);
+            self.buffer.cursor =
+                self.clamp_to_text((self.buffer.cursor.x, y).into());
+            return;
+        }
+
+        let Some(window) = self.windows.window(id) else {
+            return;
+        };
+        let Some(buffer) = self.window_buffer(window) else {
+            return;
+        };
+        let last = buffer.len_lines() - 1;
+        let y = window.cursor.y.saturating_add_signed(delta).min(last);
+        let scroll = window.scroll.saturating_add_signed(delta).min(y);
+        let cursor = buffer.clamp((window.cursor.x, y).into());
+        if let Some(window) = self.windows.window_mut(id) {
+            window.cursor = cursor;
+            window.scroll = scroll;
+        }
+    }
+
+    /// The nearest position to `pos` the cursor can be on in this mode.
+    /// Only insert mode goes past the last char of a line.
+    fn clamp_to_text(&self, pos: Cursor) -> Cursor {
+        let y = pos.y.min(self.buffer.len_lines() - 1);
+        let len = self.buffer.line(y).map_or(0, |line| line.visual_len());
+        let last = match self.mode {
+            Mode::Insert => len,
+            _ => len.saturating_sub(1),
+        };
+
+        (pos.x.min(last), y).into()
+    }
+
+    /// The chars between where visual mode started and the cursor.
+    pub fn selection(&self) -> Option<Selection> {
+        let anchor = self.anchor?;
+        let anchor = Pos::new(anchor.y, anchor.x);
+        let cursor = self.cursor_pos();
+
+        Some(Selection {
+            start: anchor.min(cursor),
+            end: anchor.max(cursor),
+        })
+    }
+
+    /// Delete the selection and go back to normal mode, with the cursor
+    /// where the selection started.
+    pub fn delete_selection(&mut self) -> Result<()> {
+        let selection = self.selection().ok_or(Error::NoSelection)?;
+        if self.buffer.is_empty() {
+            return Err(Error::NoCharsInFile);
+        }
+        let start = self.buffer.char_idx_line_start(selection.start.line)?
+            + selection.start.col;
+        let end = self.buffer.char_idx_line_start(selection.end.line)?
+            + selection.end.col;
+        let end = end.min(self.buffer.len() - 1);
+        self.buffer.remove(start.min(end)..=end)?;
+        self.enter_mode(Mode::Normal);
+
+        self.move_to_pos(selection.start)
+    }
+
+    pub fn set_waker(&mut self, waker: Waker) {
+        self.waker = waker
+    }
+
+    /// Pick up whatever background work has finished.
+    pub fn update_background(&mut self) {
+        self.update_blame();
+        self.update_lsp();
+        if let Some(picker) = self.picker.as_mut() {
+            picker.update();
+        }
+        if let Some(grep) = self.grep.as_mut() {
+            grep.update();
+        }
+        self.update_watcher();
+    }
+
+    /// Watch the open files for changes made to them by other programs.
+    pub fn watch_files(&mut self) -> Result<()> {
+        let mut watcher = Watcher::new(self.waker.clone())?;
+        for buffer in std::iter::once(&self.buffer).chain(&self.buffers) {
+            watcher.watch(Path::new(buffer.file_name()))?;
+        }
+        self.watcher = Some(watcher);
+
+        Ok(())
+    }
+
+    /// Compare the file with what was last loaded or saved of it. Without
+    /// edits the buffer is reloaded, with them what to do is asked.
+    pub fn check_file(&mut self) -> Result<()> {
+        let disk = match fs::read_to_string(self.buffer.file_name()) {
+            Ok(disk) => disk,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                self.push_notif(Notification::new(
+                    NotificationType::Warning,
+                    format!("{} was deleted", self.buffer.file_name()),
+                ));
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if self.buffer.is_saved_as(&disk) {
+            return Ok(());
+        }
+        if self.buffer.is_modified() {
+            self.open_prompt(PromptAction::FileChanged, String::new());
+            return Ok(());
+        }
+
+        self.reload_file(&disk)
+    }
+
+    /// Take the file as it is on disk, dropping any edits.
+    fn reload_file(&mut self, disk: &str) -> Result<()> {
+        reload(&mut self.buffer, disk)?;
+        self.remove_swap()?;
+        self.buffer.cursor = self.clamp_to_text(self.buffer.cursor);
+        self.after_reload();
+        if let Err(err) = self.save_history() {
+            self.push_notif(Notification::from(&err));
+        }
+        self.push_notif(Notification::new(
+            NotificationType::Info,
+            format!("reloaded {}", self.buffer.file_name()),
+        ));
+
+        Ok(())
+    }
+
+    /// Merge the edits to the buffer with the changes made on disk. Where
+    /// both changed the same lines, all three versions are left between
+    /// conflict markers, and the cursor is put on the first.
+    fn merge_file(&mut self, disk: &str) -> Result<()> {
+        let contents = self.buffer.contents();
+        let merge = diff::merge(
+            &self.buffer.saved(),
+            &contents,
+            disk,
+            ["buffer", "saved", "disk"],
+        );
+        let y = match merge.conflicts.first() {
+            Some(&line_idx) => line_idx,
+            None => {
+                diff::map_line(&contents, &merge.text, self.buffer.cursor.y)
+            }
+        };
+        self.buffer.set_text(&merge.text)?;
+        self.buffer.set_saved(disk);
+        self.buffer.cursor = self.clamp_to_text(Cursor {
+            y,
+            ..self.buffer.cursor
+        });
+        self.after_reload();
+
+        let notif = match merge.conflicts.len() {
+            0 => Notification::new(
+                NotificationType::Success,
+                "merged the changes on disk",
+            ),
+            conflicts => Notification::new(
+                NotificationType::Warning,
+                format!("merged with {} conflicts", conflicts),
+            ),
+        };
+        self.push_notif(notif);
+
+        Ok(())
     }
 
-    pub fn running_state(&self) -> RunningState {
-        self.running_state
+    /// Show how the buffer differs from `other`, named `name`, then ask
+    /// again what to do about it.
+    fn show_diff(&mut self, name: &str, other: &str, action: PromptAction) {
+        let contents = self.buffer.contents();
+        self.popup = Some(Popup {
+            title: format!("buffer and {}", name).into(),
+            lines: unified_diff(("buffer", &contents), (name, other)),
+        });
+        self.open_prompt(action, String::new());
     }
 
-    pub fn notifs(&self) -> &[Notification] {
-        self.notifications.as_slice()
+    /// Show the three ways the file went since it was last saved: what the
+    /// buffer changed of it, then what was changed on disk, then ask again
+    /// what to do about it.
+    fn show_changes(&mut self, disk: &str) {
+        let saved = self.buffer.saved();
+        let contents = self.buffer.contents();
+        let mut lines = unified_diff(("saved", &saved), ("buffer", &contents));
+        lines.extend(unified_diff(("saved", &saved), ("disk", disk)));
+        self.popup = Some(Popup {
+            title: "saved, buffer and disk".into(),
+            lines,
+        });
+        self.open_prompt(PromptAction::FileChanged, String::new());
+    }
+
+    /// Drop whatever was drawn from the old text of the buffer.
+    fn after_reload(&mut self) {
+        if self.mode == Mode::Visual {
+            self.enter_mode(Mode::Normal);
+        }
+        self.completion = None;
+        self.tab_stops = None;
+        if !matches!(self.blame, BlameState::Off) {
+            self.start_blame();
+        }
+    }
+
+    /// Write swap files of the buffers with edits to `swap`, or stop
+    /// writing them with `None`.
+    pub fn set_swap_dir(&mut self, swap: Option<SwapDir>) {
+        self.swap = swap
+    }
+
+    /// Time left until the swap files are next written, while a buffer has
+    /// edits to write.
+    pub fn swap_remaining(&self) -> Option<Duration> {
+        let interval = self.config.swap_interval;
+        let modified = std::iter::once(&self.buffer)
+            .chain(&self.buffers)
+            .any(Buffer::is_modified);
+        if self.swap.is_none() || interval == 0 || !modified {
+            return None;
+        }
+
+        let since = self.swapped.map_or(Duration::MAX, |at| at.elapsed());
+        Some(Duration::from_millis(interval).saturating_sub(since))
+    }
+
+    /// Write the text of each buffer with edits to its swap file, and its
+    /// undo history to the journal beside it.
+    pub fn write_swaps(&mut self) {
+        let Some(swap) = self.swap.as_ref() else {
+            return;
+        };
+        let written = std::iter::once(&self.buffer)
+            .chain(&self.buffers)
+            .filter(|buffer| buffer.is_modified())
+            .try_for_each(|buffer| write_swap(swap, buffer));
+        self.swapped = Some(Instant::now());
+        if let Err(err) = written {
+            self.push_notif(Notification::from(&err));
+        }
+    }
+
+    fn remove_swap(&mut self) -> Result<()> {
+        if let Some(swap) = self.swap.as_ref() {
+            swap.remove(Path::new(self.buffer.file_name()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Offer to recover edits to the file from a swap file, left by a crash
+    /// since the file was last saved.
+    pub fn check_swap(&mut self) -> Result<()> {
+        let Some(swap) = self.swap.as_ref() else {
+            return Ok(());
+        };
+        let Some(text) = swap.find(Path::new(self.buffer.file_name()))? else {
+            return Ok(());
+        };
+        match self.buffer.is_saved_as(&text) {
+            true => self.remove_swap(),
+            false => {
+                self.open_prompt(PromptAction::Recover, String::new());
+                Ok(())
+            }
+        }
+    }
+
+    /// Act on the answer to whether to recover from the swap file.
+    fn recover(&mut self, answer: &str) -> Result<()> {
+        let Some(swap) = self.swap.as_ref() else {
+            return Ok(());
+        };
+        let Some(text) = swap.find(Path::new(self.buffer.file_name()))? else {
+            return Ok(());
+        };
+        match answer {
+            "r" | "R" => {
+                let path = Path::new(self.buffer.file_name());
+                let journal = swap.journal().load(path, &text)?;
+                let cursor = self.buffer.cursor;
+                self.buffer.set_text(&text)?;
+                // Edits before the crash can be undone as they were made.
+                if !journal.is_empty() {
+                    self.buffer.set_undo_tree(journal);
+                }
+                self.buffer.cursor = self.clamp_to_text(cursor);
+                self.after_reload();
+                self.push_notif(Notification::new(
+                    NotificationType::Success,
+                    format!("recovered {}", self.buffer.file_name()),
+                ));
+            }
+            "d" | "D" => self.show_diff("swap", &text, PromptAction::Recover),
+            "x" | "X" => {
+                self.remove_swap()?;
+                self.push_notif(Notification::new(
+                    NotificationType::Info,
+                    "discarded the swap file",
+                ));
+            }
+            // Asked again next time the file is opened.
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn watch_file(&mut self, path: &Path) -> Result<()> {
+        if let Some(watcher) = self.watcher.as_mut() {
+            watcher.watch(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pick up the files changed by other programs. Buffers in the
+    /// background without edits are reloaded, those with edits are checked
+    /// again once they're switched to.
+    fn update_watcher(&mut self) {
+        let Some(watcher) = self.watcher.as_ref() else {
+            return;
+        };
+        for path in watcher.changed() {
+            if is_watched_as(&self.buffer, &path) {
+                if let Err(err) = self.check_file() {
+                    self.push_notif(Notification::from(&err));
+                }
+                continue;
+            }
+            let buffer = self
+                .buffers
+                .iter_mut()
+                .find(|buffer| is_watched_as(buffer, &path));
+            let Some(buffer) = buffer else {
+                continue;
+            };
+            // Buffers of deleted files are kept, to be saved back.
+            let Ok(disk) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if buffer.is_saved_as(&disk) {
+                continue;
+            }
+            let notif = match buffer.is_modified() {
+                true => Notification::new(
+                    NotificationType::Warning,
+                    format!("{} changed on disk", buffer.file_name()),
+                ),
+                false => match reload(buffer, &disk).and_then(|_| {
+                    keep_history(self.history_store.as_ref(), buffer)
+                }) {
+                    Ok(()) => Notification::new(
+                        NotificationType::Info,
+                        format!("reloaded {}", buffer.file_name()),
+                    ),
+                    Err(err) => Notification::from(&err),
+                },
+            };
+            self.push_notif(notif);
+        }
     }
 
-    pub fn push_notif(&mut self, msg: Notification) {
-        self.notifications.push(msg)
+    /// Write the buffer to disk and refresh anything that depends on the file.
+    pub fn save(&mut self) -> Result<()> {
+        // Don't write over changes made since the file was last read.
+        if let Ok(disk) = fs::read_to_string(self.buffer.file_name()) {
+            if !self.buffer.is_saved_as(&disk) {
+                self.open_prompt(PromptAction::FileChanged, String::new());
+                return Err(Error::ChangedOnDisk);
+            }
+        }
+        self.buffer.save()?;
+        self.push_notif(Notification::new(
+            NotificationType::Success,
+            format!("written {}", self.buffer.file_name()),
+        ));
+        if let Err(err) = self.remove_swap() {
+            self.push_notif(Notification::from(&err));
+        }
+        if let Err(err) = self.save_history() {
+            self.push_notif(Notification::from(&err));
+        }
+        if let Err(err) = self.refresh_git() {
+            self.push_notif(Notification::from(&err));
+        }
+        if !matches!(self.blame, BlameState::Off) {
+            self.start_blame();
+        }
+
+        Ok(())
     }
 
     pub fn quit(&mut self) {
+        if let Some(session) = self.lsp.as_mut() {
+            session.close();
+        }
+        for (_, state) in self.file_states.drain() {
+            state.close();
+        }
+        // Edits left unsaved stay in their swaps, up to date, to be
+        // recovered when the file is next opened.
+        if let Some(swap) = self.swap.as_ref() {
+            for buffer in std::iter::once(&self.buffer).chain(&self.buffers) {
+                _ = match buffer.is_modified() {
+                    true => write_swap(swap, buffer),
+                    false => swap
+                        .remove(Path::new(buffer.file_name()))
+                        .map_err(Error::from),
+                };
+            }
+        }
+        // The history of unsaved edits doesn't lead up to the file, it's
+        // kept in their journals instead.
+        let buffers = std::iter::once(&self.buffer)
+            .chain(&self.buffers)
+            .filter(|buffer| !buffer.is_modified());
+        for buffer in buffers {
+            _ = keep_history(self.history_store.as_ref(), buffer);
+        }
         self.running_state = RunningState::Done
     }
 
@@ -51,6 +2095,105 @@ impl App {
         Ok(())
     }
 
+    /// Insert pasted text before the cursor as one edit, and one step to
+    /// undo apart from what was typed around it, leaving the cursor after
+    /// it. Line breaks are made to match the buffer's.
+    pub fn paste(&mut self, text: &str) -> Result<()> {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let text = match self.buffer.line_ending() {
+            LineEnding::Lf => text,
+            LineEnding::Crlf => text.replace('\n', "\r\n"),
+        };
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let cursor = self.buffer.cursor;
+        let char_idx = match self.buffer.on_rope_tail(cursor.into()) {
+            true => self.buffer.len(),
+            false => self.buffer.char_idx_under_pos(cursor.into())?,
+        };
+        self.buffer.end_undo_step();
+        self.buffer.insert(char_idx, &text)?;
+        self.buffer.end_undo_step();
+
+        self.move_to_char(char_idx + text.chars().count())
+    }
+
+    /// Take back the latest `count` steps of edits, leaving the cursor where
+    /// the last of them was.
+    pub fn undo(&mut self, count: usize) -> Result<()> {
+        self.travel(count, Buffer::undo, Error::NothingToUndo)
+    }
+
+    /// Make again the latest `count` steps of edits undone, leaving the
+    /// cursor where the last of them was.
+    pub fn redo(&mut self, count: usize) -> Result<()> {
+        self.travel(count, Buffer::redo, Error::NothingToRedo)
+    }
+
+    /// Go back `count` changes in time, into undone branches too.
+    pub fn earlier(&mut self, count: usize) -> Result<()> {
+        self.travel(count, Buffer::earlier, Error::NothingToUndo)
+    }
+
+    /// Go forward `count` changes in time, into undone branches too.
+    pub fn later(&mut self, count: usize) -> Result<()> {
+        self.travel(count, Buffer::later, Error::NothingToRedo)
+    }
+
+    /// Take `count` steps through the undo tree, as far as they go, or fail
+    /// with `none` when not one could be taken.
+    fn travel(
+        &mut self,
+        count: usize,
+        step: fn(&mut Buffer) -> buffer::BufferResult<Option<usize>>,
+        none: Error,
+    ) -> Result<()> {
+        let mut position = None;
+        for _ in 0..count {
+            match step(&mut self.buffer)? {
+                Some(char_idx) => position = Some(char_idx),
+                None => break,
+            }
+        }
+        let char_idx = position.ok_or(none)?;
+
+        self.after_reload();
+        self.move_to_char(char_idx.min(self.buffer.len()))?;
+        self.buffer.cursor = self.clamp_to_text(self.buffer.cursor);
+
+        Ok(())
+    }
+
+    /// Undo what's edited from now on apart from what was edited before.
+    pub fn end_undo_step(&mut self) {
+        self.buffer.end_undo_step()
+    }
+
+    /// Keep the undo history of files in `store` between sessions, or only
+    /// for the session with `None`.
+    pub fn set_history_store(&mut self, store: Option<HistoryStore>) {
+        self.history_store = store
+    }
+
+    /// Pick up the undo history the file was left with, when it still leads
+    /// up to the text of the buffer.
+    pub fn load_history(&mut self) -> Result<()> {
+        let Some(store) = self.history_store.as_ref() else {
+            return Ok(());
+        };
+        let path = Path::new(self.buffer.file_name());
+        let tree = store.load(path, &self.buffer.contents())?;
+        self.buffer.set_undo_tree(tree);
+
+        Ok(())
+    }
+
+    fn save_history(&mut self) -> Result<()> {
+        keep_history(self.history_store.as_ref(), &self.buffer)
+    }
+
     pub fn delete_lines(&mut self, count: usize) -> Result<()> {
         if count == 0 {
             return Ok(());
@@ -169,6 +2312,14 @@ impl App {
     }
 
     pub fn enter_mode(&mut self, mode: Mode) {
+        if mode != Mode::Insert {
+            self.completion = None;
+            self.tab_stops = None;
+        }
+        self.anchor = match mode {
+            Mode::Visual => self.anchor.or(Some(self.buffer.cursor)),
+            _ => None,
+        };
         self.mode = mode
     }
 
@@ -405,6 +2556,172 @@ impl App {
     }
 }
 
+/// Is the buffer of the file known to the watcher as `path`.
+fn is_watched_as(buffer: &Buffer, path: &Path) -> bool {
+    watch::watched_path(Path::new(buffer.file_name()))
+        .is_ok_and(|watched| watched == path)
+}
+
+/// Replace the buffer's text with `disk`, read from its file, keeping the
+/// cursor on the same line, wherever that line moved to.
+fn reload(buffer: &mut Buffer, disk: &str) -> Result<()> {
+    let y = diff::map_line(&buffer.contents(), disk, buffer.cursor.y);
+    let cursor = Cursor { y, ..buffer.cursor };
+    buffer.set_text(disk)?;
+    buffer.set_saved(disk);
+    buffer.cursor = buffer.clamp(cursor);
+
+    Ok(())
+}
+
+/// Keep the undo tree of the buffer in `store`, if there is one, as at its
+/// text. Buffers without history drop what was kept.
+fn keep_history(store: Option<&HistoryStore>, buffer: &Buffer) -> Result<()> {
+    let Some(store) = store else {
+        return Ok(());
+    };
+    let path = Path::new(buffer.file_name());
+    match buffer.undo_tree() {
+        tree if tree.is_empty() => store.remove(path)?,
+        tree => store.save(path, &buffer.contents(), &tree)?,
+    }
+
+    Ok(())
+}
+
+/// Write the text of the buffer to its swap, and its undo history to the
+/// journal beside it.
+fn write_swap(swap: &SwapDir, buffer: &Buffer) -> Result<()> {
+    let (path, contents) = (Path::new(buffer.file_name()), buffer.contents());
+    swap.write(path, &contents)?;
+    swap.journal().save(path, &contents, &buffer.undo_tree())?;
+
+    Ok(())
+}
+
+/// Does the buffer hold the file at `path`.
+fn is_file_of(buffer: &Buffer, path: &Path) -> bool {
+    let file_name = Path::new(buffer.file_name());
+    match (file_name.canonicalize(), path.canonicalize()) {
+        (Ok(file_name), Ok(path)) => file_name == path,
+        _ => file_name == path,
+    }
+}
+
+/// Unified diff lines from `old` to `new`, each with its name.
+fn unified_diff(old: (&str, &str), new: (&str, &str)) -> Vec<String> {
+    let diff = similar::TextDiff::from_lines(old.1, new.1);
+
+    diff.unified_diff()
+        .header(old.0, new.0)
+        .to_string()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+/// Where `path` is after `from` was moved to `to`, when it was in it.
+fn moved_path(path: Option<&Path>, from: &Path, to: &Path) -> Option<PathBuf> {
+    let rest = path?.strip_prefix(from).ok()?;
+    // Joining an empty path would leave a trailing '/'.
+    match rest.as_os_str().is_empty() {
+        true => Some(to.to_path_buf()),
+        false => Some(to.join(rest)),
+    }
+}
+
+/// The notification history while it is open.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct History {
+    /// Only notifications containing it are listed, ignoring case.
+    pub query: String,
+    pub selected: usize,
+}
+
+/// The chars selected in visual mode, from `start` to `end` both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Selection {
+    /// Chars of the line `line_idx`, `line_len` chars long, that are
+    /// selected. Whole lines take their '\n' too.
+    pub fn cols(
+        &self,
+        line_idx: usize,
+        line_len: usize,
+    ) -> Option<Range<usize>> {
+        if line_idx < self.start.line || line_idx > self.end.line {
+            return None;
+        }
+        let start = match line_idx == self.start.line {
+            true => self.start.col,
+            false => 0,
+        };
+        let end = match line_idx == self.end.line {
+            true => self.end.col + 1,
+            false => line_len + 1,
+        };
+
+        Some(start..end)
+    }
+}
+
+/// Text drawn over the text below the cursor line until the next key press,
+/// such as hover information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Popup {
+    pub title: Box<str>,
+    pub lines: Vec<String>,
+}
+
+/// A line of input typed into the message bar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+    pub action: PromptAction,
+    pub input: String,
+}
+
+/// What a prompt's input is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptAction {
+    Rename,
+    Grep,
+    /// A command, by the name it is bound with in the config.
+    Command,
+    CreateFile,
+    RenameFile,
+    /// Confirms with `y`.
+    DeleteFile,
+    /// The file changed while the buffer has edits: `r` reloads it, `m`
+    /// merges the two, `d` shows the diff and anything else keeps the edits.
+    FileChanged,
+    /// A swap file has edits to the file: `r` recovers them, `d` shows the
+    /// diff and `x` discards them.
+    Recover,
+}
+
+impl Display for PromptAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rename => write!(f, "rename"),
+            Self::Grep => write!(f, "grep"),
+            Self::Command => write!(f, "command"),
+            Self::CreateFile => write!(f, "new file"),
+            Self::RenameFile => write!(f, "rename to"),
+            Self::DeleteFile => write!(f, "delete? (y/n)"),
+            Self::FileChanged => {
+                write!(f, "changed on disk: (r)eload/(m)erge/(d)iff/(k)eep")
+            }
+            Self::Recover => {
+                write!(f, "swap file found: (r)ecover/(d)iff/(x) discard")
+            }
+        }
+    }
+}
+
 /// Soft errors that are displayed in the status line.
 #[derive(Debug)]
 pub enum Error {
@@ -431,6 +2748,57 @@ pub enum Error {
     LineEmpty,
     KeyUnmapped,
     CountRedundant,
+    NoHunk,
+    NoMoreHunks,
+    NoLanguageServer,
+    NoDefinition,
+    NoMoreDiagnostics,
+    NoCompletions,
+    NoFileSelected,
+    NoSearch,
+    NoMoreMatches,
+    NoWindow,
+    NoSplit,
+    LastWindow,
+    NoSelection,
+    ChangedOnDisk,
+    NothingToUndo,
+    NothingToRedo,
+    Io(io::Error),
+    Git(git::Error),
+    Lsp(lsp::Error),
+    Grep(grep::Error),
+    History(ActionError),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<git::Error> for Error {
+    fn from(err: git::Error) -> Self {
+        Error::Git(err)
+    }
+}
+
+impl From<lsp::Error> for Error {
+    fn from(err: lsp::Error) -> Self {
+        Error::Lsp(err)
+    }
+}
+
+impl From<grep::Error> for Error {
+    fn from(err: grep::Error) -> Self {
+        Error::Grep(err)
+    }
+}
+
+impl From<ActionError> for Error {
+    fn from(err: ActionError) -> Self {
+        Error::History(err)
+    }
 }
 
 impl From<buffer::Error> for Error {
@@ -475,19 +2843,41 @@ impl Display for Error {
             Self::LineEmpty => write!(f, "No chars in line"),
             Self::KeyUnmapped => write!(f, "Key unmapped"),
             Self::CountRedundant => write!(f, "Count redundant"),
+            Self::NoHunk => write!(f, "No hunk under cursor"),
+            Self::NoMoreHunks => write!(f, "No more hunks"),
+            Self::NoLanguageServer => write!(f, "No language server"),
+            Self::NoDefinition => write!(f, "No definition found"),
+            Self::NoMoreDiagnostics => write!(f, "No more diagnostics"),
+            Self::NoCompletions => write!(f, "No completions"),
+            Self::NoFileSelected => write!(f, "No file selected"),
+            Self::NoSearch => write!(f, "No search"),
+            Self::NoMoreMatches => write!(f, "No more matches"),
+            Self::NoWindow => write!(f, "No window there"),
+            Self::NoSplit => write!(f, "No split to resize"),
+            Self::LastWindow => write!(f, "Can't close the last window"),
+            Self::NoSelection => write!(f, "Nothing selected"),
+            Self::ChangedOnDisk => write!(f, "File changed on disk"),
+            Self::NothingToUndo => write!(f, "Nothing to undo"),
+            Self::NothingToRedo => write!(f, "Nothing to redo"),
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Git(err) => write!(f, "{}", err),
+            Self::Lsp(err) => write!(f, "{}", err),
+            Self::Grep(err) => write!(f, "{}", err),
+            Self::History(err) => write!(f, "{}", err),
         }
     }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Mode {
     #[default]
     Normal,
     Insert,
     GoTo,
     Delete,
+    Visual,
 }
 
 impl Display for Mode {
@@ -499,17 +2889,6 @@ impl Display for Mode {
     }
 }
 
-impl Mode {
-    pub fn color(&self) -> Style {
-        match self {
-            Self::Normal => Style::default().fg(Color::White).bg(Color::Blue),
-            Self::GoTo => Style::default().fg(Color::White).bg(Color::Black),
-            Self::Insert => Style::default().fg(Color::White).bg(Color::Green),
-            Self::Delete => Style::default().fg(Color::White).bg(Color::Red),
-        }
-    }
-}
-
 #[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub enum RunningState {
     #[default]
@@ -521,84 +2900,168 @@ pub enum RunningState {
 pub struct Notification {
     message_type: NotificationType,
     text: Box<str>,
+    /// Times it was pushed again right after itself.
+    repeats: usize,
+    /// When it was last pushed, `None` before it is.
+    pushed: Option<Instant>,
 }
 
-impl Display for Notification {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let icon = match self.message_type {
+impl Notification {
+    pub fn new<T: Into<Box<str>>>(
+        message_type: NotificationType,
+        text: T,
+    ) -> Self {
+        Self {
+            message_type,
+            text: text.into(),
+            repeats: 0,
+            pushed: None,
+        }
+    }
+
+    pub fn message_type(&self) -> NotificationType {
+        self.message_type
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self.message_type {
             NotificationType::Info => "󰬐",
             NotificationType::Error => "",
             NotificationType::Warning => "",
             NotificationType::Success => "",
-        };
-        write!(f, "{}  {}", icon, self.text)
+        }
+    }
+
+    /// How many times it was pushed in a row.
+    pub fn count(&self) -> usize {
+        self.repeats + 1
+    }
+
+    /// Is it the same message as `other`, so they can be counted as one.
+    fn repeats(&self, other: &Notification) -> bool {
+        self.message_type == other.message_type && self.text == other.text
+    }
+}
+
+impl Display for Notification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}  {}", self.icon(), self.text)
     }
 }
 
 impl From<&Error> for Notification {
     fn from(err: &Error) -> Self {
         match err {
-            Error::NoMoreLinesToDelete => Self {
-                message_type: NotificationType::Warning,
-                text: "no lines to delete".into(),
-            },
-            Error::CantMoveUp(count, remaining) => Self {
-                message_type: NotificationType::Warning,
-                text: "already at top".into(),
-            },
-            Error::CantMoveDown(count, remaining) => Self {
-                message_type: NotificationType::Warning,
-                text: "already at bottom".into(),
-            },
-            Error::CantMoveLeft(count, remaining) => Self {
-                message_type: NotificationType::Warning,
-                text: "already leftmost".into(),
-            },
-            Error::AlreadyAtStart => Self {
-                message_type: NotificationType::Warning,
-                text: "already at file start".into(),
-            },
-            Error::AlreadyAtEnd => Self {
-                message_type: NotificationType::Warning,
-                text: "already at file end".into(),
-            },
-            Error::AlreadyAtLineStart => Self {
-                message_type: NotificationType::Warning,
-                text: "already at line start".into(),
-            },
-            Error::AlreadyAtLineEnd => Self {
-                message_type: NotificationType::Warning,
-                text: "already at line end".into(),
-            },
-            Error::CursorOutOfBounds => Self {
-                message_type: NotificationType::Error,
-                text: "cursor out of bounds".into(),
-            },
-            Error::NoMoreWordsInLine => Self {
-                message_type: NotificationType::Warning,
-                text: "no more words in line".into(),
-            },
-            Error::LineEmpty => Self {
-                message_type: NotificationType::Warning,
-                text: "no chars in line".into(),
-            },
-            Error::NoCharsInFile => Self {
-                message_type: NotificationType::Warning,
-                text: "no chars in file".into(),
-            },
-            Error::KeyUnmapped => Self {
-                message_type: NotificationType::Warning,
-                text: "key unmapped".into(),
-            },
-            Error::CountRedundant => Self {
-                message_type: NotificationType::Warning,
-                text: "count redundant".into(),
-            },
+            Error::NoMoreLinesToDelete => {
+                Self::new(NotificationType::Warning, "no lines to delete")
+            }
+            Error::CantMoveUp(count, remaining) => {
+                Self::new(NotificationType::Warning, "already at top")
+            }
+            Error::CantMoveDown(count, remaining) => {
+                Self::new(NotificationType::Warning, "already at bottom")
+            }
+            Error::CantMoveLeft(count, remaining) => {
+                Self::new(NotificationType::Warning, "already leftmost")
+            }
+            Error::AlreadyAtStart => {
+                Self::new(NotificationType::Warning, "already at file start")
+            }
+            Error::AlreadyAtEnd => {
+                Self::new(NotificationType::Warning, "already at file end")
+            }
+            Error::AlreadyAtLineStart => {
+                Self::new(NotificationType::Warning, "already at line start")
+            }
+            Error::AlreadyAtLineEnd => {
+                Self::new(NotificationType::Warning, "already at line end")
+            }
+            Error::CursorOutOfBounds => {
+                Self::new(NotificationType::Error, "cursor out of bounds")
+            }
+            Error::NoMoreWordsInLine => {
+                Self::new(NotificationType::Warning, "no more words in line")
+            }
+            Error::LineEmpty => {
+                Self::new(NotificationType::Warning, "no chars in line")
+            }
+            Error::NoCharsInFile => {
+                Self::new(NotificationType::Warning, "no chars in file")
+            }
+            Error::KeyUnmapped => {
+                Self::new(NotificationType::Warning, "key unmapped")
+            }
+            Error::CountRedundant => {
+                Self::new(NotificationType::Warning, "count redundant")
+            }
+            Error::NoHunk => {
+                Self::new(NotificationType::Warning, "no hunk under cursor")
+            }
+            Error::NoMoreHunks => {
+                Self::new(NotificationType::Warning, "no more hunks")
+            }
+            Error::NoLanguageServer => Self::new(
+                NotificationType::Warning,
+                "no language server for this file",
+            ),
+            Error::NoDefinition => {
+                Self::new(NotificationType::Warning, "no definition found")
+            }
+            Error::NoMoreDiagnostics => {
+                Self::new(NotificationType::Warning, "no more diagnostics")
+            }
+            Error::NoCompletions => {
+                Self::new(NotificationType::Warning, "no completions")
+            }
+            Error::NoFileSelected => {
+                Self::new(NotificationType::Warning, "no file selected")
+            }
+            Error::NoSearch => {
+                Self::new(NotificationType::Warning, "nothing was searched for")
+            }
+            Error::NoMoreMatches => {
+                Self::new(NotificationType::Warning, "no more matches")
+            }
+            Error::NoWindow => {
+                Self::new(NotificationType::Warning, "no window there")
+            }
+            Error::NoSplit => {
+                Self::new(NotificationType::Warning, "no split to resize")
+            }
+            Error::LastWindow => Self::new(
+                NotificationType::Warning,
+                "can't close the last window",
+            ),
+            Error::NoSelection => {
+                Self::new(NotificationType::Warning, "nothing selected")
+            }
+            Error::ChangedOnDisk => {
+                Self::new(NotificationType::Warning, "file changed on disk")
+            }
+            Error::NothingToUndo => {
+                Self::new(NotificationType::Warning, "already at oldest change")
+            }
+            Error::NothingToRedo => {
+                Self::new(NotificationType::Warning, "already at newest change")
+            }
+            Error::Io(err) => {
+                Self::new(NotificationType::Error, err.to_string())
+            }
+            Error::Git(err) => Self::from(err),
+            Error::Lsp(err) => Self::from(err),
+            Error::Grep(err) => Self::from(err),
+            Error::History(err) => {
+                Self::new(NotificationType::Error, err.to_string())
+            }
         }
     }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum NotificationType {
     #[default]
     Info,
@@ -1,5 +1,21 @@
+use crate::{
+    action::{
+        delta::{BufferDelta, Delta},
+        undo::UndoTree,
+    },
+    diff::{self, Diff, Hunk},
+    lsp::{Position, Range as LspRange, TextEdit},
+    sign::{Markers, SignKind},
+};
 use ropey::{Rope, RopeSlice};
-use std::{fmt::Display, fs::File, io, ops::RangeBounds, result::Result};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Write},
+    mem,
+    ops::{Bound, Range, RangeBounds},
+    result::Result,
+};
 
 // TODO: account for a change in file name.
 #[derive(Debug, Default)]
@@ -7,6 +23,19 @@ pub struct Buffer {
     file_name: Box<str>,
     rope: Rope,
     pub cursor: Cursor,
+    /// Changes against the file at `HEAD`, when it's in a repository.
+    diff: Option<Diff>,
+    /// Edits not yet sent to the language server, when there is one.
+    changes: Option<Vec<TextEdit>>,
+    /// Edited since it was last loaded or saved.
+    modified: bool,
+    /// The text as it was last loaded or saved, to tell the file apart from
+    /// what was last seen of it.
+    saved: Rope,
+    /// Marks and breakpoints, moved along with their lines as the text is
+    /// edited.
+    markers: Markers,
+    history: UndoTree,
 }
 
 impl Buffer {
@@ -17,8 +46,14 @@ impl Buffer {
         let rope = Rope::from_reader(file)?.remove_last_new_line_char();
         let buf = Buffer {
             file_name,
+            saved: rope.clone(),
+            markers: Markers::default(),
             rope,
             cursor: Default::default(),
+            diff: None,
+            changes: None,
+            modified: false,
+            history: UndoTree::default(),
         };
 
         Ok(buf)
@@ -28,6 +63,107 @@ impl Buffer {
         &self.file_name
     }
 
+    /// Point the buffer at another file, such as after its file was moved.
+    pub fn set_file_name(&mut self, file_name: String) {
+        self.file_name = file_name.into()
+    }
+
+    /// The nearest position to `cursor` inside the text, which may have
+    /// shrunk since the cursor was put there.
+    pub fn clamp(&self, cursor: Cursor) -> Cursor {
+        let y = cursor.y.min(self.len_lines() - 1);
+        let x = cursor.x.min(self.rope.line(y).len_chars());
+
+        Cursor { x, y }
+    }
+
+    /// The text as it would be saved, with the final '\n' added back.
+    pub fn contents(&self) -> String {
+        let mut contents = self.rope.to_string();
+        if !self.is_empty() {
+            contents.push('\n');
+        }
+
+        contents
+    }
+
+    /// Write the buffer to its file, adding back the final '\n' dropped on
+    /// load.
+    pub fn save(&mut self) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(self.file_name.as_ref())?);
+        self.rope.write_to(&mut file)?;
+        if !self.is_empty() {
+            file.write_all(b"\n")?;
+        }
+        file.flush()?;
+        self.modified = false;
+        self.saved = self.rope.clone();
+
+        Ok(())
+    }
+
+    /// The text as it was last loaded or saved, ending in '\n' like
+    /// `contents`.
+    pub fn saved(&self) -> String {
+        let mut saved = self.saved.to_string();
+        if self.saved.len_chars() > 0 {
+            saved.push('\n');
+        }
+
+        saved
+    }
+
+    /// Is `text`, as read from the file, what it was last loaded or saved as.
+    pub fn is_saved_as(&self, text: &str) -> bool {
+        Rope::from_str(text).remove_last_new_line_char() == self.saved
+    }
+
+    /// Take `text`, as read from the file, as what it was last saved as. The
+    /// buffer is modified when its text differs.
+    pub fn set_saved(&mut self, text: &str) {
+        self.saved = Rope::from_str(text).remove_last_new_line_char();
+        self.modified = self.rope != self.saved;
+    }
+
+    /// Replace the whole text with `text`, as read from the file, as a step
+    /// of its own to undo. Marks stay with their lines where those are kept.
+    pub fn set_text(&mut self, text: &str) -> BufferResult<()> {
+        let rope = Rope::from_str(text).remove_last_new_line_char();
+        let (contents, mut markers) = (self.contents(), self.markers.clone());
+        markers.map_lines(|idx| Some(diff::map_line(&contents, text, idx)));
+        self.end_undo_step();
+        self.remove(..)?;
+        self.insert(0, rope.to_string())?;
+        self.end_undo_step();
+        self.markers = markers;
+
+        Ok(())
+    }
+
+    pub fn markers(&self) -> &Markers {
+        &self.markers
+    }
+
+    /// Add the mark or breakpoint to the line, or remove it if it has it.
+    pub fn toggle_marker(&mut self, line_idx: usize, kind: SignKind) {
+        self.markers.toggle(line_idx, kind)
+    }
+
+    /// Edited since it was last loaded or saved.
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// How the first line ends, taken to be how every line does.
+    pub fn line_ending(&self) -> LineEnding {
+        let line = self.rope.line(0);
+        let len = line.len_chars();
+        match len >= 2 && line.char(len - 2) == '\r' {
+            true => LineEnding::Crlf,
+            false => LineEnding::Lf,
+        }
+    }
+
     /// Number of lines in buffer
     pub fn len_lines(&self) -> usize {
         self.rope.len_lines()
@@ -112,25 +248,6 @@ impl Buffer {
         pos.0 < line.visual_len()
     }
 
-    /// Number col for the line.
-    pub fn numb_col(&self, line_idx: usize) -> Box<str> {
-        let mut res = " ".to_string();
-        let total_width = self.line_numb_col_width();
-        let line_numb = &(line_idx + 1).to_string();
-
-        res.push_str(line_numb);
-
-        let right_padding_width = total_width - 3 - line_numb.len();
-
-        for _ in 0..right_padding_width {
-            res.push(' ');
-        }
-        res.push('┆');
-        res.push(' ');
-
-        res.into_boxed_str()
-    }
-
     /// Remove a range.
     /// Panics if the start of the range is greater than the end, or if the
     /// end is out of bounds (i.e. `end > len_chars()`).
@@ -138,7 +255,46 @@ impl Buffer {
         &mut self,
         char_range: R,
     ) -> BufferResult<()> {
-        self.rope.try_remove(char_range)?;
+        let start = match char_range.start_bound() {
+            Bound::Included(&idx) => idx,
+            Bound::Excluded(&idx) => idx + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match char_range.end_bound() {
+            Bound::Included(&idx) => idx + 1,
+            Bound::Excluded(&idx) => idx,
+            Bound::Unbounded => self.len(),
+        };
+        let start_line = self.rope.try_char_to_line(start)?;
+        let end_line = self.rope.try_char_to_line(end)?;
+        let range = LspRange {
+            start: self.lsp_position(start),
+            end: self.lsp_position(end),
+        };
+        // Lines are removed from the first that starts in the range, the
+        // rest of the last being joined onto the line the range starts in.
+        let first_removed = match start == self.rope.line_to_char(start_line) {
+            true => start_line,
+            false => start_line + 1,
+        };
+        let removed = self.rope.slice(start..end).to_string();
+
+        self.rope.try_remove(start..end)?;
+        self.history.record(
+            BufferDelta::Delete {
+                position: start,
+                length: end - start,
+            },
+            BufferDelta::Insert {
+                position: start,
+                text: removed.into(),
+            },
+        );
+        self.record_change(range, String::new());
+        self.edited(start_line..end_line + 1, start_line + 1);
+        self.markers
+            .remove_lines(first_removed..first_removed + end_line - start_line);
+
         Ok(())
     }
 
@@ -166,15 +322,201 @@ impl Buffer {
         char_offset: usize,
         text: T,
     ) -> BufferResult<()> {
+        let line_idx = self.rope.try_char_to_line(char_offset)?;
+        let position = self.lsp_position(char_offset);
+        // Text inserted at the start of a line pushes that line down too.
+        let at_line_start = char_offset == self.rope.line_to_char(line_idx);
+        let first_moved = match at_line_start {
+            true => line_idx,
+            false => line_idx + 1,
+        };
         self.rope.try_insert(char_offset, text.as_ref())?;
+        self.history.record(
+            BufferDelta::Insert {
+                position: char_offset,
+                text: text.as_ref().into(),
+            },
+            BufferDelta::Delete {
+                position: char_offset,
+                length: text.as_ref().chars().count(),
+            },
+        );
+        self.record_change(
+            LspRange {
+                start: position,
+                end: position,
+            },
+            text.as_ref().into(),
+        );
+        let new_lines = text.as_ref().matches('\n').count();
+        self.edited(line_idx..line_idx + 1, line_idx + 1 + new_lines);
+        self.markers.insert_lines(first_moved, new_lines);
 
         Ok(())
     }
 
-    /// Calculates the with of number column
-    /// left margin + right margin + border = 3
-    pub fn line_numb_col_width(&self) -> usize {
-        self.len_lines().to_string().len() + 3
+    /// Take back the latest step of edits, returning where the text changed,
+    /// or `None` when there's nothing left to undo.
+    pub fn undo(&mut self) -> BufferResult<Option<usize>> {
+        self.travel(UndoTree::undo)
+    }
+
+    /// Make again the latest step of edits undone, returning where the text
+    /// changed, or `None` when there's nothing left to redo.
+    pub fn redo(&mut self) -> BufferResult<Option<usize>> {
+        self.travel(UndoTree::redo)
+    }
+
+    /// Go back to the text as it was before the latest change in time,
+    /// across undone branches too.
+    pub fn earlier(&mut self) -> BufferResult<Option<usize>> {
+        self.travel(UndoTree::earlier)
+    }
+
+    /// Go forward to the text as it was after the next change in time,
+    /// across undone branches too.
+    pub fn later(&mut self) -> BufferResult<Option<usize>> {
+        self.travel(UndoTree::later)
+    }
+
+    /// Move through the undo tree, making the edits that take the text along
+    /// and returning where the last was made. The buffer is only modified
+    /// if that leaves it apart from the file.
+    fn travel(
+        &mut self,
+        step: fn(&mut UndoTree) -> Option<Vec<Delta>>,
+    ) -> BufferResult<Option<usize>> {
+        let Some(deltas) = step(&mut self.history) else {
+            return Ok(None);
+        };
+        // The edits are in the tree already, from when they were first made.
+        let history = mem::take(&mut self.history);
+        let position =
+            deltas.iter().try_fold(0, |position, delta| match delta {
+                Delta::Buffer(delta) => self.apply(delta),
+                Delta::App(_) => Ok(position),
+            });
+        self.history = history;
+        self.modified = self.rope != self.saved;
+
+        position.map(Some)
+    }
+
+    fn apply(&mut self, delta: &BufferDelta) -> BufferResult<usize> {
+        match delta {
+            BufferDelta::Insert { position, text } => {
+                self.insert(*position, text)?;
+                Ok(*position)
+            }
+            BufferDelta::Delete { position, length } => {
+                self.remove(*position..*position + length)?;
+                Ok(*position)
+            }
+            BufferDelta::Replace {
+                position,
+                length,
+                text,
+            } => {
+                self.remove(*position..*position + length)?;
+                self.insert(*position, text)?;
+                Ok(*position)
+            }
+        }
+    }
+
+    /// Undo what's edited from now on apart from what was edited before.
+    pub fn end_undo_step(&mut self) {
+        self.history.end_step()
+    }
+
+    /// Every state the text went through, the step being made ended.
+    pub fn undo_tree(&self) -> UndoTree {
+        let mut tree = self.history.clone();
+        tree.end_step();
+
+        tree
+    }
+
+    /// Pick up the undo tree kept from another session, which is at the text
+    /// as it is. Edits made in this one are dropped.
+    pub fn set_undo_tree(&mut self, tree: UndoTree) {
+        self.history = tree;
+    }
+
+    /// Keep the diff up to date after the lines `old` were replaced by lines
+    /// ending at `new_end`.
+    fn edited(&mut self, old: Range<usize>, new_end: usize) {
+        self.modified = true;
+        if let Some(diff) = self.diff.as_mut() {
+            diff.edit(&self.rope, old, new_end);
+        }
+    }
+
+    fn record_change(&mut self, range: LspRange, text: String) {
+        if let Some(changes) = self.changes.as_mut() {
+            changes.push(TextEdit { range, text });
+        }
+    }
+
+    /// Keep the edits made from now on for a language server, or stop when
+    /// there no longer is one.
+    pub fn track_changes(&mut self, track: bool) {
+        self.changes = track.then(Vec::new);
+    }
+
+    /// The edits made since the last call, oldest first.
+    pub fn take_changes(&mut self) -> Vec<TextEdit> {
+        self.changes
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Line and UTF-16 column of the char, as language servers count them.
+    pub fn lsp_position(&self, char_idx: usize) -> Position {
+        let char_idx = char_idx.min(self.len());
+        let line_idx = self.rope.char_to_line(char_idx);
+        let line_start = self.rope.line_to_char(line_idx);
+
+        Position {
+            line: line_idx,
+            character: self.rope.char_to_utf16_cu(char_idx)
+                - self.rope.char_to_utf16_cu(line_start),
+        }
+    }
+
+    /// Char at a position from a language server. Positions past the end of
+    /// a line or of the buffer are moved back to it.
+    pub fn char_idx_from_lsp(&self, position: Position) -> usize {
+        if position.line >= self.len_lines() {
+            return self.len();
+        }
+
+        let line_start = self.rope.line_to_char(position.line);
+        let line = self.rope.line(position.line);
+        let line_len = match line.chars().last() {
+            Some('\n') => line.len_chars() - 1,
+            _ => line.len_chars(),
+        };
+        let character = position.character.min(line.len_utf16_cu());
+
+        line_start + line.utf16_cu_to_char(character).min(line_len)
+    }
+
+    /// Diff the buffer against `base`, or stop diffing when `None`.
+    pub fn set_diff_base(&mut self, base: Option<&str>) {
+        self.diff = base.map(|base| Diff::new(base, &self.rope));
+    }
+
+    pub fn diff(&self) -> Option<&Diff> {
+        self.diff.as_ref()
+    }
+
+    /// A single hunk as a patch of the file at `path`.
+    pub fn hunk_patch(&self, hunk: &Hunk, path: &str) -> Option<String> {
+        let diff = self.diff.as_ref()?;
+
+        Some(diff.patch(hunk, path, &self.rope))
     }
 
     /// Returns the words of the line.
@@ -463,6 +805,22 @@ impl From<Cursor> for (usize, usize) {
     }
 }
 
+/// The characters ending each line of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineEnding::Lf => write!(f, "LF"),
+            LineEnding::Crlf => write!(f, "CRLF"),
+        }
+    }
+}
+
 /// A ropeslice wrapper with extra methods for line operations.
 #[derive(Debug)]
 pub struct Line<'a> {
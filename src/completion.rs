@@ -0,0 +1,465 @@
+use crate::{
+    buffer::{Buffer, Char},
+    fuzzy,
+};
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Most entries shown in the completion menu.
+pub const MENU_HEIGHT: usize = 8;
+
+/// Where a completion came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Word,
+    Path,
+    Lsp,
+}
+
+/// One entry of the completion menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub label: Box<str>,
+    /// Text replacing what was typed, a snippet if `snippet` is set.
+    pub text: Box<str>,
+    pub snippet: bool,
+    /// Char of the line the replaced text starts at. What was typed from
+    /// there to the cursor is what the item is ranked against.
+    pub start: usize,
+    pub kind: Kind,
+    pub detail: Option<Box<str>>,
+}
+
+/// What the sources complete.
+pub struct Context<'a> {
+    /// The cursor line up to the cursor.
+    pub line: &'a str,
+    pub buffers: &'a [&'a Buffer],
+    /// Directory relative paths start from.
+    pub dir: &'a Path,
+}
+
+/// Anything that suggests completions from the text before the cursor.
+pub trait Source: Debug {
+    fn complete(&self, ctx: &Context) -> Vec<Item>;
+}
+
+/// Words of the open buffers, for the word being typed.
+#[derive(Debug)]
+pub struct BufferWords;
+
+impl Source for BufferWords {
+    fn complete(&self, ctx: &Context) -> Vec<Item> {
+        let start = word_start(ctx.line);
+        let typed: String = ctx.line.chars().skip(start).collect();
+        // Words rarely follow a `/` or `.`, paths and members do.
+        let after_punctuation = ctx
+            .line
+            .chars()
+            .last()
+            .is_some_and(|char| char.is_ascii_punctuation());
+        if typed.is_empty() && after_punctuation {
+            return vec![];
+        }
+
+        let mut seen = HashSet::new();
+        let mut items = vec![];
+        for buffer in ctx.buffers {
+            let Ok(words) = buffer.words(..) else {
+                continue;
+            };
+            for word in identifiers(&words) {
+                let is_word =
+                    word.chars().next().is_some_and(char::is_alphabetic)
+                        && word.chars().count() > 1;
+                // The word being typed is in the buffer too.
+                if !is_word || word == typed || !seen.insert(word.clone()) {
+                    continue;
+                }
+                items.push(Item {
+                    label: word.as_str().into(),
+                    text: word.into(),
+                    snippet: false,
+                    start,
+                    kind: Kind::Word,
+                    detail: None,
+                });
+            }
+        }
+
+        items
+    }
+}
+
+/// Identifiers made of the buffer's words, which split at `_` and where
+/// letters meet digits.
+fn identifiers(words: &[Box<[Char]>]) -> Vec<String> {
+    let is_identifier = |word: &[Char]| {
+        word.iter()
+            .all(|char| char.char.is_alphanumeric() || char.char == '_')
+    };
+
+    let mut identifiers: Vec<String> = vec![];
+    let mut end = None;
+    for word in words {
+        if !is_identifier(word) {
+            end = None;
+            continue;
+        }
+        let text = word.iter().map(|char| char.char);
+        match (end, identifiers.last_mut()) {
+            (Some(end), Some(last)) if word[0].distance == end => {
+                last.extend(text)
+            }
+            _ => identifiers.push(text.collect()),
+        }
+        end = word.last().map(|char| char.distance + 1);
+    }
+
+    identifiers
+}
+
+/// Entries of the directory of a path being typed, once it has a '/'.
+#[derive(Debug)]
+pub struct FilePaths;
+
+impl Source for FilePaths {
+    fn complete(&self, ctx: &Context) -> Vec<Item> {
+        let path_start = ctx
+            .line
+            .char_indices()
+            .rev()
+            .find(|(_, char)| !is_path_char(*char))
+            .map_or(0, |(idx, char)| idx + char.len_utf8());
+        let path = &ctx.line[path_start..];
+        // `//` starts comments more often than it is in a path.
+        let Some(slash) = path.rfind('/').filter(|_| !path.contains("//"))
+        else {
+            return vec![];
+        };
+        let (dir, name) = path.split_at(slash + 1);
+        let Ok(entries) = fs::read_dir(resolve(dir, ctx.dir)) else {
+            return vec![];
+        };
+
+        let start = ctx.line.chars().count() - name.chars().count();
+        let mut items: Vec<Item> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let mut file_name = entry.file_name().into_string().ok()?;
+                // Hidden files only when asked for.
+                if file_name.starts_with('.') && !name.starts_with('.') {
+                    return None;
+                }
+                if entry.file_type().ok()?.is_dir() {
+                    file_name.push('/');
+                }
+                Some(Item {
+                    label: file_name.as_str().into(),
+                    text: file_name.into(),
+                    snippet: false,
+                    start,
+                    kind: Kind::Path,
+                    detail: None,
+                })
+            })
+            .collect();
+        items.sort_by(|a, b| a.label.cmp(&b.label));
+
+        items
+    }
+}
+
+fn is_path_char(char: char) -> bool {
+    !char.is_whitespace() && !"\"'`()[]{}<>,;=".contains(char)
+}
+
+/// The directory a typed path points at.
+fn resolve(dir: &str, relative_to: &Path) -> PathBuf {
+    match dir.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(rest),
+        None => relative_to.join(dir),
+    }
+}
+
+/// Char the identifier before the end of `line` starts at.
+pub fn word_start(line: &str) -> usize {
+    let chars: Vec<char> = line.chars().collect();
+    chars
+        .iter()
+        .rposition(|char| !(char.is_alphanumeric() || *char == '_'))
+        .map_or(0, |idx| idx + 1)
+}
+
+/// The sources asked for completions, the language server aside as it
+/// answers in the background.
+#[derive(Debug)]
+pub struct Sources {
+    sources: Vec<Box<dyn Source>>,
+}
+
+impl Default for Sources {
+    fn default() -> Self {
+        Self {
+            sources: vec![Box::new(BufferWords), Box::new(FilePaths)],
+        }
+    }
+}
+
+impl Sources {
+    pub fn add(&mut self, source: Box<dyn Source>) {
+        self.sources.push(source)
+    }
+
+    pub fn complete(&self, ctx: &Context) -> Vec<Item> {
+        self.sources
+            .iter()
+            .flat_map(|source| source.complete(ctx))
+            .collect()
+    }
+}
+
+/// The open completion menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub line_idx: usize,
+    /// Items matching what was typed, best first.
+    pub items: Vec<Item>,
+    pub selected: usize,
+    /// Everything the language server suggested, ranked again on each key.
+    pub lsp_items: Vec<Item>,
+    /// Is the language server yet to answer.
+    pub waiting: bool,
+    /// The line up to the cursor the items were ranked for.
+    typed: String,
+}
+
+impl Completion {
+    pub fn new(line_idx: usize) -> Self {
+        Self {
+            line_idx,
+            items: vec![],
+            selected: 0,
+            lsp_items: vec![],
+            waiting: false,
+            typed: String::new(),
+        }
+    }
+
+    /// Rank `items` and the language server's items for the line up to the
+    /// cursor. The selection stays on the same item when it is still there.
+    pub fn rank(&mut self, line: &str, mut items: Vec<Item>) {
+        let selected = self.selected().map(|item| item.label.clone());
+        items.extend(self.lsp_items.iter().cloned());
+        let len = line.chars().count();
+        let mut ranked: Vec<(i64, Item)> = items
+            .into_iter()
+            // The cursor went back past where the item starts.
+            .filter(|item| item.start <= len)
+            .filter_map(|item| {
+                let typed: String = line.chars().skip(item.start).collect();
+                Some((fuzzy::score(&typed, &item.label)?, item))
+            })
+            .collect();
+        // Stable, so items of the same score keep the order of the sources.
+        ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        self.items = ranked.into_iter().map(|(_, item)| item).collect();
+        self.selected = selected
+            .and_then(|label| {
+                self.items.iter().position(|item| item.label == label)
+            })
+            .unwrap_or(0);
+        self.typed = line.into();
+    }
+
+    /// Was the menu ranked for this line.
+    pub fn is_ranked_for(&self, line: &str) -> bool {
+        self.typed == line
+    }
+
+    pub fn selected(&self) -> Option<&Item> {
+        self.items.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.items.is_empty() {
+            self.selected =
+                (self.selected + self.items.len() - 1) % self.items.len();
+        }
+    }
+
+    /// Items shown, scrolled to keep the selection in view, and the index
+    /// of the first.
+    pub fn visible(&self) -> (usize, &[Item]) {
+        let first = (self.selected + 1).saturating_sub(MENU_HEIGHT);
+        let last = (first + MENU_HEIGHT).min(self.items.len());
+
+        (first, &self.items[first..last])
+    }
+}
+
+/// Text with tab stops, such as `fn ${1:name}($2) {$0}`, parsed from the
+/// snippet syntax language servers use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snippet {
+    /// The text with placeholders filled in.
+    pub text: String,
+    /// Char ranges of the tab stops, in the order they are visited. `$0`,
+    /// where the cursor ends up, is last.
+    pub stops: Vec<Range<usize>>,
+}
+
+impl Snippet {
+    pub fn parse(source: &str) -> Self {
+        let mut snippet = Snippet::default();
+        let mut numbered: Vec<(usize, Range<usize>)> = vec![];
+        let chars: Vec<char> = source.chars().collect();
+        let mut idx = 0;
+        while idx < chars.len() {
+            idx += parse_into(&chars[idx..], &mut snippet.text, &mut numbered);
+            // A `}` closing nothing is text.
+            if let Some(char) = chars.get(idx) {
+                snippet.text.push(*char);
+                idx += 1;
+            }
+        }
+
+        // Stops visit in number order, with `$0` after the rest. The first
+        // of repeated stops is the one visited.
+        numbered.sort_by_key(|(number, range)| {
+            (*number == 0, *number, range.start)
+        });
+        numbered.dedup_by_key(|(number, _)| *number);
+        snippet.stops = numbered.into_iter().map(|(_, range)| range).collect();
+
+        snippet
+    }
+}
+
+/// Append the text of `chars` to `text`, noting where each tab stop lands.
+/// Returns how many chars were read, stopping at an unescaped `}`.
+fn parse_into(
+    chars: &[char],
+    text: &mut String,
+    stops: &mut Vec<(usize, Range<usize>)>,
+) -> usize {
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '\\' if idx + 1 < chars.len() => {
+                text.push(chars[idx + 1]);
+                idx += 2;
+            }
+            '}' => return idx,
+            '$' => {
+                let read = parse_dollar(&chars[idx..], text, stops);
+                if read == 0 {
+                    text.push('$');
+                    idx += 1;
+                } else {
+                    idx += read;
+                }
+            }
+            char => {
+                text.push(char);
+                idx += 1;
+            }
+        }
+    }
+
+    idx
+}
+
+/// A `$1`, `${1}`, `${1:placeholder}`, `${1|one,two|}` or variable at the
+/// start of `chars`. Returns how many chars it took, 0 when it isn't one.
+fn parse_dollar(
+    chars: &[char],
+    text: &mut String,
+    stops: &mut Vec<(usize, Range<usize>)>,
+) -> usize {
+    let digits = |from: usize| -> usize {
+        chars[from..]
+            .iter()
+            .take_while(|char| char.is_ascii_digit())
+            .count()
+    };
+    let number = |from: usize, len: usize| -> usize {
+        chars[from..from + len]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .unwrap_or_default()
+    };
+    let at = text.chars().count();
+
+    let len = digits(1);
+    if len > 0 {
+        stops.push((number(1, len), at..at));
+        return 1 + len;
+    }
+    if chars.get(1) != Some(&'{') {
+        // `$NAME`, a variable. None are known, so it stands for nothing.
+        let len = chars[1..]
+            .iter()
+            .take_while(|char| char.is_alphanumeric() || **char == '_')
+            .count();
+        return match len {
+            0 => 0,
+            len => 1 + len,
+        };
+    }
+
+    let len = digits(2);
+    let mut idx = 2 + len;
+    match (len, chars.get(idx)) {
+        (0, _) => {
+            // `${NAME}` or `${NAME:default}`, which gives the default.
+            let name_len = chars[2..]
+                .iter()
+                .take_while(|char| char.is_alphanumeric() || **char == '_')
+                .count();
+            idx = 2 + name_len;
+            if chars.get(idx) == Some(&':') {
+                idx += 1 + parse_into(&chars[idx + 1..], text, stops);
+            }
+        }
+        (_, Some(':')) => {
+            idx += 1 + parse_into(&chars[idx + 1..], text, stops);
+            let end = text.chars().count();
+            stops.push((number(2, len), at..end));
+        }
+        (_, Some('|')) => {
+            // A choice, the first option stands in for it.
+            let options: String = chars[idx + 1..]
+                .iter()
+                .take_while(|char| **char != '|')
+                .collect();
+            idx += 2 + options.chars().count();
+            let first = options.split(',').next().unwrap_or_default();
+            text.push_str(first);
+            stops.push((number(2, len), at..at + first.chars().count()));
+        }
+        _ => stops.push((number(2, len), at..at)),
+    }
+
+    // Past the closing `}`.
+    match chars.get(idx) {
+        Some('}') => idx + 1,
+        _ => idx,
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{
+    app::{Notification, NotificationType},
+    keymap::{self, Keymap, KeysConfig},
+    logger::Level,
+    lsp, theme,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, env, fmt::Display, fs, io, path::PathBuf};
+
+/// Directory name used under the XDG base directories.
+pub const APP_DIR: &str = "dans-editor";
+
+/// Editor options, read from `$XDG_CONFIG_HOME/dans-editor/config.toml`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Number of columns a tab is drawn as.
+    pub tab_width: usize,
+    /// Lines kept visible above and below the cursor when the layout isn't
+    /// centered.
+    pub scrolloff: usize,
+    /// Save the buffer when the terminal loses focus.
+    pub auto_save: bool,
+    /// How often unsaved edits are written to swap files, to recover after
+    /// a crash, in milliseconds. 0 turns swap files off.
+    pub swap_interval: u64,
+    pub line_numbers: LineNumbers,
+    pub whitespace: Whitespace,
+    /// Name of a built-in theme, or of a file in the `themes` config dir.
+    pub theme: Box<str>,
+    pub layout: Layout,
+    pub keys: KeysConfig,
+    /// Language servers, by the language id they're told the file has.
+    pub lsp: HashMap<Box<str>, lsp::ServerConfig>,
+    /// Logs below this level are dropped.
+    pub log_level: Level,
+    pub notifications: Notifications,
+    pub status_line: StatusLine,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            scrolloff: 5,
+            auto_save: false,
+            swap_interval: 4000,
+            line_numbers: LineNumbers::default(),
+            whitespace: Whitespace::default(),
+            theme: "default".into(),
+            layout: Layout::default(),
+            keys: KeysConfig::default(),
+            lsp: HashMap::new(),
+            log_level: Level::default(),
+            notifications: Notifications::default(),
+            status_line: StatusLine::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineNumbers {
+    #[default]
+    Absolute,
+    /// Distance from the cursor line.
+    Relative,
+    /// Absolute on the cursor line, relative elsewhere.
+    Hybrid,
+    None,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Whitespace {
+    /// Draw spaces with the theme's whitespace glyph.
+    pub render: bool,
+}
+
+impl Default for Whitespace {
+    fn default() -> Self {
+        Self { render: true }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    /// Keep the cursor line in the middle of the screen. When false the text
+    /// scrolls, keeping `scrolloff` lines around the cursor.
+    pub centered: bool,
+    /// Show the sign column left of the line numbers.
+    pub sign_column: bool,
+    /// Show the git summary above the first line of the file.
+    pub git_summary: bool,
+    /// Show the logs below the last line of the file.
+    pub logs: bool,
+    /// Show the latest keys pressed over the text, for demos.
+    pub screencast: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            centered: true,
+            sign_column: true,
+            git_summary: true,
+            logs: true,
+            screencast: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Notifications {
+    /// How long a notification stays in the status line, in milliseconds.
+    /// 0 keeps it until the next.
+    pub timeout: u64,
+    /// Count a notification pushed again right after itself, rather than
+    /// listing it twice.
+    pub dedup: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            timeout: 5000,
+            dedup: true,
+        }
+    }
+}
+
+/// The segments shown either side of the message in the status line.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusLine {
+    /// From the left edge in.
+    pub left: Vec<Segment>,
+    /// From the message out to the right edge.
+    pub right: Vec<Segment>,
+}
+
+impl Default for StatusLine {
+    fn default() -> Self {
+        Self {
+            left: vec![Segment::Mode, Segment::File, Segment::Branch],
+            right: vec![
+                Segment::Diagnostics,
+                Segment::FileType,
+                Segment::Encoding,
+                Segment::LineEnding,
+                Segment::Position,
+                Segment::Percentage,
+                Segment::Keys,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Segment {
+    Mode,
+    /// File name, and whether it has unsaved edits.
+    File,
+    /// Line and column of the cursor.
+    Position,
+    /// How far through the file the cursor line is.
+    Percentage,
+    /// Lines in the file.
+    Lines,
+    LineEnding,
+    Encoding,
+    /// Language of the file, going by its extension.
+    FileType,
+    Branch,
+    /// How many diagnostics of each severity the file has.
+    Diagnostics,
+    /// Count and keys typed so far of the next command.
+    Keys,
+}
+
+impl Config {
+    /// Read the config file. A missing file gives the default config. Any
+    /// problem with the file is returned alongside a usable config, with the
+    /// offending options left at their defaults.
+    pub fn load() -> (Self, Vec<Error>) {
+        match config_dir() {
+            Some(dir) => Self::load_from(dir.join("config.toml")),
+            None => (Self::default(), vec![]),
+        }
+    }
+
+    pub fn load_from(path: PathBuf) -> (Self, Vec<Error>) {
+        let src = match fs::read_to_string(&path) {
+            Ok(src) => src,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return (Self::default(), vec![]);
+            }
+            Err(err) => return (Self::default(), vec![err.into()]),
+        };
+
+        Self::from_toml(&src)
+    }
+
+    pub fn from_toml(src: &str) -> (Self, Vec<Error>) {
+        let table = match src.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(err) => return (Self::default(), vec![err.into()]),
+        };
+
+        // Keep each option only if it reads, so one bad option leaves the
+        // rest of the file in effect.
+        let mut accepted = toml::Table::new();
+        let mut errors = vec![];
+        for (key, value) in table {
+            accept(&mut accepted, &[], key, value, &mut errors);
+        }
+        let config = toml::Value::Table(accepted)
+            .try_into::<Config>()
+            .unwrap_or_default();
+        let (config, invalid) = config.validate();
+        errors.extend(invalid);
+
+        (config, errors)
+    }
+
+    /// Reset any out of range options to their defaults.
+    fn validate(mut self) -> (Self, Vec<Error>) {
+        let default = Self::default();
+        let mut errors = vec![];
+
+        if !(1..=16).contains(&self.tab_width) {
+            errors.push(Error::InvalidValue {
+                key: "tab_width",
+                value: self.tab_width.to_string(),
+                expected: "a width from 1 to 16",
+            });
+            self.tab_width = default.tab_width;
+        }
+
+        if let Err(err) = theme::Theme::find(&self.theme) {
+            errors.push(err.into());
+            self.theme = default.theme;
+        }
+
+        // Bad bindings are skipped when the keymap is built, only report them.
+        let (_, keymap_errors) = Keymap::from_config(&self.keys);
+        errors.extend(keymap_errors.into_iter().map(Error::Keymap));
+
+        (self, errors)
+    }
+}
+
+/// Add `key` to the table at `path` in `accepted`, if the config still reads
+/// with it. A table that doesn't has each of its own keys tried instead.
+fn accept(
+    accepted: &mut toml::Table,
+    path: &[&str],
+    key: String,
+    value: toml::Value,
+    errors: &mut Vec<Error>,
+) {
+    let reads = |value: &toml::Value| {
+        let mut trial = accepted.clone();
+        table_at(&mut trial, path).insert(key.clone(), value.clone());
+        toml::Value::Table(trial).try_into::<Config>().err()
+    };
+    let Some(err) = reads(&value) else {
+        table_at(accepted, path).insert(key, value);
+        return;
+    };
+
+    match value {
+        toml::Value::Table(table)
+            if !table.is_empty()
+                && reads(&toml::Value::Table(toml::Table::new())).is_none() =>
+        {
+            table_at(accepted, path)
+                .insert(key.clone(), toml::Table::new().into());
+            let mut path = path.to_vec();
+            path.push(&key);
+            for (key, value) in table {
+                accept(accepted, &path, key, value, errors);
+            }
+        }
+        _ => {
+            let key = path.iter().copied().chain([&*key]).collect::<Vec<_>>();
+            errors.push(Error::BadOption {
+                key: key.join("."),
+                err,
+            });
+        }
+    }
+}
+
+/// The table nested in `table` under each key of `path`, which are tables.
+fn table_at<'a>(
+    mut table: &'a mut toml::Table,
+    path: &[&str],
+) -> &'a mut toml::Table {
+    for key in path {
+        table = match table.get_mut(*key) {
+            Some(toml::Value::Table(inner)) => inner,
+            _ => unreachable!("only tables are accepted along a path"),
+        };
+    }
+
+    table
+}
+
+/// `$XDG_CONFIG_HOME/dans-editor`, falling back to `~/.config/dans-editor`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_STATE_HOME/dans-editor`, falling back to
+/// `~/.local/state/dans-editor`.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// Resolve an XDG base directory, falling back to `$HOME/<fallback>` when the
+/// variable is unset or not absolute, as the spec requires.
+pub fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback))
+        })?;
+
+    Some(base.join(APP_DIR))
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    /// An option that doesn't read, by its dotted key.
+    BadOption {
+        key: String,
+        err: toml::de::Error,
+    },
+    InvalidValue {
+        key: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+    Theme(theme::Error),
+    Keymap(keymap::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Config error: ")?;
+
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Parse(err) => write!(f, "{}", err.message()),
+            Self::BadOption { key, err } => {
+                write!(f, "{}: {}", key, err.message())
+            }
+            Self::InvalidValue {
+                key,
+                value,
+                expected,
+            } => write!(f, "{} is {}, expected {}", key, value, expected),
+            Self::Theme(err) => write!(f, "{}", err),
+            Self::Keymap(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<theme::Error> for Error {
+    fn from(err: theme::Error) -> Self {
+        Error::Theme(err)
+    }
+}
+
+impl From<&Error> for Notification {
+    fn from(err: &Error) -> Self {
+        Notification::new(NotificationType::Error, err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,191 @@
+use crate::{
+    app::{Notification, NotificationType},
+    sign::{SignKind, SignProvider},
+};
+use std::{collections::BTreeMap, ops::Range};
+
+/// How bad a problem is, most severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Severity {
+    pub fn sign(self) -> SignKind {
+        match self {
+            Self::Error => SignKind::DiagnosticError,
+            Self::Warning => SignKind::DiagnosticWarning,
+            Self::Info => SignKind::DiagnosticInfo,
+            Self::Hint => SignKind::DiagnosticHint,
+        }
+    }
+}
+
+/// A char of the buffer, by line and char within the line. Orders the way
+/// the text reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub fn new(line: usize, col: usize) -> Self {
+        Self { line, col }
+    }
+}
+
+/// A problem found in a range of the buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub start: Pos,
+    /// Just past the last char.
+    pub end: Pos,
+    pub severity: Severity,
+    pub message: Box<str>,
+    /// What found it, such as the compiler behind a language server.
+    pub source: Option<Box<str>>,
+}
+
+impl Diagnostic {
+    /// Is the char at `pos` part of the range. An empty range holds the char
+    /// it starts at.
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.start <= pos && (pos < self.end || pos == self.start)
+    }
+
+    /// Chars of the line `line_idx`, `line_len` chars long, in the range.
+    /// Never empty on the lines the range touches, so every diagnostic can
+    /// be seen.
+    pub fn cols(
+        &self,
+        line_idx: usize,
+        line_len: usize,
+    ) -> Option<Range<usize>> {
+        if line_idx < self.start.line || line_idx > self.end.line {
+            return None;
+        }
+        let start = match line_idx == self.start.line {
+            true => self.start.col,
+            false => 0,
+        };
+        let end = match line_idx == self.end.line {
+            true => self.end.col,
+            false => line_len,
+        };
+
+        Some(start..end.max(start + 1))
+    }
+
+    /// The message on a single line.
+    pub fn summary(&self) -> String {
+        self.message
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl From<&Diagnostic> for Notification {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        let message_type = match diagnostic.severity {
+            Severity::Error => NotificationType::Error,
+            Severity::Warning => NotificationType::Warning,
+            Severity::Info | Severity::Hint => NotificationType::Info,
+        };
+        let text = match &diagnostic.source {
+            Some(source) => format!("{}: {}", source, diagnostic.summary()),
+            None => diagnostic.summary(),
+        };
+
+        Notification::new(message_type, text)
+    }
+}
+
+/// Diagnostics of the buffer, kept apart by what provided them so each
+/// provider, such as the language server or an external checker, can
+/// replace its own.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    providers: BTreeMap<Box<str>, Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    /// Replace the diagnostics from `provider`.
+    pub fn set(&mut self, provider: &str, diagnostics: Vec<Diagnostic>) {
+        match diagnostics.is_empty() {
+            true => self.providers.remove(provider),
+            false => self.providers.insert(provider.into(), diagnostics),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Every diagnostic, in the order they appear in the buffer, the most
+    /// severe first where they start together.
+    pub fn sorted(&self) -> Vec<&Diagnostic> {
+        let mut diagnostics: Vec<&Diagnostic> =
+            self.providers.values().flatten().collect();
+        diagnostics
+            .sort_by_key(|diagnostic| (diagnostic.start, diagnostic.severity));
+
+        diagnostics
+    }
+
+    /// Diagnostics touching the line, most severe first.
+    pub fn on_line(&self, line_idx: usize) -> Vec<&Diagnostic> {
+        let mut diagnostics: Vec<&Diagnostic> = self
+            .providers
+            .values()
+            .flatten()
+            .filter(|diagnostic| {
+                diagnostic.start.line <= line_idx
+                    && line_idx <= diagnostic.end.line
+            })
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.severity);
+
+        diagnostics
+    }
+
+    /// The most severe diagnostic holding the char at `pos`.
+    pub fn at(&self, pos: Pos) -> Option<&Diagnostic> {
+        self.providers
+            .values()
+            .flatten()
+            .filter(|diagnostic| diagnostic.contains(pos))
+            .min_by_key(|diagnostic| diagnostic.severity)
+    }
+
+    /// The first diagnostic starting after `pos`.
+    pub fn next(&self, pos: Pos) -> Option<&Diagnostic> {
+        self.sorted()
+            .into_iter()
+            .find(|diagnostic| diagnostic.start > pos)
+    }
+
+    /// The last diagnostic starting before `pos`.
+    pub fn prev(&self, pos: Pos) -> Option<&Diagnostic> {
+        self.sorted()
+            .into_iter()
+            .rev()
+            .find(|diagnostic| diagnostic.start < pos)
+    }
+}
+
+impl SignProvider for Diagnostics {
+    fn sign(&self, line_idx: usize) -> Option<SignKind> {
+        let diagnostic = self.on_line(line_idx).into_iter().next()?;
+
+        Some(diagnostic.severity.sign())
+    }
+}
@@ -0,0 +1,410 @@
+use crate::sign::{SignKind, SignProvider};
+use ropey::Rope;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use std::{hash::Hash, ops::Range};
+
+/// A run of changed lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// Lines in the base text.
+    pub before: Range<usize>,
+    /// Lines in the buffer.
+    pub after: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl Hunk {
+    pub fn kind(&self) -> HunkKind {
+        match (self.before.is_empty(), self.after.is_empty()) {
+            (true, _) => HunkKind::Added,
+            (_, true) => HunkKind::Removed,
+            _ => HunkKind::Changed,
+        }
+    }
+
+    /// Line the hunk is shown on. Removed lines are shown on the line above
+    /// where they used to be.
+    pub fn anchor(&self) -> usize {
+        match self.kind() {
+            HunkKind::Removed => self.after.start.saturating_sub(1),
+            _ => self.after.start,
+        }
+    }
+
+    fn contains(&self, line_idx: usize) -> bool {
+        self.after.contains(&line_idx) || self.anchor() == line_idx
+    }
+
+    fn shift(mut self, delta: isize) -> Self {
+        self.after = self.after.start.saturating_add_signed(delta)
+            ..self.after.end.saturating_add_signed(delta);
+
+        self
+    }
+}
+
+/// The line diff between the buffer and a base text, such as the file at
+/// `HEAD`.
+#[derive(Debug, Clone, Default)]
+pub struct Diff {
+    base: Vec<String>,
+    hunks: Vec<Hunk>,
+}
+
+impl Diff {
+    /// Diff `rope` against `base`. A final '\n' is dropped from `base`, as it
+    /// is from files loaded into a buffer, and an empty `base` or buffer has
+    /// no lines.
+    pub fn new(base: &str, rope: &Rope) -> Self {
+        let mut diff = Self {
+            base: lines(base).into_iter().map(String::from).collect(),
+            hunks: vec![],
+        };
+        diff.hunks = diff.compute(0..diff.base.len(), rope, 0..len_lines(rope));
+
+        diff
+    }
+
+    pub fn base(&self) -> &[String] {
+        &self.base
+    }
+
+    pub fn hunks(&self) -> &[Hunk] {
+        &self.hunks
+    }
+
+    /// The hunk shown on the line.
+    pub fn hunk_at(&self, line_idx: usize) -> Option<&Hunk> {
+        self.hunks.iter().find(|hunk| hunk.contains(line_idx))
+    }
+
+    /// The first hunk below the line.
+    pub fn next_hunk(&self, line_idx: usize) -> Option<&Hunk> {
+        self.hunks.iter().find(|hunk| hunk.anchor() > line_idx)
+    }
+
+    /// The last hunk above the line.
+    pub fn prev_hunk(&self, line_idx: usize) -> Option<&Hunk> {
+        self.hunks
+            .iter()
+            .rev()
+            .find(|hunk| hunk.anchor() < line_idx)
+    }
+
+    /// Update the diff after the lines `old` of the buffer were replaced, the
+    /// replacement ending at line `new_end` of `rope`. Only the edited lines
+    /// and the hunks touching them are diffed again.
+    pub fn edit(&mut self, rope: &Rope, old: Range<usize>, new_end: usize) {
+        let delta = new_end as isize - old.end as isize;
+        let first = self
+            .hunks
+            .partition_point(|hunk| hunk.after.end < old.start);
+        let last = self
+            .hunks
+            .partition_point(|hunk| hunk.after.start <= old.end);
+        let touched = &self.hunks[first..last];
+
+        let start = touched
+            .first()
+            .map_or(old.start, |hunk| hunk.after.start.min(old.start));
+        let end = touched
+            .last()
+            .map_or(old.end, |hunk| hunk.after.end.max(old.end));
+        let base_start = self.to_base(start, &self.hunks[..first]);
+        let base_end = self.to_base(end, &self.hunks[..last]);
+        let new_end = end.saturating_add_signed(delta).min(len_lines(rope));
+
+        let redone = self.compute(base_start..base_end, rope, start..new_end);
+        let after: Vec<Hunk> = self.hunks[last..]
+            .iter()
+            .map(|hunk| hunk.clone().shift(delta))
+            .collect();
+        self.hunks.truncate(first);
+        self.hunks.extend(redone);
+        self.hunks.extend(after);
+    }
+
+    /// Unified diff of a single hunk, for `git apply --unidiff-zero`.
+    pub fn patch(&self, hunk: &Hunk, path: &str, rope: &Rope) -> String {
+        // Only this hunk is applied, so both sides start at the same line. A
+        // side with no lines is numbered by the line before it.
+        let start = |len: usize| match len {
+            0 => hunk.before.start,
+            _ => hunk.before.start + 1,
+        };
+        let mut patch = format!(
+            "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n\
+             @@ -{},{} +{},{} @@\n",
+            start(hunk.before.len()),
+            hunk.before.len(),
+            start(hunk.after.len()),
+            hunk.after.len(),
+            path = path,
+        );
+        for line in &self.base[hunk.before.clone()] {
+            patch.push_str(&format!("-{}\n", line));
+        }
+        for line_idx in hunk.after.clone() {
+            patch.push_str(&format!("+{}\n", line(rope, line_idx)));
+        }
+
+        patch
+    }
+
+    /// Base line for a buffer line outside any hunk, given the hunks above it.
+    fn to_base(&self, line_idx: usize, above: &[Hunk]) -> usize {
+        let offset: isize = above
+            .iter()
+            .map(|hunk| hunk.after.len() as isize - hunk.before.len() as isize)
+            .sum();
+
+        line_idx.saturating_add_signed(-offset).min(self.base.len())
+    }
+
+    fn compute(
+        &self,
+        before: Range<usize>,
+        rope: &Rope,
+        after: Range<usize>,
+    ) -> Vec<Hunk> {
+        let lines: Vec<String> =
+            after.clone().map(|line_idx| line(rope, line_idx)).collect();
+
+        hunks(&self.base[before.clone()], &lines)
+            .into_iter()
+            .map(|hunk| Hunk {
+                before: hunk.before.start + before.start
+                    ..hunk.before.end + before.start,
+                after: hunk.after.start + after.start
+                    ..hunk.after.end + after.start,
+            })
+            .collect()
+    }
+}
+
+/// Lines of the buffer, none when it's empty, like `lines` of a text.
+fn len_lines(rope: &Rope) -> usize {
+    match rope.len_chars() {
+        0 => 0,
+        _ => rope.len_lines(),
+    }
+}
+
+/// The line without its '\n'.
+fn line(rope: &Rope, line_idx: usize) -> String {
+    let line = rope.line(line_idx).to_string();
+    match line.strip_suffix('\n') {
+        Some(line) => line.into(),
+        None => line,
+    }
+}
+
+impl SignProvider for Diff {
+    fn sign(&self, line_idx: usize) -> Option<SignKind> {
+        let hunk = self.hunk_at(line_idx)?;
+        let kind = match hunk.kind() {
+            HunkKind::Added => SignKind::GitAdded,
+            HunkKind::Removed => SignKind::GitRemoved,
+            HunkKind::Changed => SignKind::GitChanged,
+        };
+
+        Some(kind)
+    }
+}
+
+/// Line of `after` that line `line_idx` of `before` ended up on. A line that
+/// was changed or removed goes to the nearest line left in its place.
+pub fn map_line(before: &str, after: &str, line_idx: usize) -> usize {
+    let before = lines(before);
+    let after = lines(after);
+    let last = after.len().saturating_sub(1);
+
+    for op in capture_diff_slices(Algorithm::Myers, &before, &after) {
+        let (old_index, old_len, new_index, new_len) = match op {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => (old_index, len, new_index, len),
+            DiffOp::Delete {
+                old_index,
+                old_len,
+                new_index,
+            } => (old_index, old_len, new_index, 0),
+            DiffOp::Insert { .. } => continue,
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (old_index, old_len, new_index, new_len),
+        };
+        if (old_index..old_index + old_len).contains(&line_idx) {
+            let offset = (line_idx - old_index).min(new_len.saturating_sub(1));
+            return (new_index + offset).min(last);
+        }
+    }
+
+    line_idx.min(last)
+}
+
+/// The result of merging two sets of changes to the same text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    pub text: String,
+    /// Line each conflict starts on, at its `<<<<<<<` marker.
+    pub conflicts: Vec<usize>,
+}
+
+/// Three-way merge of `ours` and `theirs`, both changed from `base`. Changes
+/// to different lines are both kept. Where both sides changed the same
+/// lines differently, the lines of all three are left between conflict
+/// markers, labelled with `names` in the order ours, base, theirs.
+pub fn merge(base: &str, ours: &str, theirs: &str, names: [&str; 3]) -> Merge {
+    let base_lines = lines(base);
+    let ours_lines = lines(ours);
+    let theirs_lines = lines(theirs);
+    let mut ours_hunks = hunks(&base_lines, &ours_lines).into_iter().peekable();
+    let mut theirs_hunks =
+        hunks(&base_lines, &theirs_lines).into_iter().peekable();
+
+    let mut merged: Vec<String> = vec![];
+    let mut conflicts = vec![];
+    let mut base_idx = 0;
+    // How many lines each side is ahead of the base by, above `base_idx`.
+    let (mut ours_delta, mut theirs_delta) = (0_isize, 0_isize);
+    loop {
+        let start = match (ours_hunks.peek(), theirs_hunks.peek()) {
+            (Some(ours), Some(theirs)) => {
+                ours.before.start.min(theirs.before.start)
+            }
+            (Some(hunk), None) | (None, Some(hunk)) => hunk.before.start,
+            (None, None) => break,
+        };
+        extend(&mut merged, &base_lines[base_idx..start]);
+
+        // Hunks that overlap or touch are taken as one, as edits right next
+        // to each other clash as much as ones to the same lines.
+        let mut end = start;
+        let (ours_start, theirs_start) = (ours_delta, theirs_delta);
+        let (mut ours_changed, mut theirs_changed) = (false, false);
+        loop {
+            let (hunk, delta, changed) = if let Some(hunk) =
+                ours_hunks.next_if(|hunk| hunk.before.start <= end)
+            {
+                (hunk, &mut ours_delta, &mut ours_changed)
+            } else if let Some(hunk) =
+                theirs_hunks.next_if(|hunk| hunk.before.start <= end)
+            {
+                (hunk, &mut theirs_delta, &mut theirs_changed)
+            } else {
+                break;
+            };
+            end = end.max(hunk.before.end);
+            *delta += hunk.after.len() as isize - hunk.before.len() as isize;
+            *changed = true;
+        }
+
+        let ours_side = &ours_lines[start.saturating_add_signed(ours_start)
+            ..end.saturating_add_signed(ours_delta)];
+        let theirs_side = &theirs_lines[start
+            .saturating_add_signed(theirs_start)
+            ..end.saturating_add_signed(theirs_delta)];
+        match (ours_changed, theirs_changed) {
+            (true, false) => extend(&mut merged, ours_side),
+            (false, true) => extend(&mut merged, theirs_side),
+            _ if ours_side == theirs_side => extend(&mut merged, ours_side),
+            _ => {
+                let [ours_name, base_name, theirs_name] = names;
+                conflicts.push(merged.len());
+                merged.push(format!("<<<<<<< {}", ours_name));
+                extend(&mut merged, ours_side);
+                merged.push(format!("||||||| {}", base_name));
+                extend(&mut merged, &base_lines[start..end]);
+                merged.push("=======".into());
+                extend(&mut merged, theirs_side);
+                merged.push(format!(">>>>>>> {}", theirs_name));
+            }
+        }
+        base_idx = end;
+    }
+    extend(&mut merged, &base_lines[base_idx..]);
+
+    let mut text = merged.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+
+    Merge { text, conflicts }
+}
+
+fn extend(merged: &mut Vec<String>, lines: &[&str]) {
+    merged.extend(lines.iter().map(|line| line.to_string()));
+}
+
+/// Lines of a text read from a file, without their '\n's. A final '\n' ends
+/// the last line rather than starting another.
+fn lines(text: &str) -> Vec<&str> {
+    if text.is_empty() {
+        return vec![];
+    }
+
+    text.strip_suffix('\n')
+        .unwrap_or(text)
+        .split('\n')
+        .collect()
+}
+
+/// Runs of changed lines between two texts, ops that follow on from each
+/// other making up one.
+fn hunks<T: Hash + Ord>(before: &[T], after: &[T]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = vec![];
+    for op in capture_diff_slices(Algorithm::Myers, before, after) {
+        let hunk = match op {
+            DiffOp::Equal { .. } => continue,
+            DiffOp::Delete {
+                old_index,
+                old_len,
+                new_index,
+            } => Hunk {
+                before: old_index..old_index + old_len,
+                after: new_index..new_index,
+            },
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => Hunk {
+                before: old_index..old_index,
+                after: new_index..new_index + new_len,
+            },
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => Hunk {
+                before: old_index..old_index + old_len,
+                after: new_index..new_index + new_len,
+            },
+        };
+        match hunks.last_mut() {
+            Some(prev)
+                if prev.before.end == hunk.before.start
+                    && prev.after.end == hunk.after.start =>
+            {
+                prev.before.end = hunk.before.end;
+                prev.after.end = hunk.after.end;
+            }
+            _ => hunks.push(hunk),
+        }
+    }
+
+    hunks
+}
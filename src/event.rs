@@ -0,0 +1,111 @@
+use crossterm::event as terminal;
+use std::{
+    io,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+/// Something the main loop has to react to.
+#[derive(Debug)]
+pub enum Event {
+    /// Input from the terminal: keys, the mouse, pastes, resizes and focus.
+    Terminal(terminal::Event),
+    /// A worker has something new, picked up by `App::update_background`.
+    Wake,
+    /// Time passed, for what's done every so often whatever else happens,
+    /// such as writing swap files.
+    Tick,
+    /// Reading from the terminal failed, and it won't be read from again.
+    Input(io::Error),
+}
+
+/// Where the terminal and workers send events, for the main loop to wait on
+/// rather than blocking on the terminal alone. A send here, or on a channel a
+/// worker answers on, only fails once nothing is listening anymore: the main
+/// loop is done or what started the worker was dropped. Threads then stop,
+/// or drop what they had to send, rather than report it.
+#[derive(Debug)]
+pub struct EventBus {
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+
+    /// Read the terminal on its own thread, sending on whatever it reads.
+    pub fn read_terminal(&self) {
+        let sender = self.sender.clone();
+        thread::spawn(move || loop {
+            let event = match terminal::read() {
+                Ok(event) => Event::Terminal(event),
+                Err(err) => {
+                    _ = sender.send(Event::Input(err));
+                    return;
+                }
+            };
+            if sender.send(event).is_err() {
+                return;
+            }
+        });
+    }
+
+    /// Send a `Tick` every `interval`, on its own thread.
+    pub fn tick(&self, interval: Duration) {
+        let sender = self.sender.clone();
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            if sender.send(Event::Tick).is_err() {
+                return;
+            }
+        });
+    }
+
+    /// A handle for workers to wake the main loop with.
+    pub fn waker(&self) -> Waker {
+        Waker(Some(self.sender.clone()))
+    }
+
+    pub fn send(&self, event: Event) {
+        // The receiver lives as long as the sender here.
+        _ = self.sender.send(event);
+    }
+
+    /// Wait for the next event, up to `timeout` when there is one. `None`
+    /// when it runs out first.
+    pub fn recv(&self, timeout: Option<Duration>) -> Option<Event> {
+        // Never disconnected, as the bus holds a sender of its own.
+        match timeout {
+            Some(timeout) => self.receiver.recv_timeout(timeout).ok(),
+            None => self.receiver.recv().ok(),
+        }
+    }
+
+    /// The next event if one is already waiting.
+    pub fn try_recv(&self) -> Option<Event> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Lets a worker thread tell the main loop it has something new. The default
+/// wakes nothing, for an app run without a main loop, as in the tests.
+#[derive(Debug, Clone, Default)]
+pub struct Waker(Option<Sender<Event>>);
+
+impl Waker {
+    pub fn wake(&self) {
+        if let Some(sender) = &self.0 {
+            _ = sender.send(Event::Wake);
+        }
+    }
+}
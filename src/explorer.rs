@@ -0,0 +1,245 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A file or dir shown in the explorer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// How many expanded dirs it is below the root.
+    pub depth: usize,
+    pub is_dir: bool,
+}
+
+impl Entry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Tree of the files under a dir, with the dirs that were expanded open.
+#[derive(Debug)]
+pub struct Explorer {
+    root: PathBuf,
+    expanded: HashSet<PathBuf>,
+    /// What is shown, in order: dirs first, then files, each by name.
+    entries: Vec<Entry>,
+    pub selected: usize,
+}
+
+impl Explorer {
+    pub fn new(root: &Path) -> io::Result<Self> {
+        let mut explorer = Self {
+            root: root.canonicalize()?,
+            expanded: HashSet::new(),
+            entries: vec![],
+            selected: 0,
+        };
+        explorer.refresh()?;
+
+        Ok(explorer)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn is_expanded(&self, path: &Path) -> bool {
+        self.expanded.contains(path)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Expand the dirs down to `path` and select it.
+    pub fn reveal(&mut self, path: &Path) -> io::Result<()> {
+        let path = path.canonicalize()?;
+        let dirs = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root) && *dir != self.root);
+        self.expanded.extend(dirs.map(Path::to_path_buf));
+        self.refresh()?;
+        self.select_path(&path);
+
+        Ok(())
+    }
+
+    /// Read the tree again, keeping the selection on the same entry when it
+    /// is still there.
+    pub fn refresh(&mut self) -> io::Result<()> {
+        let selected = self.selected().map(|entry| entry.path.clone());
+        // Dirs removed since are forgotten.
+        self.expanded.retain(|dir| dir.is_dir());
+        let mut entries = vec![];
+        self.read_dir(&self.root, 0, &mut entries)?;
+        self.entries = entries;
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+        if let Some(selected) = selected {
+            self.select_path(&selected);
+        }
+
+        Ok(())
+    }
+
+    fn read_dir(
+        &self,
+        dir: &Path,
+        depth: usize,
+        entries: &mut Vec<Entry>,
+    ) -> io::Result<()> {
+        let mut children: Vec<Entry> = fs::read_dir(dir)?
+            .flatten()
+            .filter(|child| child.file_name() != ".git")
+            .map(|child| Entry {
+                path: child.path(),
+                depth,
+                is_dir: child.path().is_dir(),
+            })
+            .collect();
+        children.sort_by_key(|entry| (!entry.is_dir, entry.name()));
+
+        for entry in children {
+            let expand = entry.is_dir && self.expanded.contains(&entry.path);
+            let path = entry.path.clone();
+            entries.push(entry);
+            if expand {
+                self.read_dir(&path, depth + 1, entries)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn select_path(&mut self, path: &Path) {
+        if let Some(idx) = self.entries.iter().position(|e| e.path == path) {
+            self.selected = idx;
+        }
+    }
+
+    /// Expand the selected dir, or collapse it when it is expanded.
+    pub fn toggle(&mut self) -> io::Result<()> {
+        let Some(entry) = self.selected().filter(|entry| entry.is_dir) else {
+            return Ok(());
+        };
+        let path = entry.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+
+        self.refresh()
+    }
+
+    /// Collapse the dir holding the selected entry and select it.
+    pub fn collapse_parent(&mut self) -> io::Result<()> {
+        let Some(parent) = self.selected().and_then(|e| e.path.parent()) else {
+            return Ok(());
+        };
+        if parent == self.root {
+            return Ok(());
+        }
+        let parent = parent.to_path_buf();
+        self.expanded.remove(&parent);
+        self.refresh()?;
+        self.select_path(&parent);
+
+        Ok(())
+    }
+
+    /// The dir new files go in: the selected dir, or the dir of the
+    /// selected file.
+    fn target_dir(&self) -> PathBuf {
+        match self.selected() {
+            Some(entry) if entry.is_dir => entry.path.clone(),
+            Some(entry) => entry.path.parent().unwrap_or(&self.root).into(),
+            None => self.root.clone(),
+        }
+    }
+
+    /// Create `name` next to the selected entry, or in it when it is a dir.
+    /// A name ending in '/' makes a dir. Missing dirs on the way are made
+    /// too.
+    pub fn create(&mut self, name: &str) -> io::Result<PathBuf> {
+        let dir = self.target_dir();
+        let path = dir.join(name);
+        if path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", name),
+            ));
+        }
+        match name.ends_with('/') {
+            true => fs::create_dir_all(&path)?,
+            false => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::File::create(&path)?;
+            }
+        }
+        self.expanded.insert(dir);
+        self.reveal(&path)?;
+
+        Ok(path)
+    }
+
+    /// Rename the selected entry, within its dir. Returns the old and new
+    /// paths.
+    pub fn rename(&mut self, new_name: &str) -> io::Result<(PathBuf, PathBuf)> {
+        let Some(entry) = self.selected() else {
+            return Err(io::ErrorKind::NotFound.into());
+        };
+        let from = entry.path.clone();
+        let to = from.with_file_name(new_name);
+        if to.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", new_name),
+            ));
+        }
+        fs::rename(&from, &to)?;
+        if self.expanded.remove(&from) {
+            self.expanded.insert(to.clone());
+        }
+        self.refresh()?;
+        self.select_path(&to);
+
+        Ok((from, to))
+    }
+
+    /// Delete the selected entry, everything in it for a dir. Returns its
+    /// path.
+    pub fn delete(&mut self) -> io::Result<PathBuf> {
+        let Some(entry) = self.selected() else {
+            return Err(io::ErrorKind::NotFound.into());
+        };
+        let path = entry.path.clone();
+        match entry.is_dir {
+            true => fs::remove_dir_all(&path)?,
+            false => fs::remove_file(&path)?,
+        }
+        self.refresh()?;
+
+        Ok(path)
+    }
+}
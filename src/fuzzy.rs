@@ -0,0 +1,53 @@
+/// Score of a match that starts the candidate.
+const START_BONUS: i64 = 8;
+/// Score of a match right after a separator or at a camel case hump.
+const BOUNDARY_BONUS: i64 = 6;
+/// Score of a match right after the previous one.
+const CONSECUTIVE_BONUS: i64 = 5;
+/// Most a gap between two matches costs.
+const MAX_GAP_PENALTY: i64 = 3;
+
+/// How well `candidate` matches `pattern`, higher is better, or `None` when
+/// the chars of the pattern don't all appear in it in order. Matching
+/// ignores case unless the pattern has an uppercase char.
+pub fn score(pattern: &str, candidate: &str) -> Option<i64> {
+    let smart_case = pattern.chars().any(char::is_uppercase);
+    let eq = |a: char, b: char| match smart_case {
+        true => a == b,
+        false => a.to_lowercase().eq(b.to_lowercase()),
+    };
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut idx = 0;
+    let mut prev_match: Option<usize> = None;
+    for pattern_char in pattern.chars() {
+        let found = chars[idx..]
+            .iter()
+            .position(|char| eq(pattern_char, *char))?
+            + idx;
+
+        score += 1;
+        if found == 0 {
+            score += START_BONUS;
+        } else if is_boundary(chars[found - 1], chars[found]) {
+            score += BOUNDARY_BONUS;
+        }
+        match prev_match {
+            Some(prev) if prev + 1 == found => score += CONSECUTIVE_BONUS,
+            Some(prev) => {
+                score -= ((found - prev - 1) as i64).min(MAX_GAP_PENALTY)
+            }
+            None => score -= (found as i64).min(MAX_GAP_PENALTY),
+        }
+        prev_match = Some(found);
+        idx = found + 1;
+    }
+
+    Some(score)
+}
+
+fn is_boundary(prev: char, char: char) -> bool {
+    matches!(prev, '/' | '_' | '-' | '.' | ' ')
+        || (prev.is_lowercase() && char.is_uppercase())
+}
@@ -0,0 +1,454 @@
+use crate::app::{Notification, NotificationType};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// A git repository on disk. Everything is read through the `git` binary
+/// from the local `.git` dir, nothing touches the network.
+#[derive(Debug, Clone)]
+pub struct Repo {
+    root: PathBuf,
+}
+
+impl Repo {
+    /// Find the repository containing `path`, a file or dir.
+    pub fn discover<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let dir = match path.is_dir() {
+            true => path,
+            false => path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new(".")),
+        };
+
+        let root = run(dir, &["rev-parse", "--show-toplevel"]).map_err(
+            |err| match err {
+                Error::Command { stderr, .. }
+                    if stderr.contains("not a git repository") =>
+                {
+                    Error::NotARepo(dir.into())
+                }
+                err => err,
+            },
+        )?;
+
+        Ok(Self {
+            root: PathBuf::from(root.trim_end()),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Branch, upstream and working tree state.
+    pub fn status(&self) -> Result<Status> {
+        let out = run(&self.root, &["status", "--porcelain=v2", "--branch"])?;
+        let mut status = Status::parse(&out)?;
+        status.files = status
+            .files
+            .into_iter()
+            .map(|(path, file_status)| (self.root.join(path), file_status))
+            .collect();
+
+        Ok(status)
+    }
+
+    /// The last `count` commits on `HEAD`, newest first.
+    pub fn log(&self, count: usize) -> Result<Vec<Commit>> {
+        let count = format!("-{}", count);
+        let out = run(&self.root, &["log", &count, "--format=%h%x1f%s"])?;
+
+        out.lines()
+            .map(|line| {
+                let (hash, subject) = line
+                    .split_once('\x1f')
+                    .ok_or_else(|| Error::Parse(line.into()))?;
+                Ok(Commit {
+                    hash: hash.into(),
+                    subject: subject.into(),
+                })
+            })
+            .collect()
+    }
+
+    /// Path of a file relative to the root, as git names it.
+    pub fn relative_path<P: AsRef<Path>>(&self, path: P) -> Result<String> {
+        let path = fs::canonicalize(path)?;
+        let root = fs::canonicalize(&self.root)?;
+        let relative = path
+            .strip_prefix(&root)
+            .map_err(|_| Error::NotARepo(path.clone()))?;
+
+        Ok(relative.to_string_lossy().into_owned())
+    }
+
+    /// Contents of the file at `HEAD`, or `None` when it isn't committed.
+    pub fn head_contents<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<String>> {
+        let object = format!("HEAD:{}", self.relative_path(path)?);
+        // Fails without a word when there is no such object, as when the
+        // file or `HEAD` itself isn't committed, and loudly on real errors.
+        match run(&self.root, &["rev-parse", "--verify", "--quiet", &object]) {
+            Ok(_) => {}
+            Err(Error::Command { stderr, .. }) if stderr.is_empty() => {
+                return Ok(None)
+            }
+            Err(err) => return Err(err),
+        }
+
+        run(&self.root, &["show", &object]).map(Some)
+    }
+
+    /// Apply a patch to the index, leaving the working tree alone.
+    pub fn stage_patch(&self, patch: &str) -> Result<()> {
+        run_with_input(
+            &self.root,
+            &["apply", "--cached", "--unidiff-zero", "-"],
+            Some(patch),
+        )?;
+
+        Ok(())
+    }
+
+    /// Who last changed each line of `contents`, the current text of the
+    /// file at `path`. Lines that differ from `HEAD` are not committed yet.
+    pub fn blame<P: AsRef<Path>>(
+        &self,
+        path: P,
+        contents: &str,
+    ) -> Result<Blame> {
+        let path = self.relative_path(path)?;
+        let out = run_with_input(
+            &self.root,
+            &["blame", "--porcelain", "--contents", "-", "--", &path],
+            Some(contents),
+        )?;
+
+        Blame::parse(&out)
+    }
+
+    /// Status and log together, for the git summary.
+    pub fn summary(&self, log_count: usize) -> Result<Summary> {
+        let status = self.status()?;
+        // A repo without commits has no log to read.
+        let log = match status.has_commits {
+            true => self.log(log_count)?,
+            false => vec![],
+        };
+
+        Ok(Summary { status, log })
+    }
+}
+
+/// Run git in `dir` and return its stdout.
+fn run(dir: &Path, args: &[&str]) -> Result<String> {
+    run_with_input(dir, args, None)
+}
+
+/// Run git in `dir`, writing `input` to its stdin, and return its stdout.
+fn run_with_input(
+    dir: &Path,
+    args: &[&str],
+    input: Option<&str>,
+) -> Result<String> {
+    let mut child = Command::new("git")
+        .arg("--no-optional-locks")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            // Spawning in a dir that's gone fails the same way.
+            io::ErrorKind::NotFound if dir.is_dir() => Error::NotInstalled,
+            _ => err.into(),
+        })?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(Error::Command {
+            args: args.join(" ").into(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().into(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    /// `None` when `HEAD` is detached.
+    pub branch: Option<Box<str>>,
+    pub upstream: Option<Box<str>>,
+    /// Commits ahead of the upstream.
+    pub ahead: usize,
+    /// Commits behind the upstream.
+    pub behind: usize,
+    /// Files with changes in the index.
+    pub staged: usize,
+    /// Tracked files with changes not in the index.
+    pub unstaged: usize,
+    pub untracked: usize,
+    /// Files with merge conflicts.
+    pub conflicted: usize,
+    /// State of each changed file, by path. Untracked dirs are listed
+    /// rather than the files in them.
+    pub files: BTreeMap<PathBuf, FileStatus>,
+    has_commits: bool,
+}
+
+/// How a file differs from `HEAD`, the state that needs attention most
+/// first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileStatus {
+    Conflicted,
+    Unstaged,
+    Staged,
+    Untracked,
+}
+
+impl Status {
+    /// Parse the output of `git status --porcelain=v2 --branch`.
+    fn parse(out: &str) -> Result<Self> {
+        let mut status = Status::default();
+        for line in out.lines() {
+            let mut fields = line.split(' ');
+            match fields.next() {
+                Some("#") => match (fields.next(), fields.next()) {
+                    (Some("branch.oid"), Some(oid)) => {
+                        status.has_commits = oid != "(initial)"
+                    }
+                    (Some("branch.head"), Some(head)) => {
+                        status.branch =
+                            (head != "(detached)").then(|| head.into())
+                    }
+                    (Some("branch.upstream"), Some(upstream)) => {
+                        status.upstream = Some(upstream.into())
+                    }
+                    (Some("branch.ab"), Some(ahead)) => {
+                        status.ahead = parse_count(ahead, '+')?;
+                        let behind = fields.next().unwrap_or_default();
+                        status.behind = parse_count(behind, '-')?;
+                    }
+                    _ => {}
+                },
+                Some(kind @ ("1" | "2")) => {
+                    let xy = fields.next().unwrap_or_default();
+                    let mut xy = xy.chars();
+                    let staged = xy.next().is_some_and(|x| x != '.');
+                    let unstaged = xy.next().is_some_and(|y| y != '.');
+                    status.staged += staged as usize;
+                    status.unstaged += unstaged as usize;
+                    // Renames and copies have a score before the path, and
+                    // the original path after it.
+                    let skip = match kind {
+                        "1" => 6,
+                        _ => 7,
+                    };
+                    let path = fields.skip(skip).collect::<Vec<_>>().join(" ");
+                    let path = path.split('\t').next().unwrap_or_default();
+                    let file_status = match unstaged {
+                        true => FileStatus::Unstaged,
+                        false => FileStatus::Staged,
+                    };
+                    status.files.insert(path.into(), file_status);
+                }
+                Some("u") => {
+                    status.conflicted += 1;
+                    let path = fields.skip(8).collect::<Vec<_>>().join(" ");
+                    status.files.insert(path.into(), FileStatus::Conflicted);
+                }
+                Some("?") => {
+                    status.untracked += 1;
+                    let path = fields.collect::<Vec<_>>().join(" ");
+                    status.files.insert(path.into(), FileStatus::Untracked);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// The state of the file or dir at `path` most in need of attention,
+    /// of anything changed in it for a dir.
+    pub fn file_status(&self, path: &Path) -> Option<FileStatus> {
+        self.files
+            .iter()
+            .filter(|(changed, _)| {
+                changed.starts_with(path) || path.starts_with(changed)
+            })
+            .map(|(_, file_status)| *file_status)
+            .min()
+    }
+}
+
+fn parse_count(field: &str, sign: char) -> Result<usize> {
+    field
+        .strip_prefix(sign)
+        .and_then(|count| count.parse().ok())
+        .ok_or_else(|| Error::Parse(field.into()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: Box<str>,
+    pub subject: Box<str>,
+}
+
+/// The commit that last changed each line of a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blame {
+    commits: Vec<BlameCommit>,
+    /// Index into `commits` for each line.
+    lines: Vec<usize>,
+}
+
+impl Blame {
+    /// Parse the output of `git blame --porcelain`.
+    fn parse(out: &str) -> Result<Self> {
+        let mut blame = Blame::default();
+        let mut hashes: Vec<&str> = vec![];
+        let mut lines = out.lines();
+        while let Some(header) = lines.next() {
+            let hash = header
+                .split(' ')
+                .next()
+                .filter(|hash| !hash.is_empty())
+                .ok_or_else(|| Error::Parse(header.into()))?;
+
+            // Details are only given the first time a commit comes up.
+            let commit_idx = match hashes.iter().position(|h| *h == hash) {
+                Some(idx) => idx,
+                None => {
+                    hashes.push(hash);
+                    blame.commits.push(BlameCommit {
+                        hash: hash.into(),
+                        ..Default::default()
+                    });
+                    hashes.len() - 1
+                }
+            };
+
+            // Details run until the line itself, which starts with a tab.
+            for line in lines.by_ref() {
+                if line.starts_with('\t') {
+                    break;
+                }
+                let commit = &mut blame.commits[commit_idx];
+                match line.split_once(' ') {
+                    Some(("author", author)) => commit.author = author.into(),
+                    Some(("author-time", time)) => {
+                        commit.time = time
+                            .parse()
+                            .map_err(|_| Error::Parse(line.into()))?
+                    }
+                    Some(("summary", summary)) => {
+                        commit.summary = summary.into()
+                    }
+                    _ => {}
+                }
+            }
+            blame.lines.push(commit_idx);
+        }
+
+        Ok(blame)
+    }
+
+    /// The commit that last changed the line.
+    pub fn commit(&self, line_idx: usize) -> Option<&BlameCommit> {
+        let commit_idx = self.lines.get(line_idx)?;
+
+        self.commits.get(*commit_idx)
+    }
+
+    pub fn len_lines(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlameCommit {
+    /// The full hash, all zeros for lines not committed yet.
+    pub hash: Box<str>,
+    pub author: Box<str>,
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub summary: Box<str>,
+}
+
+impl BlameCommit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+
+    pub fn is_committed(&self) -> bool {
+        self.hash.chars().any(|char| char != '0')
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub status: Status,
+    pub log: Vec<Commit>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// There is no `git` to run.
+    NotInstalled,
+    NotARepo(PathBuf),
+    Command {
+        args: Box<str>,
+        stderr: Box<str>,
+    },
+    Parse(Box<str>),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Git error: ")?;
+
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::NotInstalled => write!(f, "git is not installed"),
+            Self::NotARepo(dir) => {
+                write!(f, "{} is not in a repository", dir.display())
+            }
+            Self::Command { args, stderr } => {
+                write!(f, "git {} failed: {}", args, stderr)
+            }
+            Self::Parse(text) => write!(f, "unexpected output {}", text),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<&Error> for Notification {
+    fn from(err: &Error) -> Self {
+        Notification::new(NotificationType::Error, err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,222 @@
+use crate::{
+    app::{Notification, NotificationType},
+    event::Waker,
+    walk,
+};
+use regex::Regex;
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+/// A line matching the pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// Relative to the directory searched.
+    pub path: Box<str>,
+    pub line_idx: usize,
+    /// Char of the line the match starts at.
+    pub col: usize,
+    pub text: Box<str>,
+}
+
+/// Search of the files under a directory for a pattern. Files are searched
+/// on another thread, and their matches come in as each is done.
+#[derive(Debug)]
+pub struct Grep {
+    root: PathBuf,
+    pattern: Box<str>,
+    matches: Vec<Match>,
+    /// The match highlighted in the list.
+    pub selected: usize,
+    /// Was the selected match jumped to, so stepping moves on from it.
+    visited: bool,
+    /// `None` once every file was searched.
+    search: Option<Receiver<Vec<Match>>>,
+    /// Set once the search is dropped, for its thread to stop at the next
+    /// file rather than the next match.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Grep {
+    /// Start searching the files under `root` for the regex `pattern`,
+    /// skipping hidden files and whatever the ignore files in it ignore.
+    pub fn new(pattern: &str, root: &Path, waker: Waker) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|err| Error::Pattern(err.to_string().into()))?;
+        let (sender, receiver) = mpsc::channel();
+        let search_root = root.to_path_buf();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            for (full_path, path) in walk::files(&search_root) {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                // Binary files aren't valid UTF-8, and are skipped with them.
+                let Ok(text) = fs::read_to_string(full_path) else {
+                    continue;
+                };
+                let matches = search(&regex, &path, &text);
+                if !matches.is_empty() {
+                    if sender.send(matches).is_err() {
+                        return;
+                    }
+                    waker.wake();
+                }
+            }
+            // The search is done once the sender is dropped.
+            drop(sender);
+            waker.wake();
+        });
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            pattern: pattern.into(),
+            matches: vec![],
+            selected: 0,
+            visited: false,
+            search: Some(receiver),
+            cancelled,
+        })
+    }
+
+    /// Are files still being searched.
+    pub fn searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Take the matches found since the last call.
+    pub fn update(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        loop {
+            match search.try_recv() {
+                Ok(matches) => self.matches.extend(matches),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.search = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn matches(&self) -> &[Match] {
+        &self.matches
+    }
+
+    pub fn selected(&self) -> Option<&Match> {
+        self.matches.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// The selected match, marked as visited.
+    pub fn visit_selected(&mut self) -> Option<&Match> {
+        self.visited = true;
+
+        self.matches.get(self.selected)
+    }
+
+    /// The match after the one last visited, or the selected one when none
+    /// was. `None` past the last.
+    pub fn visit_next(&mut self) -> Option<&Match> {
+        let next = match self.visited {
+            true => self.selected + 1,
+            false => self.selected,
+        };
+        if next >= self.matches.len() {
+            return None;
+        }
+        self.selected = next;
+
+        self.visit_selected()
+    }
+
+    /// The match before the one last visited. `None` before the first.
+    pub fn visit_prev(&mut self) -> Option<&Match> {
+        let prev = match self.visited {
+            true => self.selected.checked_sub(1)?,
+            false => self.selected,
+        };
+        if prev >= self.matches.len() {
+            return None;
+        }
+        self.selected = prev;
+
+        self.visit_selected()
+    }
+}
+
+impl Drop for Grep {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Every line of `text` the regex matches, at its first match.
+fn search(regex: &Regex, path: &str, text: &str) -> Vec<Match> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line_idx, line)| {
+            let found = regex.find(line)?;
+
+            Some(Match {
+                path: path.into(),
+                line_idx,
+                col: line[..found.start()].chars().count(),
+                text: line.into(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Pattern(Box<str>),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Grep error: ")?;
+
+        match self {
+            Self::Pattern(err) => write!(f, "invalid pattern, {}", err),
+        }
+    }
+}
+
+impl From<&Error> for Notification {
+    fn from(err: &Error) -> Self {
+        Notification::new(NotificationType::Error, err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
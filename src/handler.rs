@@ -1,19 +1,48 @@
 use crate::{
-    app::{self, App, Mode, Notification},
-    logger::Logger,
+    app::{self, App, Mode, Notification, NotificationType, PromptAction},
+    event::{self as bus, EventBus},
+    keymap::{Command, Key, Keymap, Lookup},
+    logger::{Level, LogView, Logger},
+    sign::SignKind,
+    ui,
+    window::{Direction, Split},
 };
 use crossterm::event::{
-    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton,
+    MouseEvent, MouseEventKind,
 };
+use std::{
+    collections::VecDeque,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Percent of its split a window grows or shrinks by at a time.
+const WINDOW_RESIZE_STEP: i16 = 5;
+
+/// Most keystrokes kept for the screencast.
+const MAX_KEYSTROKES: usize = 32;
+
+/// Lines scrolled by a turn of the mouse wheel.
+const MOUSE_SCROLL_LINES: isize = 3;
 
 #[derive(Default)]
 pub struct Handler {
     pub logger: Logger,
+    pub log_view: LogView,
+    keymap: Keymap,
     key_events: Vec<KeyEvent>,
     count: Option<usize>,
+    /// Keys of a sequence waiting for its next key, and when the last arrived.
+    pending: Vec<Key>,
+    pending_since: Option<Instant>,
     pub prev_sequences: Vec<Box<[KeyEvent]>>,
-    // alternate gray/black for each event. Not sure how
-    // to display modifiers...
+    /// The latest keys pressed, oldest first, and how many were in all.
+    keystrokes: VecDeque<Key>,
+    pressed: usize,
+    /// Show the latest keys pressed over the text.
+    pub screencast: bool,
 }
 
 impl Handler {
@@ -23,58 +52,572 @@ impl Handler {
         }
     }
 
-    pub fn listen(&mut self, app: &mut App) -> std::io::Result<()> {
-        match event::read()? {
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap
+    }
+
+    /// Keys typed so far of an unfinished sequence.
+    pub fn pending(&self) -> &[Key] {
+        &self.pending
+    }
+
+    /// Count typed so far for the next command.
+    pub fn count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// The latest keys pressed, oldest first, each with how many came
+    /// before it.
+    pub fn keystrokes(&self) -> impl DoubleEndedIterator<Item = (usize, &Key)> {
+        let first = self.pressed - self.keystrokes.len();
+        self.keystrokes
+            .iter()
+            .enumerate()
+            .map(move |(idx, key)| (first + idx, key))
+    }
+
+    /// Wait for the next event on the bus and handle it, with any queued
+    /// behind it. The pending sequence and the notification time out on
+    /// their own, and swap files are written on the tick they're due.
+    /// Returns whether anything may have changed on screen, so only then is
+    /// it drawn again.
+    pub fn listen(
+        &mut self,
+        app: &mut App,
+        bus: &EventBus,
+    ) -> std::io::Result<bool> {
+        let notif = app.notif_remaining().filter(|left| !left.is_zero());
+        let timeout = self.pending_remaining().into_iter().chain(notif).min();
+        let Some(event) = bus.recv(timeout) else {
+            if self.pending_remaining() == Some(Duration::ZERO) {
+                self.expire_pending(app);
+            }
+            self.log_notifs(app);
+            return Ok(true);
+        };
+
+        let mut changed = false;
+        let mut next = Some(event);
+        while let Some(event) = next {
+            changed |= match event {
+                bus::Event::Terminal(event) => {
+                    let redraw = redraws(&event);
+                    self.handle_event(event, app);
+                    redraw
+                }
+                bus::Event::Wake => {
+                    app.update_background();
+                    true
+                }
+                bus::Event::Tick => {
+                    let due = app.swap_remaining() == Some(Duration::ZERO);
+                    if due {
+                        app.write_swaps();
+                    }
+                    due
+                }
+                bus::Event::Input(err) => return Err(err),
+            };
+            next = bus.try_recv();
+        }
+        self.log_notifs(app);
+
+        Ok(changed)
+    }
+
+    pub fn handle_event(&mut self, event: Event, app: &mut App) {
+        match event {
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 self.handle_key_event(key_event, app);
             }
+            Event::Mouse(mouse_event) => {
+                self.handle_mouse_event(mouse_event, app)
+            }
+            Event::Paste(text) => self.handle_paste(&text, app),
+            Event::Resize(width, height) => app.resize(width, height),
+            Event::FocusGained => app.focus_gained(),
+            Event::FocusLost => {
+                if let Err(err) = app.focus_lost() {
+                    app.push_notif(Notification::from(&err))
+                }
+            }
             _ => {}
+        }
+        self.log_notifs(app);
+    }
+
+    /// Log what the app notified since the last call, errors included.
+    fn log_notifs(&mut self, app: &mut App) {
+        for (message_type, text) in app.take_unlogged() {
+            let level = match message_type {
+                NotificationType::Error => Level::Error,
+                NotificationType::Warning => Level::Warning,
+                NotificationType::Info | NotificationType::Success => {
+                    Level::Info
+                }
+            };
+            self.logger.log(level, text.into());
+        }
+    }
+
+    /// Click to move the cursor, drag to select and scroll with the wheel,
+    /// in whichever window is under the mouse.
+    pub fn handle_mouse_event(
+        &mut self,
+        mouse_event: MouseEvent,
+        app: &mut App,
+    ) {
+        // Like keys, the mouse doesn't reach the text past an overlay.
+        if app.prompt().is_some()
+            || app.picker().is_some()
+            || app.history().is_some()
+        {
+            return;
+        }
+        let Some(hit) = ui::hit(app, mouse_event.column, mouse_event.row)
+        else {
+            return;
         };
 
-        Ok(())
+        let result = match (mouse_event.kind, hit.pos) {
+            (MouseEventKind::Down(MouseButton::Left), Some(pos)) => {
+                self.take_pending();
+                self.reset_count();
+                if app.mode() != Mode::Insert {
+                    self.reset_keys();
+                }
+                app.close_popup();
+                app.unfocus_explorer();
+                app.click(hit.window, pos)
+            }
+            (MouseEventKind::Drag(MouseButton::Left), Some(pos))
+                if hit.window == app.windows().focused() =>
+            {
+                app.drag(pos);
+                Ok(())
+            }
+            (MouseEventKind::ScrollDown, _) => {
+                app.scroll_window(hit.window, MOUSE_SCROLL_LINES);
+                Ok(())
+            }
+            (MouseEventKind::ScrollUp, _) => {
+                app.scroll_window(hit.window, -MOUSE_SCROLL_LINES);
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            app.push_notif(Notification::from(&err))
+        }
+        app.update_completion();
+    }
+
+    /// Put pasted text in the prompt or picker if one is open, or into the
+    /// buffer in insert mode. Other modes ignore it, as its keys would be
+    /// run as commands.
+    pub fn handle_paste(&mut self, text: &str, app: &mut App) {
+        // A line of input has no room for line breaks.
+        let line: String = text
+            .chars()
+            .filter(|char| *char != '\n' && *char != '\r')
+            .collect();
+        if let Some(prompt) = app.prompt_mut() {
+            prompt.input.push_str(&line);
+            return;
+        }
+        if let Some(picker) = app.picker_mut() {
+            let query = format!("{}{}", picker.query(), line);
+            picker.set_query(query);
+            return;
+        }
+        if app.mode() != Mode::Insert {
+            return;
+        }
+
+        // Keys typed before the paste go in first.
+        let keys = self.take_pending();
+        let result = self.insert_keys(&keys, app).and_then(|_| app.paste(text));
+        if let Err(err) = result {
+            app.push_notif(Notification::from(&err))
+        }
+        app.update_completion();
     }
 
     pub fn handle_key_event(&mut self, key_event: KeyEvent, app: &mut App) {
-        if let Err(err) = match app.mode() {
-            Mode::Normal => self.handle_normal_mode_key_event(key_event, app),
-            Mode::Insert => self.handle_insert_mode_key_event(key_event, app),
-            Mode::GoTo => self.handle_go_to_mode_key_event(key_event, app),
-            Mode::Delete => self.handle_delete_mode_key_event(key_event, app),
-        } {
+        if self.keystrokes.len() == MAX_KEYSTROKES {
+            self.keystrokes.pop_front();
+        }
+        self.keystrokes.push_back(Key::from(key_event));
+        self.pressed += 1;
+        if self.pending_remaining() == Some(Duration::ZERO) {
+            self.expire_pending(app);
+        }
+        app.close_popup();
+
+        if app.prompt().is_some() {
+            if let Err(err) = self.prompt_key(key_event, app) {
+                app.push_notif(Notification::from(&err))
+            }
+            return;
+        }
+        if app.picker().is_some() {
+            if let Err(err) = self.picker_key(key_event, app) {
+                app.push_notif(Notification::from(&err))
+            }
+            return;
+        }
+        if app.history().is_some() {
+            self.history_key(key_event, app);
+            return;
+        }
+        if app.explorer_focused() && app.mode() == Mode::Normal {
+            if let Some(result) = self.explorer_key(key_event, app) {
+                if let Err(err) = result {
+                    app.push_notif(Notification::from(&err))
+                }
+                return;
+            }
+        }
+        if app.grep_list() && app.mode() == Mode::Normal {
+            if let Some(result) = self.grep_list_key(key_event, app) {
+                if let Err(err) = result {
+                    app.push_notif(Notification::from(&err))
+                }
+                return;
+            }
+        }
+
+        // What's typed in insert mode is undone in one go, anything else a
+        // key at a time.
+        if app.mode() != Mode::Insert {
+            app.end_undo_step();
+        }
+        if let Err(err) = self.dispatch(key_event, app) {
             app.push_notif(Notification::from(&err))
         };
+        app.update_completion();
+    }
+
+    /// Edit the prompt's input. Keys aren't looked up in the keymap while a
+    /// prompt is open.
+    fn prompt_key(
+        &mut self,
+        key_event: KeyEvent,
+        app: &mut App,
+    ) -> app::Result<()> {
+        let typed = key_event.modifiers.difference(KeyModifiers::SHIFT);
+        match key_event.code {
+            KeyCode::Enter => match app.prompt().map(|prompt| prompt.action) {
+                Some(PromptAction::Command) => self.run_command_line(app),
+                _ => return app.submit_prompt(),
+            },
+            KeyCode::Esc => app.cancel_prompt(),
+            KeyCode::Backspace => {
+                if let Some(prompt) = app.prompt_mut() {
+                    prompt.input.pop();
+                }
+            }
+            KeyCode::Char(char) if typed.is_empty() => {
+                if let Some(prompt) = app.prompt_mut() {
+                    prompt.input.push(char);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
     }
 
-    fn handle_normal_mode_key_event(
+    /// Edit the picker's query or move through its matches. Like a prompt,
+    /// the picker takes every key while it is open.
+    fn picker_key(
         &mut self,
         key_event: KeyEvent,
         app: &mut App,
     ) -> app::Result<()> {
+        let typed = key_event.modifiers.difference(KeyModifiers::SHIFT);
+        let ctrl = key_event.modifiers == KeyModifiers::CONTROL;
+        let Some(picker) = app.picker_mut() else {
+            return Ok(());
+        };
         match key_event.code {
+            KeyCode::Enter => return app.submit_picker(),
+            KeyCode::Esc => app.close_picker(),
+            KeyCode::Down => picker.select_next(),
+            KeyCode::Up => picker.select_prev(),
+            KeyCode::Char('n') if ctrl => picker.select_next(),
+            KeyCode::Char('p') if ctrl => picker.select_prev(),
+            KeyCode::Backspace => {
+                let mut query = picker.query().to_string();
+                query.pop();
+                picker.set_query(query);
+            }
+            KeyCode::Char(char) if typed.is_empty() => {
+                let query = format!("{}{}", picker.query(), char);
+                picker.set_query(query);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Search or move through the notification history. It takes every key
+    /// while it is open.
+    fn history_key(&mut self, key_event: KeyEvent, app: &mut App) {
+        let typed = key_event.modifiers.difference(KeyModifiers::SHIFT);
+        let ctrl = key_event.modifiers == KeyModifiers::CONTROL;
+        let len = app.history_matches().len();
+        let Some(history) = app.history_mut() else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Enter => app.close_history(),
+            KeyCode::Char('l') if ctrl => app.clear_notifications(),
+            KeyCode::Down => history.selected += 1,
             KeyCode::Up => {
+                history.selected = history.selected.saturating_sub(1)
+            }
+            KeyCode::Char('n') if ctrl => history.selected += 1,
+            KeyCode::Char('p') if ctrl => {
+                history.selected = history.selected.saturating_sub(1)
+            }
+            KeyCode::Backspace => {
+                history.query.pop();
+                history.selected = 0;
+            }
+            KeyCode::Char(char) if typed.is_empty() => {
+                history.query.push(char);
+                history.selected = 0;
+            }
+            _ => {}
+        }
+        if let Some(history) = app.history_mut() {
+            history.selected = history.selected.min(len.saturating_sub(1));
+        }
+    }
+
+    /// Run the command named in the command line.
+    fn run_command_line(&mut self, app: &mut App) {
+        let Some(prompt) = app.prompt() else {
+            return;
+        };
+        let name = prompt.input.trim().to_string();
+        app.cancel_prompt();
+        if name.is_empty() {
+            return;
+        }
+        let result = match Command::from_str(&name) {
+            Ok(command) => {
+                // The enter that submitted the line is the key that ran it.
+                let key_event =
+                    KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+                self.execute(command, key_event, app)
+            }
+            Err(err) => return app.push_notif(Notification::from(&err)),
+        };
+        if let Err(err) = result {
+            app.push_notif(Notification::from(&err))
+        }
+    }
+
+    /// Move through the explorer and act on the selected entry. `None` for
+    /// keys the explorer doesn't take.
+    fn explorer_key(
+        &mut self,
+        key_event: KeyEvent,
+        app: &mut App,
+    ) -> Option<app::Result<()>> {
+        let typed = key_event.modifiers.difference(KeyModifiers::SHIFT);
+        if !typed.is_empty() {
+            return None;
+        }
+        let explorer = app.explorer_mut()?;
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => explorer.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => explorer.select_prev(),
+            KeyCode::Enter | KeyCode::Char('l') => {
+                return Some(app.explorer_open())
+            }
+            KeyCode::Char('h') => {
+                return Some(
+                    explorer.collapse_parent().map_err(app::Error::from),
+                )
+            }
+            KeyCode::Char('a') => app.start_create_file(),
+            KeyCode::Char('r') => app.start_rename_file(),
+            KeyCode::Char('d') => app.start_delete_file(),
+            KeyCode::Esc => app.unfocus_explorer(),
+            KeyCode::Char('q') => return Some(app.toggle_explorer()),
+            _ => return None,
+        }
+
+        Some(Ok(()))
+    }
+
+    /// Move through the search results or open one. `None` for keys the
+    /// list doesn't take, which do what they always do.
+    fn grep_list_key(
+        &mut self,
+        key_event: KeyEvent,
+        app: &mut App,
+    ) -> Option<app::Result<()>> {
+        if !key_event.modifiers.is_empty() {
+            return None;
+        }
+        let grep = app.grep_mut()?;
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => grep.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => grep.select_prev(),
+            KeyCode::Enter => return Some(app.open_selected_match()),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_grep_list(),
+            _ => return None,
+        }
+
+        Some(Ok(()))
+    }
+
+    /// Time left before the pending sequence is dropped.
+    fn pending_remaining(&self) -> Option<Duration> {
+        self.pending_since
+            .map(|since| self.keymap.timeout().saturating_sub(since.elapsed()))
+    }
+
+    /// Drop a pending sequence that timed out. In insert mode the keys are
+    /// typed out instead.
+    fn expire_pending(&mut self, app: &mut App) {
+        let keys = self.take_pending();
+        if app.mode() == Mode::Insert {
+            if let Err(err) = self.insert_keys(&keys, app) {
+                app.push_notif(Notification::from(&err))
+            }
+        }
+    }
+
+    fn take_pending(&mut self) -> Vec<Key> {
+        self.pending_since = None;
+        std::mem::take(&mut self.pending)
+    }
+
+    fn dispatch(
+        &mut self,
+        key_event: KeyEvent,
+        app: &mut App,
+    ) -> app::Result<()> {
+        let key = Key::from(key_event);
+        let mode = app.mode();
+
+        let takes_count =
+            matches!(mode, Mode::Normal | Mode::Delete | Mode::Visual);
+        let is_digit = matches!(
+            (key.code(), key.modifiers()),
+            (KeyCode::Char(char), KeyModifiers::NONE) if char.is_ascii_digit()
+        );
+        if takes_count
+            && is_digit
+            && self.pending.is_empty()
+            && self.keymap.lookup(mode, &[key]) == Lookup::Unmapped
+        {
+            self.add_count_digit(key_event);
+            return Ok(());
+        }
+
+        self.pending.push(key);
+        match self.keymap.lookup(mode, &self.pending) {
+            Lookup::Command(command) => {
+                self.take_pending();
+                self.execute(command, key_event, app)
+            }
+            Lookup::Pending => {
+                self.pending_since = Some(Instant::now());
+                Ok(())
+            }
+            Lookup::Unmapped if self.pending.len() > 1 => {
+                // The prefix led nowhere. Let the last key start afresh.
+                let mut keys = self.take_pending();
+                keys.pop();
+                if mode == Mode::Insert {
+                    self.insert_keys(&keys, app)?;
+                }
+                self.dispatch(key_event, app)
+            }
+            Lookup::Unmapped => {
+                self.take_pending();
+                match mode {
+                    Mode::Insert => {
+                        self.key_events.push(key_event);
+                        self.insert_keys(&[key], app)
+                    }
+                    Mode::Normal => {
+                        self.reset_count();
+                        Err(app::Error::KeyUnmapped)
+                    }
+                    _ => Err(app::Error::KeyUnmapped),
+                }
+            }
+        }
+    }
+
+    /// Type out keys that aren't bound in insert mode.
+    fn insert_keys(&mut self, keys: &[Key], app: &mut App) -> app::Result<()> {
+        for key in keys {
+            let typed = key.modifiers().difference(KeyModifiers::SHIFT);
+            match key.code() {
+                KeyCode::Char(char) if typed.is_empty() => {
+                    app.insert_char_before(char)?;
+                    app.move_right(1);
+                    app.trigger_completion(char);
+                }
+                _ => return Err(app::Error::KeyUnmapped),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        command: Command,
+        key_event: KeyEvent,
+        app: &mut App,
+    ) -> app::Result<()> {
+        // Go to is a one-shot mode, whatever is run from it lands in normal.
+        if app.mode() == Mode::GoTo {
+            app.enter_mode(Mode::Normal);
+        }
+
+        match command {
+            Command::MoveUp => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_up(count)?
             }
-            KeyCode::Down => {
+            Command::MoveDown => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_down(count)?
             }
-            KeyCode::Left => {
+            Command::MoveLeft => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_left(count)?
             }
-            KeyCode::Right => {
+            Command::MoveRight => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_right(count)
             }
-            KeyCode::Char('G') => {
+            Command::MoveToFileStart => {
+                app.move_to_start_of_file()?;
+            }
+            Command::MoveToFileEnd => {
                 let count = self.count.take();
                 app.move_to_end_of_file()?;
                 if count.is_some() {
                     return Err(app::Error::CountRedundant);
                 };
             }
-            KeyCode::Char('w') => {
+            Command::MoveWordForwardStart => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_word(
                     count,
@@ -83,7 +626,7 @@ impl Handler {
                     app::Boundary::Start,
                 )?
             }
-            KeyCode::Char('W') => {
+            Command::MoveLongWordForwardStart => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_word(
                     count,
@@ -92,7 +635,7 @@ impl Handler {
                     app::Boundary::Start,
                 )?
             }
-            KeyCode::Char('e') => {
+            Command::MoveWordForwardEnd => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_word(
                     count,
@@ -101,7 +644,7 @@ impl Handler {
                     app::Boundary::End,
                 )?
             }
-            KeyCode::Char('E') => {
+            Command::MoveLongWordForwardEnd => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_word(
                     count,
@@ -110,7 +653,7 @@ impl Handler {
                     app::Boundary::End,
                 )?
             }
-            KeyCode::Char('b') => {
+            Command::MoveWordBackwardStart => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_word(
                     count,
@@ -119,7 +662,7 @@ impl Handler {
                     app::Boundary::Start,
                 )?
             }
-            KeyCode::Char('B') => {
+            Command::MoveLongWordBackwardStart => {
                 let count = self.count.take().unwrap_or(1);
                 app.move_word(
                     count,
@@ -128,21 +671,30 @@ impl Handler {
                     app::Boundary::Start,
                 )?
             }
-            KeyCode::Home => {
+            Command::MoveWordBackwardEnd => {
+                let count = self.count.take().unwrap_or(1);
+                app.move_word(
+                    count,
+                    app::Direction::Backward,
+                    app::Length::Short,
+                    app::Boundary::End,
+                )?
+            }
+            Command::MoveLineStart => {
                 let count = self.count.take();
                 app.move_start_line()?;
                 if count.is_some() {
                     return Err(app::Error::CountRedundant);
                 };
             }
-            KeyCode::End => {
+            Command::MoveLineEnd => {
                 let count = self.count.take();
                 app.move_end_line()?;
                 if count.is_some() {
                     return Err(app::Error::CountRedundant);
                 };
             }
-            KeyCode::Char('i') => {
+            Command::InsertMode => {
                 self.key_events = vec![key_event];
                 let pos: (usize, usize) = app.buffer.cursor.into();
                 if app.buffer.in_rope_bounds(pos)
@@ -156,184 +708,206 @@ impl Handler {
                     return Err(app::Error::CursorOutOfBounds);
                 }
             }
-            KeyCode::Char('g') => {
+            Command::GoToMode => {
                 self.key_events = vec![key_event];
                 app.enter_mode(Mode::GoTo);
                 if self.count.take().is_some() {
                     return Err(app::Error::CountRedundant);
                 };
             }
-            KeyCode::Char('d') => {
+            Command::DeleteMode => {
                 self.key_events = vec![key_event];
                 app.enter_mode(Mode::Delete);
                 if self.count.take().is_some() {
                     return Err(app::Error::CountRedundant);
                 };
             }
-            KeyCode::Char('c') | KeyCode::Char('C') => {
-                if key_event.modifiers == KeyModifiers::CONTROL {
-                    app.quit();
-                    // TODO: Save logs
-                }
-            }
-            KeyCode::Char(numb) if numb.is_ascii_digit() => {
-                self.add_count_digit(key_event);
-            }
-            _ => {
-                self.reset_count();
-                return Err(app::Error::KeyUnmapped);
+            Command::VisualMode => {
+                self.key_events = vec![key_event];
+                app.enter_mode(Mode::Visual);
+                if self.count.take().is_some() {
+                    return Err(app::Error::CountRedundant);
+                };
             }
-        }
-
-        Ok(())
-    }
-
-    /// Count will be zero and keys will be 'i' when insert mode is entered.
-    fn handle_insert_mode_key_event(
-        &mut self,
-        key_event: KeyEvent,
-        app: &mut App,
-    ) -> app::Result<()> {
-        match key_event.code {
-            KeyCode::Char(char) => {
-                self.key_events.push(key_event);
-                app.insert_char_before(char)?;
-                app.move_right(1);
+            Command::NormalMode => {
+                if app.mode() == Mode::Insert {
+                    self.flush_keys();
+                    let _ = app.move_left(1);
+                } else {
+                    self.reset_keys();
+                    self.reset_count();
+                }
+                app.enter_mode(Mode::Normal)
             }
-            KeyCode::Enter => {
+            Command::InsertNewline => {
+                let completing = app
+                    .completion()
+                    .is_some_and(|completion| completion.selected().is_some());
+                if completing {
+                    return app.accept_completion();
+                }
                 self.key_events.push(key_event);
                 app.insert_char_before('\n')?;
                 app.move_down(1)?;
                 app.move_start_line()?;
             }
-            KeyCode::Esc => {
-                self.flush_keys();
-                let _ = app.move_left(1);
-                app.enter_mode(Mode::Normal)
-            }
-            _ => return Err(app::Error::KeyUnmapped),
-        }
-
-        Ok(())
-    }
-
-    /// Count will be zero and keys will be 'g' when insert mode is entered.
-    fn handle_go_to_mode_key_event(
-        &mut self,
-        key_event: KeyEvent,
-        app: &mut App,
-    ) -> app::Result<()> {
-        match key_event.code {
-            KeyCode::Char('g') => {
+            Command::DeleteLines => {
+                let count = self.count.take().unwrap_or(1);
+                app.delete_lines(count)?;
                 app.enter_mode(Mode::Normal);
-                app.move_to_start_of_file()?;
+                self.reset_count();
             }
-            KeyCode::Esc => {
-                app.enter_mode(Mode::Normal);
+            Command::DeleteWordForwardStart => {
+                self.delete_word(app, app::Length::Short, app::Boundary::Start)?
             }
-            KeyCode::Char('e') => {
-                let count = self.count.take().unwrap_or(1);
-
-                app.enter_mode(Mode::Normal);
-                app.move_word(
-                    count,
-                    app::Direction::Backward,
-                    app::Length::Short,
-                    app::Boundary::Start,
-                )?;
+            Command::DeleteLongWordForwardStart => {
+                self.delete_word(app, app::Length::Long, app::Boundary::Start)?
             }
-            KeyCode::Char('E') => {
-                let count = self.count.take().unwrap_or(1);
-
-                app.enter_mode(Mode::Normal);
-                app.move_word(
-                    count,
-                    app::Direction::Backward,
-                    app::Length::Short,
-                    app::Boundary::End,
-                )?;
+            Command::DeleteWordForwardEnd => {
+                self.delete_word(app, app::Length::Short, app::Boundary::End)?
             }
-            _ => return Err(app::Error::KeyUnmapped),
-        };
-
-        Ok(())
-    }
-
-    /// Count will be zero and keys will be 'd' when insert mode is entered.
-    fn handle_delete_mode_key_event(
-        &mut self,
-        key_event: KeyEvent,
-        app: &mut App,
-    ) -> app::Result<()> {
-        match key_event.code {
-            KeyCode::Char('d') => {
-                let count = self.count.take().unwrap_or(1);
-                app.delete_lines(count)?;
-                app.enter_mode(Mode::Normal);
+            Command::DeleteLongWordForwardEnd => {
+                self.delete_word(app, app::Length::Long, app::Boundary::End)?
+            }
+            Command::DeleteSelection => {
                 self.reset_count();
+                app.delete_selection()?
             }
-            KeyCode::Char('w') => {
+            Command::Undo => {
                 let count = self.count.take().unwrap_or(1);
-                app.enter_mode(Mode::Normal);
-
-                app.delete_word(
-                    count,
-                    app::Direction::Forward,
-                    app::Length::Short,
-                    app::Boundary::Start,
-                )?;
-                self.reset_count();
+                app.undo(count)?
             }
-            KeyCode::Char('W') => {
+            Command::Redo => {
                 let count = self.count.take().unwrap_or(1);
-                app.enter_mode(Mode::Normal);
-
-                app.delete_word(
-                    count,
-                    app::Direction::Forward,
-                    app::Length::Long,
-                    app::Boundary::Start,
-                )?;
-                self.reset_count();
+                app.redo(count)?
             }
-            KeyCode::Char('e') => {
+            Command::Earlier => {
                 let count = self.count.take().unwrap_or(1);
-                app.enter_mode(Mode::Normal);
-
-                app.delete_word(
-                    count,
-                    app::Direction::Forward,
-                    app::Length::Short,
-                    app::Boundary::End,
-                )?;
-                self.reset_count();
+                app.earlier(count)?
             }
-            KeyCode::Char('E') => {
+            Command::Later => {
                 let count = self.count.take().unwrap_or(1);
-                app.enter_mode(Mode::Normal);
-
-                app.delete_word(
-                    count,
-                    app::Direction::Forward,
-                    app::Length::Long,
-                    app::Boundary::End,
-                )?;
-                self.reset_count();
+                app.later(count)?
+            }
+            Command::ToggleMark => app.toggle_marker(SignKind::Mark),
+            Command::ToggleBreakpoint => {
+                app.toggle_marker(SignKind::Breakpoint)
+            }
+            Command::Save => app.save()?,
+            Command::NextHunk => app.move_next_hunk()?,
+            Command::PrevHunk => app.move_prev_hunk()?,
+            Command::PreviewHunk => app.toggle_hunk_preview(),
+            Command::RevertHunk => app.revert_hunk()?,
+            Command::StageHunk => app.stage_hunk()?,
+            Command::ToggleBlame => app.toggle_blame(),
+            Command::GoToDefinition => app.goto_definition()?,
+            Command::GoToReferences => app.references()?,
+            Command::Hover => app.hover()?,
+            Command::Rename => app.start_rename()?,
+            Command::Format => app.format()?,
+            Command::NextDiagnostic => app.move_next_diagnostic()?,
+            Command::PrevDiagnostic => app.move_prev_diagnostic()?,
+            Command::ToggleDiagnosticList => app.toggle_diagnostic_list(),
+            Command::CompleteNext => app.complete_next()?,
+            Command::CompletePrev => app.complete_prev()?,
+            Command::NextTabStop => app.next_tab_stop()?,
+            Command::FilePicker => app.open_picker(Path::new(".")),
+            Command::Grep => app.open_prompt(PromptAction::Grep, String::new()),
+            Command::NextMatch => app.move_next_match()?,
+            Command::PrevMatch => app.move_prev_match()?,
+            Command::ToggleGrepList => app.toggle_grep_list()?,
+            Command::CommandLine => {
+                app.open_prompt(PromptAction::Command, String::new())
+            }
+            Command::ToggleExplorer => app.toggle_explorer()?,
+            Command::SplitHorizontal => app.split_window(Split::Horizontal),
+            Command::SplitVertical => app.split_window(Split::Vertical),
+            Command::FocusWindowLeft => app.focus_window(Direction::Left)?,
+            Command::FocusWindowDown => app.focus_window(Direction::Down)?,
+            Command::FocusWindowUp => app.focus_window(Direction::Up)?,
+            Command::FocusWindowRight => app.focus_window(Direction::Right)?,
+            Command::CloseWindow => app.close_window()?,
+            Command::GrowWindowHeight => {
+                self.resize_window(app, Split::Horizontal, 1)?
+            }
+            Command::ShrinkWindowHeight => {
+                self.resize_window(app, Split::Horizontal, -1)?
+            }
+            Command::GrowWindowWidth => {
+                self.resize_window(app, Split::Vertical, 1)?
+            }
+            Command::ShrinkWindowWidth => {
+                self.resize_window(app, Split::Vertical, -1)?
+            }
+            Command::CycleTheme => {
+                if let Err(err) = app.cycle_theme() {
+                    self.logger.log(Level::Error, err.to_string());
+                }
             }
-            KeyCode::Char(numb) if numb.is_ascii_digit() => {
-                self.add_count_digit(key_event);
+            Command::NotificationHistory => app.open_history(),
+            Command::ClearNotifications => app.clear_notifications(),
+            Command::CycleLogLevel => {
+                self.log_view.level = self.log_view.level.next();
+                self.log_view.scroll = 0;
             }
-            KeyCode::Esc => {
-                self.reset_keys();
-                self.reset_count();
-                app.enter_mode(Mode::Normal);
+            Command::ScrollLogsUp => {
+                let count = self.count.take().unwrap_or(1);
+                self.log_view.scroll =
+                    self.log_view.scroll.saturating_sub(count);
+            }
+            Command::ScrollLogsDown => {
+                let count = self.count.take().unwrap_or(1);
+                let level = self.log_view.level;
+                let shown = self
+                    .logger
+                    .logs()
+                    .filter(|log| log.level() >= level)
+                    .count();
+                // The oldest log stays in view.
+                self.log_view.scroll =
+                    (self.log_view.scroll + count).min(shown.saturating_sub(1));
+            }
+            Command::ToggleScreencast => self.screencast = !self.screencast,
+            Command::Quit => {
+                app.quit();
+                self.log_notifs(app);
+                // Whatever failed to be written as it was logged gets a last
+                // try, with nowhere left to show a failure.
+                _ = self.logger.flush();
             }
-            _ => return Err(app::Error::KeyUnmapped),
         }
 
         Ok(())
     }
 
+    fn delete_word(
+        &mut self,
+        app: &mut App,
+        length: app::Length,
+        boundary: app::Boundary,
+    ) -> app::Result<()> {
+        let count = self.count.take().unwrap_or(1);
+        app.enter_mode(Mode::Normal);
+
+        app.delete_word(count, app::Direction::Forward, length, boundary)?;
+        self.reset_count();
+
+        Ok(())
+    }
+
+    /// Resize the focused window by a step for each of the count.
+    fn resize_window(
+        &mut self,
+        app: &mut App,
+        split: Split,
+        sign: i16,
+    ) -> app::Result<()> {
+        // Past this many steps a window is at its smallest or largest.
+        let count = self.count.take().unwrap_or(1).min(100) as i16;
+
+        app.resize_window(split, sign * count * WINDOW_RESIZE_STEP)
+    }
+
     fn reset_keys(&mut self) {
         self.key_events = vec![];
     }
@@ -358,3 +932,13 @@ impl Handler {
         };
     }
 }
+
+/// Does the event change anything on screen. Key releases and mouse moves
+/// come through too, and are ignored.
+fn redraws(event: &Event) -> bool {
+    match event {
+        Event::Key(key_event) => key_event.kind == KeyEventKind::Press,
+        Event::Mouse(mouse_event) => mouse_event.kind != MouseEventKind::Moved,
+        _ => true,
+    }
+}
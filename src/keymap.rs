@@ -0,0 +1,704 @@
+use crate::app::{Mode, Notification, NotificationType};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    str::FromStr,
+    time::Duration,
+};
+
+/// Every action a key can be bound to, named as it is written in the config.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Command {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveToFileStart,
+    MoveToFileEnd,
+    MoveLineStart,
+    MoveLineEnd,
+    MoveWordForwardStart,
+    MoveLongWordForwardStart,
+    MoveWordForwardEnd,
+    MoveLongWordForwardEnd,
+    MoveWordBackwardStart,
+    MoveLongWordBackwardStart,
+    MoveWordBackwardEnd,
+    InsertMode,
+    GoToMode,
+    DeleteMode,
+    VisualMode,
+    NormalMode,
+    InsertNewline,
+    DeleteLines,
+    DeleteWordForwardStart,
+    DeleteLongWordForwardStart,
+    DeleteWordForwardEnd,
+    DeleteLongWordForwardEnd,
+    DeleteSelection,
+    Undo,
+    Redo,
+    Earlier,
+    Later,
+    ToggleMark,
+    ToggleBreakpoint,
+    Save,
+    NextHunk,
+    PrevHunk,
+    PreviewHunk,
+    RevertHunk,
+    StageHunk,
+    ToggleBlame,
+    GoToDefinition,
+    GoToReferences,
+    Hover,
+    Rename,
+    Format,
+    NextDiagnostic,
+    PrevDiagnostic,
+    ToggleDiagnosticList,
+    CompleteNext,
+    CompletePrev,
+    NextTabStop,
+    FilePicker,
+    Grep,
+    NextMatch,
+    PrevMatch,
+    ToggleGrepList,
+    CommandLine,
+    ToggleExplorer,
+    SplitHorizontal,
+    SplitVertical,
+    FocusWindowLeft,
+    FocusWindowDown,
+    FocusWindowUp,
+    FocusWindowRight,
+    CloseWindow,
+    GrowWindowHeight,
+    ShrinkWindowHeight,
+    GrowWindowWidth,
+    ShrinkWindowWidth,
+    NotificationHistory,
+    ClearNotifications,
+    CycleLogLevel,
+    ScrollLogsUp,
+    ScrollLogsDown,
+    ToggleScreencast,
+    CycleTheme,
+    Quit,
+}
+
+const COMMANDS: [(&str, Command); 77] = [
+    ("move_up", Command::MoveUp),
+    ("move_down", Command::MoveDown),
+    ("move_left", Command::MoveLeft),
+    ("move_right", Command::MoveRight),
+    ("move_to_file_start", Command::MoveToFileStart),
+    ("move_to_file_end", Command::MoveToFileEnd),
+    ("move_line_start", Command::MoveLineStart),
+    ("move_line_end", Command::MoveLineEnd),
+    ("move_word_forward_start", Command::MoveWordForwardStart),
+    (
+        "move_long_word_forward_start",
+        Command::MoveLongWordForwardStart,
+    ),
+    ("move_word_forward_end", Command::MoveWordForwardEnd),
+    (
+        "move_long_word_forward_end",
+        Command::MoveLongWordForwardEnd,
+    ),
+    ("move_word_backward_start", Command::MoveWordBackwardStart),
+    (
+        "move_long_word_backward_start",
+        Command::MoveLongWordBackwardStart,
+    ),
+    ("move_word_backward_end", Command::MoveWordBackwardEnd),
+    ("insert_mode", Command::InsertMode),
+    ("goto_mode", Command::GoToMode),
+    ("delete_mode", Command::DeleteMode),
+    ("visual_mode", Command::VisualMode),
+    ("normal_mode", Command::NormalMode),
+    ("insert_newline", Command::InsertNewline),
+    ("delete_lines", Command::DeleteLines),
+    ("delete_word_forward_start", Command::DeleteWordForwardStart),
+    (
+        "delete_long_word_forward_start",
+        Command::DeleteLongWordForwardStart,
+    ),
+    ("delete_word_forward_end", Command::DeleteWordForwardEnd),
+    (
+        "delete_long_word_forward_end",
+        Command::DeleteLongWordForwardEnd,
+    ),
+    ("delete_selection", Command::DeleteSelection),
+    ("undo", Command::Undo),
+    ("redo", Command::Redo),
+    ("earlier", Command::Earlier),
+    ("later", Command::Later),
+    ("toggle_mark", Command::ToggleMark),
+    ("toggle_breakpoint", Command::ToggleBreakpoint),
+    ("save", Command::Save),
+    ("next_hunk", Command::NextHunk),
+    ("prev_hunk", Command::PrevHunk),
+    ("preview_hunk", Command::PreviewHunk),
+    ("revert_hunk", Command::RevertHunk),
+    ("stage_hunk", Command::StageHunk),
+    ("toggle_blame", Command::ToggleBlame),
+    ("goto_definition", Command::GoToDefinition),
+    ("goto_references", Command::GoToReferences),
+    ("hover", Command::Hover),
+    ("rename", Command::Rename),
+    ("format", Command::Format),
+    ("next_diagnostic", Command::NextDiagnostic),
+    ("prev_diagnostic", Command::PrevDiagnostic),
+    ("toggle_diagnostic_list", Command::ToggleDiagnosticList),
+    ("complete_next", Command::CompleteNext),
+    ("complete_prev", Command::CompletePrev),
+    ("next_tab_stop", Command::NextTabStop),
+    ("file_picker", Command::FilePicker),
+    ("grep", Command::Grep),
+    // Named after the quickfix commands they stand in for.
+    ("cnext", Command::NextMatch),
+    ("cprev", Command::PrevMatch),
+    ("toggle_grep_list", Command::ToggleGrepList),
+    ("command_line", Command::CommandLine),
+    ("toggle_explorer", Command::ToggleExplorer),
+    ("split_horizontal", Command::SplitHorizontal),
+    ("split_vertical", Command::SplitVertical),
+    ("focus_window_left", Command::FocusWindowLeft),
+    ("focus_window_down", Command::FocusWindowDown),
+    ("focus_window_up", Command::FocusWindowUp),
+    ("focus_window_right", Command::FocusWindowRight),
+    ("close_window", Command::CloseWindow),
+    ("grow_window_height", Command::GrowWindowHeight),
+    ("shrink_window_height", Command::ShrinkWindowHeight),
+    ("grow_window_width", Command::GrowWindowWidth),
+    ("shrink_window_width", Command::ShrinkWindowWidth),
+    ("notification_history", Command::NotificationHistory),
+    ("clear_notifications", Command::ClearNotifications),
+    ("cycle_log_level", Command::CycleLogLevel),
+    ("scroll_logs_up", Command::ScrollLogsUp),
+    ("scroll_logs_down", Command::ScrollLogsDown),
+    ("toggle_screencast", Command::ToggleScreencast),
+    ("cycle_theme", Command::CycleTheme),
+    ("quit", Command::Quit),
+];
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        COMMANDS
+            .iter()
+            .find(|(_, command)| command == self)
+            .map(|(name, _)| *name)
+            .expect("every command should be named")
+    }
+}
+
+impl FromStr for Command {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        COMMANDS
+            .iter()
+            .find(|(command_name, _)| *command_name == name)
+            .map(|(_, command)| *command)
+            .ok_or_else(|| Error::UnknownCommand(name.into()))
+    }
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Bindings every keymap starts from, before the config is applied.
+const DEFAULT_BINDINGS: [(Mode, &str, Command); 94] = [
+    (Mode::Normal, "<up>", Command::MoveUp),
+    (Mode::Normal, "<down>", Command::MoveDown),
+    (Mode::Normal, "<left>", Command::MoveLeft),
+    (Mode::Normal, "<right>", Command::MoveRight),
+    (Mode::Normal, "G", Command::MoveToFileEnd),
+    (Mode::Normal, "w", Command::MoveWordForwardStart),
+    (Mode::Normal, "W", Command::MoveLongWordForwardStart),
+    (Mode::Normal, "e", Command::MoveWordForwardEnd),
+    (Mode::Normal, "E", Command::MoveLongWordForwardEnd),
+    (Mode::Normal, "b", Command::MoveWordBackwardStart),
+    (Mode::Normal, "B", Command::MoveLongWordBackwardStart),
+    (Mode::Normal, "<home>", Command::MoveLineStart),
+    (Mode::Normal, "<end>", Command::MoveLineEnd),
+    (Mode::Normal, "i", Command::InsertMode),
+    (Mode::Normal, "g", Command::GoToMode),
+    (Mode::Normal, "d", Command::DeleteMode),
+    (Mode::Normal, "v", Command::VisualMode),
+    (Mode::Normal, "<C-c>", Command::Quit),
+    (Mode::Normal, "<C-t>", Command::CycleTheme),
+    (Mode::Normal, "u", Command::Undo),
+    (Mode::Normal, "<C-r>", Command::Redo),
+    (Mode::Normal, "m", Command::ToggleMark),
+    (Mode::Normal, "<leader>b", Command::ToggleBreakpoint),
+    (Mode::Normal, "<C-s>", Command::Save),
+    (Mode::Normal, "<leader>w", Command::Save),
+    (Mode::Normal, "]c", Command::NextHunk),
+    (Mode::Normal, "[c", Command::PrevHunk),
+    (Mode::Normal, "<leader>hp", Command::PreviewHunk),
+    (Mode::Normal, "<leader>hr", Command::RevertHunk),
+    (Mode::Normal, "<leader>hs", Command::StageHunk),
+    (Mode::Normal, "<leader>gb", Command::ToggleBlame),
+    (Mode::Normal, "K", Command::Hover),
+    (Mode::Normal, "<leader>lr", Command::Rename),
+    (Mode::Normal, "<leader>lf", Command::Format),
+    (Mode::Normal, "]d", Command::NextDiagnostic),
+    (Mode::Normal, "[d", Command::PrevDiagnostic),
+    (Mode::Normal, "<leader>d", Command::ToggleDiagnosticList),
+    (Mode::Normal, "<leader>f", Command::FilePicker),
+    (Mode::Normal, "<leader>/", Command::Grep),
+    (Mode::Normal, "]q", Command::NextMatch),
+    (Mode::Normal, "[q", Command::PrevMatch),
+    (Mode::Normal, "<leader>r", Command::ToggleGrepList),
+    (Mode::Normal, ":", Command::CommandLine),
+    (Mode::Normal, "<leader>e", Command::ToggleExplorer),
+    (Mode::Normal, "<C-w>s", Command::SplitHorizontal),
+    (Mode::Normal, "<C-w>v", Command::SplitVertical),
+    (Mode::Normal, "<C-w>h", Command::FocusWindowLeft),
+    (Mode::Normal, "<C-w>j", Command::FocusWindowDown),
+    (Mode::Normal, "<C-w>k", Command::FocusWindowUp),
+    (Mode::Normal, "<C-w>l", Command::FocusWindowRight),
+    (Mode::Normal, "<C-w>q", Command::CloseWindow),
+    (Mode::Normal, "<C-w>+", Command::GrowWindowHeight),
+    (Mode::Normal, "<C-w>-", Command::ShrinkWindowHeight),
+    (Mode::Normal, "<C-w>>", Command::GrowWindowWidth),
+    (Mode::Normal, "<C-w><lt>", Command::ShrinkWindowWidth),
+    (Mode::Normal, "<leader>n", Command::NotificationHistory),
+    (Mode::Normal, "<leader>L", Command::CycleLogLevel),
+    (Mode::Normal, "[l", Command::ScrollLogsUp),
+    (Mode::Normal, "]l", Command::ScrollLogsDown),
+    (Mode::Normal, "<leader>k", Command::ToggleScreencast),
+    (Mode::Normal, "<leader>q", Command::Quit),
+    (Mode::Normal, "<leader>t", Command::CycleTheme),
+    (Mode::Insert, "<enter>", Command::InsertNewline),
+    (Mode::Insert, "<esc>", Command::NormalMode),
+    (Mode::Insert, "<C-s>", Command::Save),
+    (Mode::Insert, "<C-n>", Command::CompleteNext),
+    (Mode::Insert, "<C-p>", Command::CompletePrev),
+    (Mode::Insert, "<tab>", Command::NextTabStop),
+    (Mode::GoTo, "g", Command::MoveToFileStart),
+    (Mode::GoTo, "e", Command::MoveWordBackwardStart),
+    (Mode::GoTo, "E", Command::MoveWordBackwardEnd),
+    (Mode::GoTo, "d", Command::GoToDefinition),
+    (Mode::GoTo, "r", Command::GoToReferences),
+    (Mode::GoTo, "-", Command::Earlier),
+    (Mode::GoTo, "+", Command::Later),
+    (Mode::GoTo, "<esc>", Command::NormalMode),
+    (Mode::Delete, "d", Command::DeleteLines),
+    (Mode::Delete, "w", Command::DeleteWordForwardStart),
+    (Mode::Delete, "W", Command::DeleteLongWordForwardStart),
+    (Mode::Delete, "e", Command::DeleteWordForwardEnd),
+    (Mode::Delete, "E", Command::DeleteLongWordForwardEnd),
+    (Mode::Delete, "<esc>", Command::NormalMode),
+    (Mode::Visual, "<up>", Command::MoveUp),
+    (Mode::Visual, "<down>", Command::MoveDown),
+    (Mode::Visual, "<left>", Command::MoveLeft),
+    (Mode::Visual, "<right>", Command::MoveRight),
+    (Mode::Visual, "G", Command::MoveToFileEnd),
+    (Mode::Visual, "w", Command::MoveWordForwardStart),
+    (Mode::Visual, "e", Command::MoveWordForwardEnd),
+    (Mode::Visual, "b", Command::MoveWordBackwardStart),
+    (Mode::Visual, "<home>", Command::MoveLineStart),
+    (Mode::Visual, "<end>", Command::MoveLineEnd),
+    (Mode::Visual, "d", Command::DeleteSelection),
+    (Mode::Visual, "<esc>", Command::NormalMode),
+];
+
+/// Key bindings read from the `[keys]` table of the config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    /// Key that `<leader>` stands for in key sequences.
+    pub leader: Box<str>,
+    /// How long to wait for the next key of a sequence, in milliseconds.
+    pub timeout: u64,
+    /// Sequences mapped to command names. The name `none` removes a binding.
+    pub normal: HashMap<String, String>,
+    pub insert: HashMap<String, String>,
+    pub goto: HashMap<String, String>,
+    pub delete: HashMap<String, String>,
+    pub visual: HashMap<String, String>,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        Self {
+            leader: "<space>".into(),
+            timeout: 1000,
+            normal: HashMap::new(),
+            insert: HashMap::new(),
+            goto: HashMap::new(),
+            delete: HashMap::new(),
+            visual: HashMap::new(),
+        }
+    }
+}
+
+/// A key press, with shift folded into the char it produced.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut key = Self { code, modifiers };
+        if let KeyCode::Char(char) = key.code {
+            key.modifiers.remove(KeyModifiers::SHIFT);
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                key.code = KeyCode::Char(char.to_ascii_lowercase());
+            }
+        }
+
+        key
+    }
+
+    pub fn code(&self) -> KeyCode {
+        self.code
+    }
+
+    pub fn modifiers(&self) -> KeyModifiers {
+        self.modifiers
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(key_event: KeyEvent) -> Self {
+        Key::new(key_event.code, key_event.modifiers)
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.code {
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char('<') => "lt".to_string(),
+            KeyCode::Char(char) if self.modifiers.is_empty() => {
+                return write!(f, "{}", char);
+            }
+            KeyCode::Char(char) => char.to_string(),
+            KeyCode::F(numb) => format!("f{}", numb),
+            code => KEY_NAMES
+                .iter()
+                .find(|(_, named)| *named == code)
+                .map_or("?", |(name, _)| *name)
+                .to_string(),
+        };
+
+        write!(f, "<")?;
+        for (prefix, modifier) in MODIFIER_PREFIXES {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", prefix)?;
+            }
+        }
+        write!(f, "{}>", name)
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 15] = [
+    ("esc", KeyCode::Esc),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("bs", KeyCode::Backspace),
+    ("del", KeyCode::Delete),
+    ("ins", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+const MODIFIER_PREFIXES: [(&str, KeyModifiers); 3] = [
+    ("C-", KeyModifiers::CONTROL),
+    ("A-", KeyModifiers::ALT),
+    ("S-", KeyModifiers::SHIFT),
+];
+
+/// Parse a key sequence such as `gg`, `<C-c>` or `<leader>w`.
+pub fn parse_keys(sequence: &str, leader: &[Key]) -> Result<Vec<Key>> {
+    let invalid = || Error::InvalidKeys(sequence.into());
+    let mut keys = vec![];
+    let mut rest = sequence;
+
+    while let Some(char) = rest.chars().next() {
+        if char != '<' {
+            keys.push(Key::new(KeyCode::Char(char), KeyModifiers::NONE));
+            rest = &rest[char.len_utf8()..];
+            continue;
+        }
+
+        let end = rest.find('>').ok_or_else(invalid)?;
+        let mut name = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if name == "leader" {
+            keys.extend_from_slice(leader);
+            continue;
+        }
+
+        let mut modifiers = KeyModifiers::NONE;
+        'prefixes: loop {
+            for (prefix, modifier) in MODIFIER_PREFIXES {
+                if let Some(stripped) = name.strip_prefix(prefix) {
+                    modifiers |= modifier;
+                    name = stripped;
+                    continue 'prefixes;
+                }
+            }
+            break;
+        }
+
+        let lowercase = name.to_ascii_lowercase();
+        let code = match lowercase.as_str() {
+            "space" => KeyCode::Char(' '),
+            "lt" => KeyCode::Char('<'),
+            "cr" | "return" => KeyCode::Enter,
+            "backspace" => KeyCode::Backspace,
+            _ if name.chars().count() == 1 => {
+                KeyCode::Char(name.chars().next().unwrap())
+            }
+            _ => match lowercase.strip_prefix('f').map(str::parse::<u8>) {
+                Some(Ok(numb)) => KeyCode::F(numb),
+                _ => KEY_NAMES
+                    .iter()
+                    .find(|(key_name, _)| *key_name == lowercase)
+                    .map(|(_, code)| *code)
+                    .ok_or_else(invalid)?,
+            },
+        };
+        keys.push(Key::new(code, modifiers));
+    }
+
+    if keys.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(keys)
+}
+
+/// A prefix tree from key sequences to commands.
+#[derive(Debug, Clone)]
+pub enum KeyTrie {
+    Command(Command),
+    Node(HashMap<Key, KeyTrie>),
+}
+
+impl Default for KeyTrie {
+    fn default() -> Self {
+        KeyTrie::Node(HashMap::new())
+    }
+}
+
+impl KeyTrie {
+    /// Bind a sequence, replacing any binding that is a prefix of it or that
+    /// it is a prefix of.
+    fn insert(&mut self, keys: &[Key], command: Command) {
+        let Some((first, rest)) = keys.split_first() else {
+            *self = KeyTrie::Command(command);
+            return;
+        };
+
+        if let KeyTrie::Command(_) = self {
+            *self = KeyTrie::default();
+        }
+        if let KeyTrie::Node(children) = self {
+            children.entry(*first).or_default().insert(rest, command);
+        }
+    }
+
+    fn remove(&mut self, keys: &[Key]) {
+        let KeyTrie::Node(children) = self else {
+            return;
+        };
+        match keys {
+            [] => {}
+            [last] => {
+                children.remove(last);
+            }
+            [first, rest @ ..] => {
+                if let Some(child) = children.get_mut(first) {
+                    child.remove(rest);
+                }
+            }
+        }
+    }
+
+    pub fn search(&self, keys: &[Key]) -> Option<&KeyTrie> {
+        keys.iter().try_fold(self, |trie, key| match trie {
+            KeyTrie::Node(children) => children.get(key),
+            KeyTrie::Command(_) => None,
+        })
+    }
+
+    /// The keys that can follow this node and what each leads to, sorted for
+    /// display.
+    pub fn continuations(&self) -> Vec<(Key, Continuation)> {
+        let KeyTrie::Node(children) = self else {
+            return vec![];
+        };
+        let mut res: Vec<(Key, Continuation)> = children
+            .iter()
+            .map(|(key, trie)| {
+                let next = match trie {
+                    KeyTrie::Command(command) => {
+                        Continuation::Command(*command)
+                    }
+                    KeyTrie::Node(children) => {
+                        Continuation::Prefix(children.len())
+                    }
+                };
+                (*key, next)
+            })
+            .collect();
+        res.sort_by_key(|(key, _)| key.to_string());
+
+        res
+    }
+}
+
+/// What a key leads to from a pending prefix.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Continuation {
+    Command(Command),
+    /// Another prefix, with this many continuations.
+    Prefix(usize),
+}
+
+/// Result of looking up a key sequence.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Lookup {
+    Command(Command),
+    /// The keys are the start of a longer sequence.
+    Pending,
+    Unmapped,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    modes: HashMap<Mode, KeyTrie>,
+    timeout: Duration,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&KeysConfig::default()).0
+    }
+}
+
+impl Keymap {
+    /// Build the default keymap with the config's bindings layered on top.
+    /// Invalid bindings are skipped and returned as errors.
+    pub fn from_config(config: &KeysConfig) -> (Self, Vec<Error>) {
+        let mut errors = vec![];
+        let leader = match parse_keys(&config.leader, &[]) {
+            Ok(leader) => leader,
+            Err(err) => {
+                errors.push(err);
+                parse_keys(&KeysConfig::default().leader, &[])
+                    .expect("default leader should parse")
+            }
+        };
+
+        let mut keymap = Self {
+            modes: HashMap::new(),
+            timeout: Duration::from_millis(config.timeout),
+        };
+        for (mode, sequence, command) in DEFAULT_BINDINGS {
+            let keys = parse_keys(sequence, &leader)
+                .expect("default bindings should parse");
+            keymap.modes.entry(mode).or_default().insert(&keys, command);
+        }
+
+        let overrides = [
+            (Mode::Normal, &config.normal),
+            (Mode::Insert, &config.insert),
+            (Mode::GoTo, &config.goto),
+            (Mode::Delete, &config.delete),
+            (Mode::Visual, &config.visual),
+        ];
+        for (mode, bindings) in overrides {
+            let trie = keymap.modes.entry(mode).or_default();
+            for (sequence, name) in bindings {
+                let keys = match parse_keys(sequence, &leader) {
+                    Ok(keys) => keys,
+                    Err(err) => {
+                        errors.push(err);
+                        continue;
+                    }
+                };
+                if name == "none" {
+                    trie.remove(&keys);
+                    continue;
+                }
+                match name.parse() {
+                    Ok(command) => trie.insert(&keys, command),
+                    Err(err) => errors.push(err),
+                }
+            }
+        }
+
+        (keymap, errors)
+    }
+
+    /// How long a pending sequence waits for its next key.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn trie(&self, mode: Mode) -> Option<&KeyTrie> {
+        self.modes.get(&mode)
+    }
+
+    pub fn lookup(&self, mode: Mode, keys: &[Key]) -> Lookup {
+        match self.trie(mode).and_then(|trie| trie.search(keys)) {
+            Some(KeyTrie::Command(command)) => Lookup::Command(*command),
+            Some(KeyTrie::Node(_)) => Lookup::Pending,
+            None => Lookup::Unmapped,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidKeys(Box<str>),
+    UnknownCommand(Box<str>),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Keymap error: ")?;
+
+        match self {
+            Self::InvalidKeys(keys) => {
+                write!(f, "invalid key sequence {}", keys)
+            }
+            Self::UnknownCommand(name) => {
+                write!(f, "no command named {}", name)
+            }
+        }
+    }
+}
+
+impl From<&Error> for Notification {
+    fn from(err: &Error) -> Self {
+        Notification::new(NotificationType::Error, err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -11,3 +11,41 @@ pub mod buffer;
 pub mod logger;
 
 pub mod action;
+
+pub mod theme;
+
+pub mod config;
+
+pub mod keymap;
+
+pub mod sign;
+
+pub mod git;
+
+pub mod diff;
+
+pub mod lsp;
+
+pub mod diagnostic;
+
+pub mod fuzzy;
+
+pub mod completion;
+
+pub mod picker;
+
+pub mod grep;
+
+pub mod explorer;
+
+pub mod window;
+
+pub mod event;
+
+pub mod watch;
+
+pub mod swap;
+
+pub mod state;
+
+pub mod walk;
@@ -1,59 +1,167 @@
+use crate::config;
 use chrono::{DateTime, Local};
+use serde::Deserialize;
 use std::{
+    collections::VecDeque,
     fmt::{Display, Formatter, Result},
-    fs::{self, File},
-    io::{self, Write},
+    fs::{self, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
 };
 
-#[derive(Debug, Default)]
+/// Most logs kept in memory, the oldest are dropped past it.
+const MAX_LOGS: usize = 1000;
+
+/// Size the log file grows to before it is rotated.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Rotated log files kept, as `<file>.1` (the newest) to `<file>.N`.
+const ROTATED_FILES: usize = 3;
+
+#[derive(Debug)]
 pub struct Logger {
-    logs: Vec<Log>,
+    /// The latest logs, oldest first.
+    logs: VecDeque<Log>,
+    /// Logs below this level are dropped.
+    min_level: Level,
+    /// File the logs are appended to, `None` to keep them in memory only.
+    path: Option<PathBuf>,
+    max_file_size: u64,
+    /// How many of the latest logs aren't in the file yet.
+    unwritten: usize,
 }
 
-impl Logger {
-    pub fn logs(&self) -> &Vec<Log> {
-        &self.logs
-    }
-
-    pub fn logs_mut(&mut self) -> &mut Vec<Log> {
-        &mut self.logs
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Logger {
+    /// A logger keeping its logs in memory only.
     pub fn new() -> Self {
-        Logger { logs: Vec::new() }
+        Logger {
+            logs: VecDeque::with_capacity(MAX_LOGS),
+            min_level: Level::default(),
+            path: None,
+            max_file_size: MAX_FILE_SIZE,
+            unwritten: 0,
+        }
+    }
+
+    /// A logger appending each log to the file at `path` as it's logged.
+    pub fn with_file(path: PathBuf) -> Self {
+        Logger {
+            path: Some(path),
+            ..Self::new()
+        }
+    }
+
+    /// Rotate the file once it grows past `size` bytes.
+    pub fn with_max_file_size(mut self, size: u64) -> Self {
+        self.max_file_size = size;
+
+        self
+    }
+
+    /// `$XDG_STATE_HOME/dans-editor/editor.log`.
+    pub fn default_file() -> Option<PathBuf> {
+        config::state_dir().map(|dir| dir.join("editor.log"))
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The latest logs, oldest first.
+    pub fn logs(&self) -> impl DoubleEndedIterator<Item = &Log> {
+        self.logs.iter()
+    }
+
+    pub fn min_level(&self) -> Level {
+        self.min_level
+    }
+
+    pub fn set_min_level(&mut self, level: Level) {
+        self.min_level = level
     }
 
     pub fn log(&mut self, level: Level, message: String) {
-        let now: DateTime<Local> = Local::now();
-        let log = Log {
+        if level < self.min_level {
+            return;
+        }
+        if self.logs.len() == MAX_LOGS {
+            self.logs.pop_front();
+        }
+        self.logs.push_back(Log {
             level,
             message,
-            timestamp: now,
-        };
-        self.logs.push(log);
-        if self.logs.len() > 1000 {
-            self.logs.remove(0); // Remove the oldest log
-        }
+            timestamp: Local::now(),
+        });
+        // Logs dropped before they were written are lost.
+        self.unwritten = (self.unwritten + 1).min(self.logs.len());
+        // Written through, so a crash loses none. Ones that fail to be are
+        // tried again with the next.
+        _ = self.flush();
     }
 
-    pub fn write_to_file(&mut self) -> io::Result<()> {
-        fs::create_dir_all(".local/share")?;
-        let mut file = File::create(".local/share/dans-editor.log")?;
+    /// Append the logs not yet written to the file, rotating it first when
+    /// it grew too big. Only ones that failed to be when logged are left.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if self.unwritten == 0 {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+        if size >= self.max_file_size {
+            rotate(path)?;
+        }
 
-        for log in self.logs.iter().rev() {
-            let log_line = format!(
-                "{:?} - {:?}: {}\n",
-                log.timestamp, log.level, log.message
-            );
-            file.write_all(log_line.as_bytes()).unwrap();
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut file = BufWriter::new(file);
+        let skip = self.logs.len() - self.unwritten;
+        for log in self.logs.iter().skip(skip) {
+            writeln!(file, "{}", log)?;
         }
-        // TODO: Limit the number of log entries to 1000
+        file.flush()?;
+        self.unwritten = 0;
+
         Ok(())
     }
 }
 
+/// Shift `<path>.1` to `<path>.2` and so on, dropping the oldest, and move
+/// the file to `<path>.1`.
+fn rotate(path: &Path) -> io::Result<()> {
+    let rotated = |idx: usize| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", idx));
+        PathBuf::from(name)
+    };
+    for idx in (1..ROTATED_FILES).rev() {
+        let from = rotated(idx);
+        if from.exists() {
+            fs::rename(from, rotated(idx + 1))?;
+        }
+    }
+
+    fs::rename(path, rotated(1))
+}
+
+/// What the logs panel shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LogView {
+    /// Logs below this level are hidden.
+    pub level: Level,
+    /// How many of the newest logs are scrolled past.
+    pub scroll: usize,
+}
+
 #[derive(Debug)]
 pub struct Log {
     level: Level,
@@ -61,19 +169,61 @@ pub struct Log {
     timestamp: DateTime<Local>,
 }
 
+impl Log {
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn timestamp(&self) -> DateTime<Local> {
+        self.timestamp
+    }
+}
+
 impl Display for Log {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        writeln!(
+        write!(
             f,
-            "{:?} - {:?}: {}\n",
-            self.timestamp, self.level, self.message
+            "{} {}: {}",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.level,
+            self.message
         )
     }
 }
 
-#[derive(Debug)]
+/// How severe a log is, least first.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
 pub enum Level {
+    #[default]
     Info,
     Warning,
     Error,
 }
+
+impl Level {
+    /// The next level up, wrapping around after the last.
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Info => Self::Warning,
+            Self::Warning => Self::Error,
+            Self::Error => Self::Info,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
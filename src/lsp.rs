@@ -0,0 +1,991 @@
+use crate::{
+    app::{Notification, NotificationType},
+    diagnostic::Severity,
+    event::Waker,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a server gets to answer `initialize`.
+const INIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a server told to exit gets to, before it's killed.
+const EXIT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// A language server, started for files with one of its extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    pub command: Box<str>,
+    #[serde(default)]
+    pub args: Vec<Box<str>>,
+    /// File extensions, without the dot.
+    pub extensions: Vec<Box<str>>,
+}
+
+/// Language id and server for the file, picked by its extension.
+pub fn server_for<'a>(
+    servers: &'a HashMap<Box<str>, ServerConfig>,
+    path: &str,
+) -> Option<(&'a str, &'a ServerConfig)> {
+    let extension = Path::new(path).extension()?.to_str()?;
+    servers
+        .iter()
+        .find(|(_, server)| {
+            server.extensions.iter().any(|ext| &**ext == extension)
+        })
+        .map(|(language_id, server)| (&**language_id, server))
+}
+
+/// A place in a document. Columns count UTF-16 code units, as the protocol
+/// does by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, character: usize) -> Self {
+        Self { line, character }
+    }
+
+    pub fn to_json(self) -> Value {
+        json!({ "line": self.line, "character": self.character })
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            line: value.get("line")?.as_u64()? as usize,
+            character: value.get("character")?.as_u64()? as usize,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Range {
+    pub fn to_json(self) -> Value {
+        json!({ "start": self.start.to_json(), "end": self.end.to_json() })
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            start: Position::from_json(value.get("start")?)?,
+            end: Position::from_json(value.get("end")?)?,
+        })
+    }
+}
+
+/// Text replacing a range, both for edits the buffer sends the server and
+/// for edits the server asks for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range,
+    pub text: String,
+}
+
+impl TextEdit {
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            range: Range::from_json(value.get("range")?)?,
+            text: value.get("newText")?.as_str()?.into(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub range: Range,
+}
+
+impl Location {
+    /// A `Location` or a `LocationLink`.
+    fn from_json(value: &Value) -> Option<Self> {
+        let (uri, range) = match value.get("targetUri") {
+            Some(uri) => (uri, value.get("targetSelectionRange")?),
+            None => (value.get("uri")?, value.get("range")?),
+        };
+
+        Some(Self {
+            path: uri_to_path(uri.as_str()?)?,
+            range: Range::from_json(range)?,
+        })
+    }
+
+    /// A location, a list of them or `null`.
+    fn list_from_json(value: &Value) -> Vec<Self> {
+        match value {
+            Value::Array(values) => {
+                values.iter().filter_map(Location::from_json).collect()
+            }
+            value => Location::from_json(value).into_iter().collect(),
+        }
+    }
+}
+
+/// A problem the server found, its range still in the server's terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: Severity,
+    pub message: String,
+    pub source: Option<Box<str>>,
+}
+
+impl Diagnostic {
+    fn from_json(value: &Value) -> Option<Self> {
+        // Servers leave the severity out to let the client pick, error it is.
+        let severity = match value.get("severity").and_then(Value::as_u64) {
+            Some(2) => Severity::Warning,
+            Some(3) => Severity::Info,
+            Some(4) => Severity::Hint,
+            _ => Severity::Error,
+        };
+
+        Some(Self {
+            range: Range::from_json(value.get("range")?)?,
+            severity,
+            message: value.get("message")?.as_str()?.into(),
+            source: value.get("source").and_then(Value::as_str).map(Into::into),
+        })
+    }
+}
+
+/// A completion the server suggested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub text: String,
+    pub snippet: bool,
+    /// What the text replaces, when the server says.
+    pub range: Option<Range>,
+    pub detail: Option<String>,
+}
+
+impl CompletionItem {
+    fn from_json(value: &Value) -> Option<Self> {
+        let label = value.get("label")?.as_str()?;
+        let edit = value.get("textEdit");
+        // An `InsertReplaceEdit` has two ranges, inserting leaves the rest
+        // of the word alone.
+        let range = edit
+            .and_then(|edit| edit.get("range").or_else(|| edit.get("insert")))
+            .and_then(Range::from_json);
+        let text = edit
+            .and_then(|edit| edit.get("newText"))
+            .or_else(|| value.get("insertText"))
+            .and_then(Value::as_str)
+            .unwrap_or(label);
+        let snippet =
+            value.get("insertTextFormat").and_then(Value::as_u64) == Some(2);
+
+        Some(Self {
+            label: label.into(),
+            text: text.into(),
+            snippet,
+            range,
+            detail: value.get("detail").and_then(Value::as_str).map(Into::into),
+        })
+    }
+
+    /// Items of a list of them or of a `CompletionList`.
+    fn list_from_json(value: &Value) -> Vec<Self> {
+        value
+            .as_array()
+            .or_else(|| value.get("items").and_then(Value::as_array))
+            .into_iter()
+            .flatten()
+            .filter_map(CompletionItem::from_json)
+            .collect()
+    }
+}
+
+/// Edits to make across files, such as for a rename.
+pub type WorkspaceEdit = Vec<(PathBuf, Vec<TextEdit>)>;
+
+fn workspace_edit_from_json(value: &Value) -> WorkspaceEdit {
+    let edits = |edits: &Value| -> Vec<TextEdit> {
+        edits
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(TextEdit::from_json)
+            .collect()
+    };
+
+    if let Some(changes) = value.get("documentChanges") {
+        // Creating, renaming and deleting files isn't supported, only edits.
+        return changes
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|change| {
+                let uri = change.pointer("/textDocument/uri")?;
+                Some((uri_to_path(uri.as_str()?)?, edits(change.get("edits")?)))
+            })
+            .collect();
+    }
+
+    value
+        .get("changes")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(uri, file_edits)| {
+            Some((uri_to_path(uri)?, edits(file_edits)))
+        })
+        .collect()
+}
+
+/// Text of hover contents, which may be markup, a marked string or a list of
+/// marked strings.
+fn hover_text(contents: &Value) -> String {
+    match contents {
+        Value::String(text) => text.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(hover_text)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        value => value
+            .get("value")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .into(),
+    }
+}
+
+/// Make edits to a file on disk, such as the other files touched by a rename.
+pub fn edit_file(path: &Path, mut edits: Vec<TextEdit>) -> Result<()> {
+    let mut rope = ropey::Rope::from_str(&fs::read_to_string(path)?);
+    let char_idx = |rope: &ropey::Rope, position: Position| {
+        if position.line >= rope.len_lines() {
+            return rope.len_chars();
+        }
+        let line_start = rope.line_to_char(position.line);
+        let line = rope.line(position.line);
+        let character = position.character.min(line.len_utf16_cu());
+
+        line_start + line.utf16_cu_to_char(character)
+    };
+
+    // Last first, so the positions of the rest still hold.
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+    for edit in edits {
+        let start = char_idx(&rope, edit.range.start);
+        let end = char_idx(&rope, edit.range.end).max(start);
+        rope.remove(start..end);
+        rope.insert(start, &edit.text);
+    }
+    fs::write(path, rope.to_string())?;
+
+    Ok(())
+}
+
+/// `file://` URI of an absolute path.
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'/'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~' => uri.push(byte as char),
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        match encoded[i] {
+            b'%' => {
+                let hex =
+                    std::str::from_utf8(encoded.get(i + 1..i + 3)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            byte => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    Some(PathBuf::from(String::from_utf8(bytes).ok()?))
+}
+
+/// Read one message framed by a `Content-Length` header. `None` when the
+/// stream has ended.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok();
+            }
+        }
+    }
+
+    let content_length = content_length
+        .ok_or_else(|| Error::Protocol("missing Content-Length".into()))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+pub fn write_message<W: Write>(
+    writer: &mut W,
+    message: &Value,
+) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+
+    writer.flush()
+}
+
+/// A message from the server, other than requests which the client answers
+/// itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Response {
+        id: u64,
+        result: std::result::Result<Value, Error>,
+    },
+    Notification {
+        method: String,
+        params: Value,
+    },
+}
+
+/// A language server process, talked to over its stdin and stdout. Messages
+/// are read on another thread so waiting on the server never blocks.
+#[derive(Debug)]
+pub struct Client {
+    process: Child,
+    stdin: ChildStdin,
+    messages: Receiver<Result<Value>>,
+    next_id: u64,
+    /// What the server said it can do when it initialized.
+    capabilities: Value,
+    /// The id of the `initialize` request while it's unanswered, with when
+    /// it's given up on.
+    initializing: Option<(u64, Instant)>,
+    /// What was sent before the server initialized, sent on once it has.
+    queued: Vec<Value>,
+    /// Whether the server was told to exit.
+    exiting: bool,
+}
+
+impl Client {
+    /// Start the server and begin the `initialize` handshake. It goes on as
+    /// messages are received, and what's sent meanwhile waits for it.
+    pub fn start(
+        server: &ServerConfig,
+        root: &Path,
+        waker: Waker,
+    ) -> Result<Self> {
+        let mut process = Command::new(&*server.command)
+            .args(server.args.iter().map(|arg| &**arg))
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| Error::Spawn {
+                command: server.command.clone(),
+                err: err.kind(),
+            })?;
+        let stdin = process.stdin.take().ok_or(Error::Exited)?;
+        let stdout = process.stdout.take().ok_or(Error::Exited)?;
+
+        let (sender, messages) = mpsc::channel();
+        let timer = waker.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                match read_message(&mut reader) {
+                    Ok(Some(message)) => {
+                        if sender.send(Ok(message)).is_err() {
+                            return;
+                        }
+                        waker.wake();
+                    }
+                    Ok(None) => break,
+                    Err(err) => {
+                        _ = sender.send(Err(err));
+                        break;
+                    }
+                }
+            }
+            // Let the exit be noticed.
+            drop(sender);
+            waker.wake();
+        });
+
+        let mut client = Self {
+            process,
+            stdin,
+            messages,
+            next_id: 0,
+            capabilities: Value::Null,
+            initializing: None,
+            queued: vec![],
+            exiting: false,
+        };
+        client.initialize(root)?;
+        // Let a server that never answers be noticed.
+        thread::spawn(move || {
+            thread::sleep(INIT_TIMEOUT);
+            timer.wake();
+        });
+
+        Ok(client)
+    }
+
+    fn initialize(&mut self, root: &Path) -> Result<()> {
+        let capabilities = json!({
+            "general": { "positionEncodings": ["utf-16"] },
+            "textDocument": {
+                "hover": { "contentFormat": ["plaintext", "markdown"] },
+                "definition": {},
+                "references": {},
+                "rename": {},
+                "formatting": {},
+                "publishDiagnostics": {},
+                "completion": { "completionItem": { "snippetSupport": true } },
+            },
+        });
+        let id = self.request(
+            "initialize",
+            json!({
+                "processId": std::process::id(),
+                "rootUri": path_to_uri(root),
+                "capabilities": capabilities,
+            }),
+        )?;
+        self.initializing = Some((id, Instant::now() + INIT_TIMEOUT));
+
+        Ok(())
+    }
+
+    /// Finish the handshake with the answer to `initialize`, sending on
+    /// what waited for it.
+    fn initialized(&mut self, result: Result<Value>) -> Result<()> {
+        self.initializing = None;
+        self.capabilities =
+            result?.get("capabilities").cloned().unwrap_or_default();
+        self.notify("initialized", json!({}))?;
+        for message in std::mem::take(&mut self.queued) {
+            self.send(message)?;
+        }
+
+        Ok(())
+    }
+
+    /// Empty until the server has initialized.
+    pub fn capabilities(&self) -> &Value {
+        &self.capabilities
+    }
+
+    pub fn is_initializing(&self) -> bool {
+        self.initializing.is_some()
+    }
+
+    /// Send a request, returning its id to match the response to.
+    pub fn request(&mut self, method: &str, params: Value) -> Result<u64> {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))?;
+
+        Ok(id)
+    }
+
+    pub fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(
+            json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        )
+    }
+
+    fn send(&mut self, message: Value) -> Result<()> {
+        if self.is_initializing() {
+            self.queued.push(message);
+            return Ok(());
+        }
+
+        write_message(&mut self.stdin, &message).map_err(|_| Error::Exited)
+    }
+
+    /// The next message from the server, if one has arrived.
+    pub fn try_recv(&mut self) -> Result<Option<Message>> {
+        loop {
+            let message = match self.messages.try_recv() {
+                Ok(message) => message?,
+                Err(TryRecvError::Empty) => {
+                    return match self.initializing {
+                        Some((_, deadline)) if Instant::now() >= deadline => {
+                            Err(Error::Timeout)
+                        }
+                        _ => Ok(None),
+                    }
+                }
+                Err(TryRecvError::Disconnected) => return Err(Error::Exited),
+            };
+            if let Some(message) = self.handle(message)? {
+                return Ok(Some(message));
+            }
+        }
+    }
+
+    /// Sort a message out. Requests from the server are answered with
+    /// `null`, none of them are needed for what the editor does.
+    fn handle(&mut self, message: Value) -> Result<Option<Message>> {
+        let method = message.get("method").and_then(Value::as_str);
+        match (message.get("id"), method) {
+            (Some(id), Some(_)) => {
+                self.send(
+                    json!({ "jsonrpc": "2.0", "id": id, "result": null }),
+                )?;
+                Ok(None)
+            }
+            (None, Some(method)) => Ok(Some(Message::Notification {
+                method: method.into(),
+                params: message.get("params").cloned().unwrap_or_default(),
+            })),
+            (Some(id), None) => {
+                let id = id.as_u64().ok_or_else(|| {
+                    Error::Protocol(format!("unknown id {}", id).into())
+                })?;
+                let result = match message.get("error") {
+                    Some(error) => Err(Error::Server {
+                        code: error
+                            .get("code")
+                            .and_then(Value::as_i64)
+                            .unwrap_or_default(),
+                        message: error
+                            .get("message")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .into(),
+                    }),
+                    None => {
+                        Ok(message.get("result").cloned().unwrap_or_default())
+                    }
+                };
+                if self.initializing.is_some_and(|(init, _)| init == id) {
+                    self.initialized(result)?;
+                    return Ok(None);
+                }
+                Ok(Some(Message::Response { id, result }))
+            }
+            (None, None) => {
+                Err(Error::Protocol(format!("bad message {}", message).into()))
+            }
+        }
+    }
+
+    /// Ask the server to shut down and exit, without waiting for it. One
+    /// still initializing is left to be killed.
+    pub fn shutdown(&mut self) {
+        if self.is_initializing() {
+            return;
+        }
+        _ = self.request("shutdown", Value::Null);
+        self.exiting = self.notify("exit", Value::Null).is_ok();
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let deadline = Instant::now() + EXIT_TIMEOUT;
+        while self.exiting && Instant::now() < deadline {
+            match self.process.try_wait() {
+                Ok(Some(_)) => return,
+                Ok(None) => thread::sleep(Duration::from_millis(5)),
+                Err(_) => break,
+            }
+        }
+        _ = self.process.kill();
+        _ = self.process.wait();
+    }
+}
+
+/// A request the editor is waiting on an answer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    Definition,
+    Hover,
+    References,
+    Rename,
+    Formatting,
+    Completion,
+}
+
+/// Answers and messages from the server, for the app to act on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Definition(Vec<Location>),
+    Hover(Option<String>),
+    References(Vec<Location>),
+    Rename(WorkspaceEdit),
+    Formatting(Vec<TextEdit>),
+    Completion(Vec<CompletionItem>),
+    /// `window/showMessage`.
+    Message(NotificationType, String),
+    /// Every diagnostic of the document, replacing the ones sent before.
+    Diagnostics(Vec<Diagnostic>),
+    Failed(Request, Error),
+}
+
+/// One document open in a language server.
+#[derive(Debug)]
+pub struct Session {
+    client: Client,
+    path: PathBuf,
+    uri: String,
+    version: i64,
+    /// Requests without an answer yet.
+    pending: HashMap<u64, Request>,
+}
+
+impl Session {
+    /// Start the server and open the document in it.
+    pub fn open(
+        server: &ServerConfig,
+        language_id: &str,
+        path: &Path,
+        root: &Path,
+        text: &str,
+        waker: Waker,
+    ) -> Result<Self> {
+        let path = fs::canonicalize(path)?;
+        let uri = path_to_uri(&path);
+        let mut client = Client::start(server, root, waker)?;
+        client.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": language_id,
+                    "version": 0,
+                    "text": text,
+                },
+            }),
+        )?;
+
+        Ok(Self {
+            client,
+            path,
+            uri,
+            version: 0,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Path of the open document, canonicalized.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Send the edits made to the buffer since the last call.
+    pub fn did_change(&mut self, changes: Vec<TextEdit>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        self.version += 1;
+        let changes = changes
+            .into_iter()
+            .map(|change| {
+                json!({ "range": change.range.to_json(), "text": change.text })
+            })
+            .collect::<Vec<_>>();
+        self.client.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": self.uri, "version": self.version },
+                "contentChanges": changes,
+            }),
+        )
+    }
+
+    pub fn definition(&mut self, position: Position) -> Result<()> {
+        let params = self.position_params(position);
+        self.request(Request::Definition, "textDocument/definition", params)
+    }
+
+    pub fn hover(&mut self, position: Position) -> Result<()> {
+        let params = self.position_params(position);
+        self.request(Request::Hover, "textDocument/hover", params)
+    }
+
+    pub fn references(&mut self, position: Position) -> Result<()> {
+        let mut params = self.position_params(position);
+        params["context"] = json!({ "includeDeclaration": true });
+        self.request(Request::References, "textDocument/references", params)
+    }
+
+    pub fn rename(&mut self, position: Position, new_name: &str) -> Result<()> {
+        let mut params = self.position_params(position);
+        params["newName"] = new_name.into();
+        self.request(Request::Rename, "textDocument/rename", params)
+    }
+
+    pub fn formatting(&mut self, tab_width: usize) -> Result<()> {
+        let params = json!({
+            "textDocument": self.document(),
+            "options": { "tabSize": tab_width, "insertSpaces": true },
+        });
+        self.request(Request::Formatting, "textDocument/formatting", params)
+    }
+
+    pub fn completion(&mut self, position: Position) -> Result<()> {
+        let params = self.position_params(position);
+        self.request(Request::Completion, "textDocument/completion", params)
+    }
+
+    /// Chars that start a completion as they are typed.
+    pub fn trigger_characters(&self) -> Vec<char> {
+        self.client
+            .capabilities()
+            .pointer("/completionProvider/triggerCharacters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter_map(|trigger| trigger.chars().next())
+            .collect()
+    }
+
+    /// Is a request, or the server initializing, waiting on an answer.
+    pub fn is_waiting(&self) -> bool {
+        !self.pending.is_empty() || self.client.is_initializing()
+    }
+
+    /// Everything the server has sent since the last call.
+    pub fn poll(&mut self) -> Result<Vec<Event>> {
+        let mut events = vec![];
+        while let Some(message) = self.client.try_recv()? {
+            match message {
+                Message::Response { id, result } => {
+                    let Some(request) = self.pending.remove(&id) else {
+                        continue;
+                    };
+                    events.push(match result {
+                        Ok(result) => event(request, &result),
+                        Err(err) => Event::Failed(request, err),
+                    });
+                }
+                Message::Notification { method, params } => {
+                    match method.as_str() {
+                        "window/showMessage" => {
+                            events.push(message_event(&params))
+                        }
+                        "textDocument/publishDiagnostics" => {
+                            let uri = params.get("uri").and_then(Value::as_str);
+                            if uri != Some(self.uri.as_str()) {
+                                continue;
+                            }
+                            let diagnostics = params
+                                .get("diagnostics")
+                                .and_then(Value::as_array)
+                                .into_iter()
+                                .flatten()
+                                .filter_map(Diagnostic::from_json)
+                                .collect();
+                            events.push(Event::Diagnostics(diagnostics));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Close the document and stop the server.
+    pub fn close(&mut self) {
+        _ = self.client.notify(
+            "textDocument/didClose",
+            json!({ "textDocument": self.document() }),
+        );
+        self.client.shutdown();
+    }
+
+    fn request(
+        &mut self,
+        request: Request,
+        method: &str,
+        params: Value,
+    ) -> Result<()> {
+        let id = self.client.request(method, params)?;
+        self.pending.insert(id, request);
+
+        Ok(())
+    }
+
+    fn document(&self) -> Value {
+        json!({ "uri": self.uri })
+    }
+
+    fn position_params(&self, position: Position) -> Value {
+        json!({
+            "textDocument": self.document(),
+            "position": position.to_json(),
+        })
+    }
+}
+
+/// The event for a `window/showMessage`.
+fn message_event(params: &Value) -> Event {
+    let kind = match params.get("type").and_then(Value::as_u64) {
+        Some(1) => NotificationType::Error,
+        Some(2) => NotificationType::Warning,
+        _ => NotificationType::Info,
+    };
+    let text = params
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    Event::Message(kind, text.into())
+}
+
+/// The event for the answer to a request.
+fn event(request: Request, result: &Value) -> Event {
+    match request {
+        Request::Definition => {
+            Event::Definition(Location::list_from_json(result))
+        }
+        Request::References => {
+            Event::References(Location::list_from_json(result))
+        }
+        Request::Hover => Event::Hover(
+            result
+                .get("contents")
+                .map(hover_text)
+                .filter(|text| !text.trim().is_empty()),
+        ),
+        Request::Rename => Event::Rename(workspace_edit_from_json(result)),
+        Request::Formatting => Event::Formatting(
+            result
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(TextEdit::from_json)
+                .collect(),
+        ),
+        Request::Completion => {
+            Event::Completion(CompletionItem::list_from_json(result))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Io(io::ErrorKind),
+    /// A message that isn't JSON, with what's wrong with it.
+    Json(Box<str>),
+    Spawn {
+        command: Box<str>,
+        err: io::ErrorKind,
+    },
+    /// The server sent something that doesn't follow the protocol.
+    Protocol(Box<str>),
+    /// The server answered a request with an error.
+    Server {
+        code: i64,
+        message: Box<str>,
+    },
+    /// The server didn't answer `initialize` in time.
+    Timeout,
+    Exited,
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LSP error: ")?;
+
+        match self {
+            Self::Io(kind) => write!(f, "{}", kind),
+            Self::Json(err) => write!(f, "{}", err),
+            Self::Spawn { command, err } => {
+                write!(f, "could not start {}: {}", command, err)
+            }
+            Self::Protocol(msg) => write!(f, "{}", msg),
+            Self::Server { code, message } => {
+                write!(f, "server error {}: {}", code, message)
+            }
+            Self::Timeout => write!(f, "server did not initialize in time"),
+            Self::Exited => write!(f, "server exited"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err.kind())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err.to_string().into())
+    }
+}
+
+impl From<&Error> for Notification {
+    fn from(err: &Error) -> Self {
+        Notification::new(NotificationType::Error, err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
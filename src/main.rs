@@ -1,21 +1,76 @@
 use editor::{
-    app::{self, RunningState},
+    action::history::HistoryStore,
+    app::{self, Notification, RunningState},
     buffer::Buffer,
-    handler, tui,
+    config::Config,
+    event::EventBus,
+    handler,
+    keymap::Keymap,
+    logger::Logger,
+    swap::SwapDir,
+    tui,
 };
+use std::time::Duration;
 
 fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     let path = std::env::args().nth(1).expect("file name not found");
     let buffer = Buffer::from_file(path).expect("could not find file");
     let mut app = app::App::new(buffer);
 
+    let (config, errors) = Config::load();
+    if let Err(err) = app.configure(config) {
+        app.push_notif(Notification::from(&err));
+    }
+    for err in errors.iter() {
+        app.push_notif(Notification::from(err));
+    }
+    let bus = EventBus::new();
+    app.set_waker(bus.waker());
+    if let Err(err) = app.watch_files() {
+        app.push_notif(Notification::from(&err));
+    }
+    app.set_swap_dir(SwapDir::default_dir());
+    app.set_history_store(HistoryStore::default_dir());
+    if let Err(err) = app.load_history() {
+        app.push_notif(Notification::from(&err));
+    }
+    if let Err(err) = app.check_swap() {
+        app.push_notif(Notification::from(&err));
+    }
+    if let Err(err) = app.refresh_git() {
+        app.push_notif(Notification::from(&err));
+    }
+    if let Err(err) = app.start_lsp() {
+        app.push_notif(Notification::from(&err));
+    }
+
     tui::install_panic_hook();
     let mut terminal = tui::init()?;
+    let size = terminal.size()?;
+    app.resize(size.width, size.height);
     let mut handler = handler::Handler::new();
+    if let Some(path) = Logger::default_file() {
+        handler.logger = Logger::with_file(path);
+    }
+    handler.logger.set_min_level(app.config().log_level);
+    handler.screencast = app.config().layout.screencast;
+    // Errors were reported when the config was validated.
+    let (keymap, _) = Keymap::from_config(&app.config().keys);
+    handler.set_keymap(keymap);
 
+    bus.read_terminal();
+    // Swap files are written on the first tick after they're due.
+    bus.tick(Duration::from_secs(1));
+    let mut redraw = true;
     while app.running_state() == RunningState::Running {
-        tui::draw(&mut terminal, &app, &handler.logger)?;
-        handler.listen(&mut app)?; // blocks
+        if redraw {
+            let status_line_height = 1;
+            app.scroll_to_cursor(
+                (terminal.size()?.height - status_line_height) as usize,
+            );
+            tui::draw(&mut terminal, &app, &handler)?;
+        }
+        redraw = handler.listen(&mut app, &bus)?; // blocks
     }
 
     tui::exit()?;
@@ -0,0 +1,171 @@
+use crate::{event::Waker, fuzzy, walk};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// Paths sent over from the walk at a time.
+const WALK_BATCH: usize = 256;
+
+/// Fuzzy finder over the files of a directory. The directory is walked on
+/// another thread, and the files are ranked as they come in.
+#[derive(Debug)]
+pub struct Picker {
+    root: PathBuf,
+    /// Paths relative to the root, in the order they were found.
+    files: Vec<Box<str>>,
+    query: String,
+    /// Score and index into `files` of the files matching the query, best
+    /// first.
+    matches: Vec<(i64, usize)>,
+    pub selected: usize,
+    /// `None` once the walk is done.
+    walk: Option<Receiver<Vec<Box<str>>>>,
+}
+
+impl Picker {
+    /// Start walking `root`, skipping hidden files and whatever the
+    /// `.gitignore` files in it ignore.
+    pub fn new(root: &Path, waker: Waker) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let walk_root = root.to_path_buf();
+        thread::spawn(move || {
+            let mut batch = Vec::with_capacity(WALK_BATCH);
+            for (_, path) in walk::files(&walk_root) {
+                batch.push(path);
+                if batch.len() == WALK_BATCH {
+                    if sender.send(std::mem::take(&mut batch)).is_err() {
+                        return;
+                    }
+                    waker.wake();
+                }
+            }
+            _ = sender.send(batch);
+            // The walk is done once the sender is dropped.
+            drop(sender);
+            waker.wake();
+        });
+
+        Self {
+            root: root.to_path_buf(),
+            files: vec![],
+            query: String::new(),
+            matches: vec![],
+            selected: 0,
+            walk: Some(receiver),
+        }
+    }
+
+    /// Is the directory still being walked.
+    pub fn walking(&self) -> bool {
+        self.walk.is_some()
+    }
+
+    /// Take the files found since the last call, and rank them.
+    pub fn update(&mut self) {
+        let Some(walk) = &self.walk else {
+            return;
+        };
+        let found = self.files.len();
+        loop {
+            match walk.try_recv() {
+                Ok(batch) => self.files.extend(batch),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.walk = None;
+                    break;
+                }
+            }
+        }
+        if self.files.len() > found {
+            self.rank_from(found);
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.matches.clear();
+        self.selected = 0;
+        self.rank_from(0);
+    }
+
+    /// How many files were found.
+    pub fn len_files(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Paths matching the query, best first.
+    pub fn matches(&self) -> impl Iterator<Item = &str> {
+        self.matches.iter().map(|(_, idx)| &*self.files[*idx])
+    }
+
+    pub fn len_matches(&self) -> usize {
+        self.matches.len()
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = (self.selected + 1) % self.matches.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected =
+                (self.selected + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+
+    /// The selected path, relative to the root.
+    pub fn selected(&self) -> Option<&str> {
+        let (_, idx) = self.matches.get(self.selected)?;
+
+        Some(&self.files[*idx])
+    }
+
+    /// The first `height` lines of the selected file.
+    pub fn preview(&self, height: usize) -> Vec<String> {
+        let Some(path) = self.selected() else {
+            return vec![];
+        };
+        let Ok(file) = File::open(self.root.join(path)) else {
+            return vec!["(can't be read)".into()];
+        };
+        let lines: Result<Vec<String>, _> =
+            BufReader::new(file).lines().take(height).collect();
+
+        lines.unwrap_or_else(|_| vec!["(binary file)".into()])
+    }
+
+    /// Score the files from `from` on and merge the matches into the
+    /// ranking. Shorter paths go first among equal scores.
+    fn rank_from(&mut self, from: usize) {
+        let selected = self.matches.get(self.selected).copied();
+        for (idx, file) in self.files.iter().enumerate().skip(from) {
+            if let Some(score) = fuzzy::score(&self.query, file) {
+                self.matches.push((score, idx));
+            }
+        }
+        let files = &self.files;
+        self.matches.sort_by_key(|(score, idx)| {
+            (std::cmp::Reverse(*score), files[*idx].len(), *idx)
+        });
+        // Files coming in don't move the selection off the file.
+        self.selected = selected
+            .and_then(|selected| {
+                self.matches.iter().position(|entry| *entry == selected)
+            })
+            .unwrap_or(0);
+    }
+}
@@ -0,0 +1,121 @@
+use std::{collections::HashSet, ops::Range};
+
+/// A marker shown in the sign column next to a line.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SignKind {
+    GitAdded,
+    GitChanged,
+    GitRemoved,
+    DiagnosticError,
+    DiagnosticWarning,
+    DiagnosticInfo,
+    DiagnosticHint,
+    Mark,
+    Breakpoint,
+}
+
+impl SignKind {
+    /// When several signs land on one line, the highest priority one is shown.
+    pub fn priority(&self) -> u8 {
+        match self {
+            Self::Breakpoint => 8,
+            Self::DiagnosticError => 7,
+            Self::DiagnosticWarning => 6,
+            Self::Mark => 5,
+            Self::DiagnosticInfo => 4,
+            Self::DiagnosticHint => 3,
+            Self::GitRemoved => 2,
+            Self::GitChanged => 1,
+            Self::GitAdded => 0,
+        }
+    }
+}
+
+/// Anything that places signs on lines of the buffer.
+pub trait SignProvider {
+    /// Sign for the line at `line_idx`, if this provider has one there.
+    fn sign(&self, line_idx: usize) -> Option<SignKind>;
+}
+
+/// Signs from every provider, resolved to one per line.
+#[derive(Default)]
+pub struct SignColumn<'a> {
+    providers: Vec<&'a dyn SignProvider>,
+}
+
+impl<'a> SignColumn<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, provider: &'a dyn SignProvider) -> Self {
+        self.providers.push(provider);
+
+        self
+    }
+
+    /// The highest priority sign on the line.
+    pub fn sign(&self, line_idx: usize) -> Option<SignKind> {
+        self.providers
+            .iter()
+            .filter_map(|provider| provider.sign(line_idx))
+            .max_by_key(|kind| kind.priority())
+    }
+}
+
+/// Signs the user toggles on lines, such as marks and breakpoints.
+#[derive(Debug, Default, Clone)]
+pub struct Markers {
+    lines: HashSet<(usize, SignKind)>,
+}
+
+impl Markers {
+    /// Add the sign to the line, or remove it if the line has it.
+    pub fn toggle(&mut self, line_idx: usize, kind: SignKind) {
+        if !self.lines.remove(&(line_idx, kind)) {
+            self.lines.insert((line_idx, kind));
+        }
+    }
+
+    pub fn contains(&self, line_idx: usize, kind: SignKind) -> bool {
+        self.lines.contains(&(line_idx, kind))
+    }
+
+    /// Move the signs from `line_idx` on down by `count`, as that many lines
+    /// were inserted before it.
+    pub fn insert_lines(&mut self, line_idx: usize, count: usize) {
+        self.map_lines(|idx| match idx >= line_idx {
+            true => Some(idx + count),
+            false => Some(idx),
+        })
+    }
+
+    /// Drop the signs on the removed `lines`, moving the ones below up.
+    pub fn remove_lines(&mut self, lines: Range<usize>) {
+        self.map_lines(|idx| match idx {
+            idx if idx >= lines.end => Some(idx - lines.len()),
+            idx if idx >= lines.start => None,
+            idx => Some(idx),
+        })
+    }
+
+    /// Move each sign to the line `f` gives for its line, dropping it on
+    /// `None`.
+    pub fn map_lines(&mut self, f: impl Fn(usize) -> Option<usize>) {
+        self.lines = self
+            .lines
+            .drain()
+            .filter_map(|(idx, kind)| Some((f(idx)?, kind)))
+            .collect();
+    }
+}
+
+impl SignProvider for Markers {
+    fn sign(&self, line_idx: usize) -> Option<SignKind> {
+        self.lines
+            .iter()
+            .filter(|(idx, _)| *idx == line_idx)
+            .map(|(_, kind)| *kind)
+            .max_by_key(|kind| kind.priority())
+    }
+}
@@ -0,0 +1,37 @@
+use crate::watch;
+use std::{io, path::Path};
+
+/// Longest name `flat_name` gives, leaving room under the usual 255 byte
+/// limit on file names for the extensions put after it.
+const MAX_NAME_LEN: usize = 200;
+
+/// The file's `watch::watched_path` flattened into a file name, to keep
+/// state about the file under, such as its swap or undo history. Each '/'
+/// is percent-encoded, and each '%' first, so no two paths share a name. A
+/// name too long for a file keeps the end of the path, after a hash of all
+/// of it.
+pub fn flat_name(path: &Path) -> io::Result<String> {
+    let path = watch::watched_path(path)?;
+    let name = path
+        .to_string_lossy()
+        .replace('%', "%25")
+        .replace('/', "%2F");
+    if name.len() <= MAX_NAME_LEN {
+        return Ok(name);
+    }
+
+    let hash = format!("{:016x}-", stable_hash(&name));
+    let mut start = name.len() - (MAX_NAME_LEN - hash.len());
+    while !name.is_char_boundary(start) {
+        start += 1;
+    }
+    Ok(hash + &name[start..])
+}
+
+/// FNV-1a, which unlike the std hasher hashes the same from one build to the
+/// next.
+pub fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
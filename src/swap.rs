@@ -0,0 +1,75 @@
+use crate::{action::history::HistoryStore, config, state};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Where swap files go, holding the unsaved text of open files so it can be
+/// recovered after a crash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapDir {
+    dir: PathBuf,
+}
+
+impl SwapDir {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `swap` under the state directory.
+    pub fn default_dir() -> Option<Self> {
+        config::state_dir().map(|dir| Self::new(dir.join("swap")))
+    }
+
+    /// The swap file of `file`, named after its full path as flattened by
+    /// `state::flat_name`.
+    pub fn path(&self, file: &Path) -> io::Result<PathBuf> {
+        Ok(self.dir.join(state::flat_name(file)? + ".swp"))
+    }
+
+    /// Write `contents` as the swap of `file`. It's written to the side and
+    /// moved into place, so a crash halfway leaves the last swap whole.
+    pub fn write(&self, file: &Path, contents: &str) -> io::Result<()> {
+        let path = self.path(file)?;
+        let partial = path.with_extension("swp.partial");
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&partial, contents)?;
+
+        fs::rename(partial, path)
+    }
+
+    /// The undo journals kept beside the swaps, with the steps of edits
+    /// that lead up to the text of each.
+    pub fn journal(&self) -> HistoryStore {
+        HistoryStore::new(self.dir.clone())
+    }
+
+    /// Drop the swap of `file` and its journal, if it has them.
+    pub fn remove(&self, file: &Path) -> io::Result<()> {
+        self.journal().remove(file)?;
+        match fs::remove_file(self.path(file)?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// The text of the swap of `file`, when it was written after the file
+    /// last was, as it is after a crash.
+    pub fn find(&self, file: &Path) -> io::Result<Option<String>> {
+        let path = self.path(file)?;
+        let swapped = match fs::metadata(&path) {
+            Ok(metadata) => metadata.modified()?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(None)
+            }
+            Err(err) => return Err(err),
+        };
+        // A swap outlives a deleted file.
+        let saved = fs::metadata(file).and_then(|metadata| metadata.modified());
+        if saved.is_ok_and(|saved| saved > swapped) {
+            return Ok(None);
+        }
+
+        fs::read_to_string(path).map(Some)
+    }
+}
@@ -0,0 +1,406 @@
+use crate::{
+    app::{Mode, NotificationType},
+    config,
+    sign::SignKind,
+};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::{
+    collections::HashMap, fmt::Display, fs, io, path::Path, str::FromStr,
+};
+use toml::Table;
+
+/// Themes compiled into the binary, in the order they are cycled through.
+const BUILTIN: [(&str, &str); 3] = [
+    ("default", include_str!("../themes/default.toml")),
+    ("gruvbox", include_str!("../themes/gruvbox.toml")),
+    (
+        "solarized-light",
+        include_str!("../themes/solarized-light.toml"),
+    ),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Theme {
+    #[serde(skip)]
+    name: Box<str>,
+    mode: ModeStyles,
+    cursor: CursorStyles,
+    cursor_line: ThemeStyle,
+    selection: ThemeStyle,
+    whitespace: Whitespace,
+    line_number: ThemeStyle,
+    cursor_line_number: ThemeStyle,
+    notification: NotificationStyles,
+    sign: SignStyles,
+    git: GitStyles,
+    keystroke: KeystrokeStyles,
+    syntax: HashMap<Box<str>, ThemeStyle>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin("default").expect("default theme should be valid")
+    }
+}
+
+impl Theme {
+    /// Names of the themes compiled into the binary.
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    /// Load one of the themes compiled into the binary.
+    pub fn builtin(name: &str) -> Result<Self> {
+        let (name, src) = BUILTIN
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .ok_or_else(|| Error::UnknownTheme(name.into()))?;
+
+        Self::from_toml(name, src)
+    }
+
+    /// Load a theme by name, trying the built-in themes before the `themes`
+    /// dir in the config dir.
+    pub fn find(name: &str) -> Result<Self> {
+        if let Ok(theme) = Self::builtin(name) {
+            return Ok(theme);
+        }
+
+        let path = config::config_dir()
+            .map(|dir| dir.join("themes").join(format!("{}.toml", name)))
+            .filter(|path| path.is_file())
+            .ok_or_else(|| Error::UnknownTheme(name.into()))?;
+
+        Self::from_file(path)
+    }
+
+    /// Load a theme file from disk. The theme is named after the file stem.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let src = fs::read_to_string(path)?;
+
+        Self::from_toml(&name, &src)
+    }
+
+    /// Parse a theme. Any key the theme leaves out is taken from the default
+    /// theme.
+    pub fn from_toml(name: &str, src: &str) -> Result<Self> {
+        let mut table: Table = toml::from_str(BUILTIN[0].1)?;
+        merge(&mut table, toml::from_str(src)?);
+
+        let mut theme: Theme = table.try_into()?;
+        theme.name = name.into();
+
+        Ok(theme)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mode(&self, mode: Mode) -> Style {
+        let styles = &self.mode;
+        match mode {
+            Mode::Normal => styles.normal.0,
+            Mode::Insert => styles.insert.0,
+            Mode::GoTo => styles.goto.0,
+            Mode::Delete => styles.delete.0,
+            Mode::Visual => styles.visual.0,
+        }
+    }
+
+    /// Style of the cursor, depending on whether it sits on a visible char.
+    pub fn cursor(&self, in_bounds: bool) -> Style {
+        match in_bounds {
+            true => self.cursor.in_bounds.0,
+            false => self.cursor.out_of_bounds.0,
+        }
+    }
+
+    pub fn cursor_line(&self) -> Style {
+        self.cursor_line.0
+    }
+
+    /// Style of the text selected in visual mode.
+    pub fn selection(&self) -> Style {
+        self.selection.0
+    }
+
+    /// Glyph drawn in place of a space.
+    pub fn space_glyph(&self) -> char {
+        self.whitespace.space
+    }
+
+    pub fn whitespace(&self) -> Style {
+        self.whitespace.style.0
+    }
+
+    pub fn line_number(&self) -> Style {
+        self.line_number.0
+    }
+
+    pub fn cursor_line_number(&self) -> Style {
+        self.cursor_line_number.0
+    }
+
+    pub fn notification(&self, notification_type: NotificationType) -> Style {
+        let styles = &self.notification;
+        match notification_type {
+            NotificationType::Info => styles.info.0,
+            NotificationType::Warning => styles.warning.0,
+            NotificationType::Error => styles.error.0,
+            NotificationType::Success => styles.success.0,
+        }
+    }
+
+    /// Glyph and style of a sign in the sign column.
+    pub fn sign(&self, kind: SignKind) -> (char, Style) {
+        let styles = &self.sign;
+        let sign = match kind {
+            SignKind::GitAdded => &styles.git_added,
+            SignKind::GitChanged => &styles.git_changed,
+            SignKind::GitRemoved => &styles.git_removed,
+            SignKind::DiagnosticError => &styles.diagnostic_error,
+            SignKind::DiagnosticWarning => &styles.diagnostic_warning,
+            SignKind::DiagnosticInfo => &styles.diagnostic_info,
+            SignKind::DiagnosticHint => &styles.diagnostic_hint,
+            SignKind::Mark => &styles.mark,
+            SignKind::Breakpoint => &styles.breakpoint,
+        };
+
+        (sign.glyph, sign.style.0)
+    }
+
+    pub fn git(&self, element: GitElement) -> Style {
+        let styles = &self.git;
+        match element {
+            GitElement::Branch => styles.branch.0,
+            GitElement::Sync => styles.sync.0,
+            GitElement::Staged => styles.staged.0,
+            GitElement::Unstaged => styles.unstaged.0,
+            GitElement::Untracked => styles.untracked.0,
+            GitElement::Conflicted => styles.conflicted.0,
+            GitElement::Hash => styles.hash.0,
+            GitElement::Subject => styles.subject.0,
+        }
+    }
+
+    /// Style of the `idx`th keystroke shown, alternating so keys typed one
+    /// after another stay apart.
+    pub fn keystroke(&self, idx: usize) -> Style {
+        match idx % 2 {
+            0 => self.keystroke.even.0,
+            _ => self.keystroke.odd.0,
+        }
+    }
+
+    /// Style for a syntax scope such as `keyword.control`. Falls back to the
+    /// parent scope (`keyword`) when the exact scope isn't themed.
+    pub fn scope(&self, scope: &str) -> Style {
+        let mut scope = scope;
+        loop {
+            if let Some(style) = self.syntax.get(scope) {
+                return style.0;
+            }
+            match scope.rsplit_once('.') {
+                Some((parent, _)) => scope = parent,
+                None => return Style::default(),
+            }
+        }
+    }
+}
+
+/// Parts of the git summary that are styled separately.
+#[derive(Debug, Clone, Copy)]
+pub enum GitElement {
+    Branch,
+    /// Commits ahead of and behind the upstream.
+    Sync,
+    Staged,
+    Unstaged,
+    Untracked,
+    Conflicted,
+    Hash,
+    Subject,
+}
+
+/// Overwrite the keys of `base` with those of `overlay`, descending into
+/// tables so a theme can override a single field of a style.
+fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (
+                Some(toml::Value::Table(base_table)),
+                toml::Value::Table(table),
+            ) => merge(base_table, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ModeStyles {
+    normal: ThemeStyle,
+    insert: ThemeStyle,
+    goto: ThemeStyle,
+    delete: ThemeStyle,
+    visual: ThemeStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CursorStyles {
+    in_bounds: ThemeStyle,
+    out_of_bounds: ThemeStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Whitespace {
+    space: char,
+    style: ThemeStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NotificationStyles {
+    info: ThemeStyle,
+    warning: ThemeStyle,
+    error: ThemeStyle,
+    success: ThemeStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SignStyles {
+    git_added: SignStyle,
+    git_changed: SignStyle,
+    git_removed: SignStyle,
+    diagnostic_error: SignStyle,
+    diagnostic_warning: SignStyle,
+    diagnostic_info: SignStyle,
+    diagnostic_hint: SignStyle,
+    mark: SignStyle,
+    breakpoint: SignStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GitStyles {
+    branch: ThemeStyle,
+    sync: ThemeStyle,
+    staged: ThemeStyle,
+    unstaged: ThemeStyle,
+    untracked: ThemeStyle,
+    conflicted: ThemeStyle,
+    hash: ThemeStyle,
+    subject: ThemeStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KeystrokeStyles {
+    even: ThemeStyle,
+    odd: ThemeStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SignStyle {
+    glyph: char,
+    style: ThemeStyle,
+}
+
+/// A ratatui style as written in a theme file.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "StyleDef")]
+struct ThemeStyle(Style);
+
+#[derive(Deserialize)]
+struct StyleDef {
+    fg: Option<String>,
+    bg: Option<String>,
+    #[serde(default)]
+    modifiers: Vec<String>,
+}
+
+impl TryFrom<StyleDef> for ThemeStyle {
+    type Error = Error;
+
+    fn try_from(def: StyleDef) -> Result<Self> {
+        let mut style = Style::default();
+        if let Some(fg) = def.fg {
+            style = style.fg(parse_color(&fg)?);
+        }
+        if let Some(bg) = def.bg {
+            style = style.bg(parse_color(&bg)?);
+        }
+        for modifier in def.modifiers {
+            style = style.add_modifier(parse_modifier(&modifier)?);
+        }
+
+        Ok(ThemeStyle(style))
+    }
+}
+
+fn parse_color(color: &str) -> Result<Color> {
+    Color::from_str(color).map_err(|_| Error::InvalidColor(color.into()))
+}
+
+fn parse_modifier(modifier: &str) -> Result<Modifier> {
+    let modifier = match modifier {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underlined" => Modifier::UNDERLINED,
+        "slow_blink" => Modifier::SLOW_BLINK,
+        "rapid_blink" => Modifier::RAPID_BLINK,
+        "reversed" => Modifier::REVERSED,
+        "hidden" => Modifier::HIDDEN,
+        "crossed_out" => Modifier::CROSSED_OUT,
+        _ => return Err(Error::InvalidModifier(modifier.into())),
+    };
+
+    Ok(modifier)
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    UnknownTheme(Box<str>),
+    InvalidColor(Box<str>),
+    InvalidModifier(Box<str>),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Theme error: ")?;
+
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Parse(err) => write!(f, "{}", err.message()),
+            Self::UnknownTheme(name) => write!(f, "no theme named {}", name),
+            Self::InvalidColor(color) => write!(f, "invalid color {}", color),
+            Self::InvalidModifier(modifier) => {
+                write!(f, "invalid modifier {}", modifier)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Parse(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -1,5 +1,11 @@
-use crate::{app::App, logger::Logger, ui};
-use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crate::{app::App, handler::Handler, ui};
+use crossterm::{
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture,
+        EnableBracketedPaste, EnableFocusChange, EnableMouseCapture,
+    },
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
@@ -8,13 +14,25 @@ use std::{io, panic};
 
 pub fn init() -> io::Result<Terminal<impl Backend>> {
     terminal::enable_raw_mode()?;
-    crossterm::execute!(io::stdout(), EnterAlternateScreen)?;
+    crossterm::execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange,
+        EnableBracketedPaste
+    )?;
     let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
     Ok(terminal)
 }
 
 pub fn exit() -> io::Result<()> {
-    crossterm::execute!(io::stdout(), LeaveAlternateScreen)?;
+    crossterm::execute!(
+        io::stdout(),
+        DisableBracketedPaste,
+        DisableFocusChange,
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    )?;
     terminal::disable_raw_mode()?;
     Ok(())
 }
@@ -32,8 +50,8 @@ pub fn install_panic_hook() {
 pub fn draw(
     terminal: &mut Terminal<impl Backend>,
     app: &App,
-    logger: &Logger,
+    handler: &Handler,
 ) -> io::Result<()> {
-    terminal.draw(|frame| ui::render(app, logger, frame))?;
+    terminal.draw(|frame| ui::render(app, handler, frame))?;
     Ok(())
 }
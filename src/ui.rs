@@ -1,73 +1,528 @@
-use self::widgets::{
-    CursorLine, GitSummary, Logs, LowerTextArea, MessageBlock, ModeBlock,
-    UpperTextArea,
+use self::{
+    gutter::Gutter,
+    status_line::StatusLine,
+    widgets::{
+        char_idx_at_col, visual_col, BlameMessage, CompletionMenu, CursorLine,
+        DiagnosticList, FilePicker, FileTree, GitSummary, GrepList,
+        HunkPreview, InfoPopup, Keystrokes, Logs, LowerTextArea, MessageBlock,
+        NotificationHistory, PromptLine, StatusMessage, UpperTextArea,
+        WhichKey, WindowBar,
+    },
+};
+use crate::{
+    app::{App, Notification},
+    buffer::{Buffer, Cursor},
+    diagnostic::Diagnostics,
+    handler::Handler,
+    sign::SignColumn,
+    window::Window,
 };
-use crate::{app::App, logger::Logger};
 use ratatui::{
-    layout::{Constraint, Layout},
+    layout::{Constraint, Layout, Rect},
+    widgets::Clear,
     Frame,
 };
 
+mod gutter;
+mod status_line;
 mod widgets;
 
-pub fn render(app: &App, logger: &Logger, frame: &mut Frame) {
-    let outer_layout = Layout::default()
-        .direction(ratatui::layout::Direction::Vertical)
-        .constraints([Constraint::Fill(1), Constraint::Length(1)])
-        .split(frame.size());
-
-    let upper_window_size = (frame.size().height - 2) / 2;
-    let lower_window_size = frame.size().height - 2 - upper_window_size;
-
-    let window_layout = Layout::default()
-        .direction(ratatui::layout::Direction::Vertical)
-        .constraints(vec![
-            Constraint::Length(upper_window_size),
-            Constraint::Length(1),
-            Constraint::Length(lower_window_size),
-        ])
-        .split(outer_layout[0]);
-
-    let scroll_pos = app.buffer.cursor.y as u16;
+/// Widest the explorer gets.
+const EXPLORER_WIDTH: u16 = 30;
+
+pub fn render(app: &App, handler: &Handler, frame: &mut Frame) {
+    let [text_area, status_area] = outer_areas(frame.size());
+    let theme = app.theme();
+
+    // The explorer takes the left of everything above the status line.
+    let (tree_area, main_area) = explorer_areas(app, text_area);
+    if let (Some(explorer), Some(tree_area)) = (app.explorer(), tree_area) {
+        let status = app.git().map(|summary| &summary.status);
+        frame.render_widget(
+            FileTree::new(explorer, status, app.explorer_focused(), theme),
+            tree_area,
+        );
+    }
+
+    let windows = app.windows();
+    let mut window_layout = None;
+    for (id, area, bar_area) in window_areas(app, main_area) {
+        let focused = id == windows.focused();
+        if let Some(bar_area) = bar_area {
+            let file_name = match focused {
+                true => app.buffer.file_name(),
+                false => {
+                    windows.window(id).map_or("", |window| &*window.file_name)
+                }
+            };
+            let mode = focused.then_some(app.mode());
+            frame.render_widget(
+                WindowBar::new(file_name, mode, theme),
+                bar_area,
+            );
+        }
+        if focused {
+            let view = WindowView {
+                buffer: &app.buffer,
+                cursor: app.buffer.cursor,
+                scroll: app.scroll(),
+                focused: true,
+            };
+            window_layout =
+                Some(render_window(app, handler, view, frame, area));
+            continue;
+        }
+        let Some(window) = windows.window(id) else {
+            continue;
+        };
+        let Some(buffer) = app.window_buffer(window) else {
+            continue;
+        };
+        let view = WindowView::of(buffer, window);
+        render_window(app, handler, view, frame, area);
+    }
+    let window_layout =
+        window_layout.expect("the focused window should be laid out");
+
+    let config = app.config();
+    let diagnostic = app.diagnostic_under_cursor().map(Notification::from);
+    let commit = app
+        .blame()
+        .and_then(|blame| blame.commit(app.buffer.cursor.y));
+    let message = match (app.prompt(), &diagnostic, commit) {
+        (Some(prompt), _, _) => {
+            StatusMessage::Prompt(PromptLine::new(prompt, theme))
+        }
+        (None, Some(diagnostic), _) => StatusMessage::Notification(
+            MessageBlock::new(Some(diagnostic), theme),
+        ),
+        (None, None, Some(commit)) => {
+            StatusMessage::Blame(BlameMessage::new(commit, theme))
+        }
+        (None, None, None) => StatusMessage::Notification(MessageBlock::new(
+            app.current_notif(),
+            theme,
+        )),
+    };
+    let message_area = StatusLine::new(app, handler).render(
+        status_area,
+        message.width(),
+        frame.buffer_mut(),
+    );
+    frame.render_widget(message, message_area);
+
+    let hunk = app
+        .buffer
+        .diff()
+        .and_then(|diff| Some((diff, diff.hunk_at(app.buffer.cursor.y)?)));
+    if let (true, Some((diff, hunk))) = (app.hunk_preview(), hunk) {
+        let preview =
+            HunkPreview::new(&diff.base()[hunk.before.clone()], theme);
+        let area = window_layout.lower;
+        let height = preview.height().min(area.height);
+        frame.render_widget(preview, Rect { height, ..area });
+    }
+
+    if let Some(popup) = app.popup() {
+        let popup = InfoPopup::new(popup, theme);
+        let area = window_layout.lower;
+        let height = popup.height().min(area.height);
+        frame.render_widget(popup, Rect { height, ..area });
+    }
+
+    if let Some(completion) = app.completion() {
+        let menu = CompletionMenu::new(completion, theme);
+        let cursor = app.buffer.cursor;
+        let start = completion.selected().map_or(cursor.x, |item| item.start);
+        let text = app
+            .buffer
+            .line(cursor.y)
+            .map(|line| line.to_string())
+            .unwrap_or_default();
+        let col = visual_col(&text, start, config.tab_width) as u16;
+        let signs = app.sign_column();
+        let gutter = Gutter::new(&app.buffer, cursor, config, theme, &signs)
+            .with_blame(app.blame());
+        let anchor = (
+            window_layout.cursor_line.x + gutter.width() + col,
+            window_layout.cursor_line.y,
+        );
+        let area = menu_area(main_area, anchor, menu.size());
+        frame.render_widget(menu, area);
+    }
+
+    if let Some(picker) = app.picker() {
+        let area = picker_area(text_area);
+        // Inside the border.
+        let preview = picker.preview(area.height.saturating_sub(2) as usize);
+        frame.render_widget(
+            FilePicker::new(picker, &preview, config.tab_width, theme),
+            area,
+        );
+    }
+
+    if let Some(history) = app.history() {
+        let matches = app.history_matches();
+        frame.render_widget(
+            NotificationHistory::new(history, &matches, theme),
+            picker_area(text_area),
+        );
+    }
+
+    // The screencast takes the bottom row, popups go above it.
+    let mut popup_bounds = text_area;
+    if handler.screencast {
+        let keystrokes = Keystrokes::new(handler.keystrokes(), theme);
+        let width = keystrokes.width(popup_bounds.width);
+        let area = popup_area(popup_bounds, (width, 1));
+        frame.render_widget(Clear, area);
+        frame.render_widget(keystrokes, area);
+        popup_bounds.height = popup_bounds.height.saturating_sub(1);
+    }
+
+    let pending = handler.pending();
+    let continuations = handler
+        .keymap()
+        .trie(app.mode())
+        .and_then(|trie| trie.search(pending))
+        .map(|trie| trie.continuations())
+        .unwrap_or_default();
+    if !pending.is_empty() && !continuations.is_empty() {
+        let which_key = WhichKey::new(pending, &continuations, theme);
+        let area = popup_area(popup_bounds, which_key.size());
+        frame.render_widget(which_key, area);
+    }
+}
+
+/// A buffer as one window shows it.
+struct WindowView<'a> {
+    buffer: &'a Buffer,
+    /// The window's cursor, as the buffer's is the focused window's.
+    cursor: Cursor,
+    scroll: usize,
+    /// Only the focused window has the git summary above its text and the
+    /// logs or a list below.
+    focused: bool,
+}
+
+impl<'a> WindowView<'a> {
+    /// An unfocused window onto `buffer`, its cursor and scroll kept in the
+    /// text should it have shrunk since the window was focused.
+    fn of(buffer: &'a Buffer, window: &Window) -> Self {
+        let cursor = buffer.clamp(window.cursor);
+        WindowView {
+            buffer,
+            cursor,
+            scroll: window.scroll.min(cursor.y),
+            focused: false,
+        }
+    }
+}
+
+/// Where the parts of a window go.
+struct WindowLayout {
+    git_summary: Rect,
+    upper_text: Rect,
+    cursor_line: Rect,
+    lower_text: Rect,
+    /// Below the last line, for the logs or a list.
+    below: Rect,
+    /// Everything below the cursor line.
+    lower: Rect,
+}
+
+/// Lay out the text of a window around its cursor line.
+fn window_layout(app: &App, view: &WindowView, area: Rect) -> WindowLayout {
+    let buffer = view.buffer;
+    let cursor = view.cursor;
+    let config = app.config();
+    let text_height = area.height.saturating_sub(1);
+    let upper_window_size = match config.layout.centered {
+        true => text_height / 2,
+        false => std::cmp::min(
+            text_height,
+            cursor.y.saturating_sub(view.scroll) as u16,
+        ),
+    };
+    let lower_window_size = text_height - upper_window_size;
+
+    let [upper, cursor_line, lower] = Layout::vertical([
+        Constraint::Length(upper_window_size),
+        Constraint::Length(1),
+        Constraint::Length(lower_window_size),
+    ])
+    .areas(area);
+
+    let scroll_pos = cursor.y as u16;
     let upper_text_area_size = std::cmp::min(upper_window_size, scroll_pos);
     let git_summary_size = upper_window_size - upper_text_area_size;
-    let upper_window_layout = Layout::default()
-        .direction(ratatui::layout::Direction::Vertical)
-        .constraints(vec![
-            Constraint::Length(git_summary_size),
-            Constraint::Length(upper_text_area_size),
-        ])
-        .split(window_layout[0]);
-
-    let len_lines = app.buffer.len_lines() as u16;
-    let lower_text_area_size =
+    let [git_summary, upper_text] = Layout::vertical([
+        Constraint::Length(git_summary_size),
+        Constraint::Length(upper_text_area_size),
+    ])
+    .areas(upper);
+
+    let len_lines = buffer.len_lines() as u16;
+    let mut lower_text_area_size =
         std::cmp::min(lower_window_size, len_lines - 1 - scroll_pos);
+    // A list gets at least half the lower window.
+    let list =
+        app.diagnostic_list() || (app.grep_list() && app.grep().is_some());
+    if view.focused && list {
+        lower_text_area_size =
+            lower_text_area_size.min(lower_window_size - lower_window_size / 2);
+    }
     let logs_size = lower_window_size - lower_text_area_size;
+    let [lower_text, below] = Layout::vertical([
+        Constraint::Length(lower_text_area_size),
+        Constraint::Length(logs_size),
+    ])
+    .areas(lower);
 
-    let lower_window_layout = Layout::default()
-        .direction(ratatui::layout::Direction::Vertical)
-        .constraints(vec![
-            Constraint::Length(lower_text_area_size),
-            Constraint::Length(logs_size),
-        ])
-        .split(window_layout[2]);
+    WindowLayout {
+        git_summary,
+        upper_text,
+        cursor_line,
+        lower_text,
+        below,
+        lower,
+    }
+}
 
-    let status_line_layout = Layout::default()
-        .direction(ratatui::layout::Direction::Horizontal)
-        .constraints(vec![Constraint::Length(8), Constraint::Fill(1)])
-        .split(outer_layout[1]);
+/// Is `buffer` of the file being edited, rather than another window's.
+fn is_editing(app: &App, buffer: &Buffer) -> bool {
+    buffer.file_name() == app.buffer.file_name()
+}
 
-    frame.render_widget(ModeBlock::new(app.mode()), status_line_layout[0]);
+/// Signs of a window's buffer. Only the file being edited has signs
+/// besides its diff.
+fn window_signs<'a>(app: &'a App, buffer: &'a Buffer) -> SignColumn<'a> {
+    match (is_editing(app, buffer), buffer.diff()) {
+        (true, _) => app.sign_column(),
+        (false, Some(diff)) => SignColumn::new().with(diff),
+        (false, None) => SignColumn::new(),
+    }
+}
+
+/// Draw the text of a window around its cursor line.
+fn render_window(
+    app: &App,
+    handler: &Handler,
+    view: WindowView,
+    frame: &mut Frame,
+    area: Rect,
+) -> WindowLayout {
+    let layout = window_layout(app, &view, area);
+    let WindowView {
+        buffer,
+        cursor,
+        focused,
+        ..
+    } = view;
+    let config = app.config();
+    let theme = app.theme();
+    // Signs, diagnostics, blame and the selection are of the file being
+    // edited.
+    let editing = is_editing(app, buffer);
+    let no_diagnostics = Diagnostics::default();
+    let diagnostics = match editing {
+        true => app.diagnostics(),
+        false => &no_diagnostics,
+    };
+    let signs = window_signs(app, buffer);
+    let blame = app.blame().filter(|_| editing);
+    let gutter =
+        Gutter::new(buffer, cursor, config, theme, &signs).with_blame(blame);
+    let selection = app.selection().filter(|_| focused);
+
+    if focused && config.layout.git_summary {
+        frame.render_widget(
+            GitSummary::new(app.git(), theme),
+            layout.git_summary,
+        );
+    }
     frame.render_widget(
-        MessageBlock::new(app.notifs().last()),
-        status_line_layout[1],
+        UpperTextArea::new(buffer, cursor, config, theme, &gutter, diagnostics)
+            .with_selection(selection),
+        layout.upper_text,
     );
-    frame.render_widget(GitSummary::new(app), upper_window_layout[0]);
-    frame
-        .render_widget(UpperTextArea::new(&app.buffer), upper_window_layout[1]);
     #[rustfmt::skip]
-    frame.render_widget(CursorLine::new(&app.buffer, app.mode()), window_layout[1]);
-    frame
-        .render_widget(LowerTextArea::new(&app.buffer), lower_window_layout[0]);
-    frame.render_widget(Logs::new(&logger), lower_window_layout[1]);
+    frame.render_widget(CursorLine::new(buffer, cursor, app.mode(), config, theme, &gutter, diagnostics).with_selection(selection), layout.cursor_line);
+    frame.render_widget(
+        LowerTextArea::new(buffer, cursor, config, theme, &gutter, diagnostics)
+            .with_selection(selection),
+        layout.lower_text,
+    );
+    if !focused {
+        return layout;
+    }
+    let grep_list = app.grep().filter(|_| app.grep_list());
+    if app.diagnostic_list() {
+        frame.render_widget(
+            DiagnosticList::new(app.diagnostics(), theme),
+            layout.below,
+        );
+    } else if let Some(grep) = grep_list {
+        frame.render_widget(GrepList::new(grep, theme), layout.below);
+    } else if config.layout.logs {
+        frame.render_widget(
+            Logs::new(&handler.logger, handler.log_view, theme),
+            layout.below,
+        );
+    }
+
+    layout
+}
+
+/// What is under a cell of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    /// Id of the window the cell is in.
+    pub window: usize,
+    /// Position in the window's buffer, when the cell is on its text.
+    pub pos: Option<Cursor>,
+}
+
+/// Find the window and text under the cell at `column` and `row`, laid out
+/// as last drawn at the app's size.
+pub fn hit(app: &App, column: u16, row: u16) -> Option<Hit> {
+    let (width, height) = app.size();
+    let [text_area, _] = outer_areas(Rect::new(0, 0, width, height));
+    let (_, main_area) = explorer_areas(app, text_area);
+    let (window, area, _) = window_areas(app, main_area)
+        .into_iter()
+        .find(|(_, area, _)| contains(*area, column, row))?;
+
+    let view = match window == app.windows().focused() {
+        true => WindowView {
+            buffer: &app.buffer,
+            cursor: app.buffer.cursor,
+            scroll: app.scroll(),
+            focused: true,
+        },
+        false => {
+            let stored = app.windows().window(window)?;
+            WindowView::of(app.window_buffer(stored)?, stored)
+        }
+    };
+    let layout = window_layout(app, &view, area);
+    let on_text =
+        row >= layout.upper_text.top() && row < layout.lower_text.bottom();
+    if !on_text {
+        return Some(Hit { window, pos: None });
+    }
+
+    let buffer = view.buffer;
+    let line_idx = (view.cursor.y + row as usize)
+        .saturating_sub(layout.cursor_line.y as usize);
+    let signs = window_signs(app, buffer);
+    let blame = app.blame().filter(|_| is_editing(app, buffer));
+    let gutter =
+        Gutter::new(buffer, view.cursor, app.config(), app.theme(), &signs)
+            .with_blame(blame);
+    let col = column.saturating_sub(area.x + gutter.width());
+    let text = buffer
+        .line(line_idx)
+        .map(|line| line.to_string())
+        .unwrap_or_default();
+    let char_idx = char_idx_at_col(&text, col as usize, app.config().tab_width);
+
+    Some(Hit {
+        window,
+        pos: Some((char_idx, line_idx).into()),
+    })
+}
+
+/// The text area and the status line below it.
+fn outer_areas(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area)
+}
+
+/// The explorer, when it is open, and the windows right of it.
+fn explorer_areas(app: &App, area: Rect) -> (Option<Rect>, Rect) {
+    if app.explorer().is_none() {
+        return (None, area);
+    }
+    let [tree_area, main_area] = Layout::horizontal([
+        Constraint::Length(explorer_width(area)),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+
+    (Some(tree_area), main_area)
+}
+
+/// Id and area of each window, with the bar naming its file below it when
+/// there are several.
+fn window_areas(
+    app: &App,
+    main_area: Rect,
+) -> Vec<(usize, Rect, Option<Rect>)> {
+    let windows = app.windows();
+    let bars = windows.len() > 1;
+
+    windows
+        .areas(main_area)
+        .into_iter()
+        .map(|(id, area)| match bars {
+            true => {
+                let [area, bar_area] = Layout::vertical([
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                ])
+                .areas(area);
+                (id, area, Some(bar_area))
+            }
+            false => (id, area, None),
+        })
+        .collect()
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    (area.left()..area.right()).contains(&column)
+        && (area.top()..area.bottom()).contains(&row)
+}
+
+/// Area of the given size below the `anchor` cell, or above it when there
+/// isn't room below, kept inside `area`.
+fn menu_area(
+    area: Rect,
+    (x, y): (u16, u16),
+    (width, height): (u16, u16),
+) -> Rect {
+    let width = width.min(area.width);
+    let x = x.min(area.right() - width);
+    let y = match y + 1 + height <= area.bottom() {
+        true => y + 1,
+        false => y.saturating_sub(height).max(area.y),
+    };
+    let height = height.min(area.bottom() - y);
+
+    Rect::new(x, y, width, height)
+}
+
+/// Width of the explorer, a third of `area` at most.
+fn explorer_width(area: Rect) -> u16 {
+    (area.width / 3).min(EXPLORER_WIDTH)
+}
+
+/// Most of `area`, centered.
+fn picker_area(area: Rect) -> Rect {
+    let width = area.width - area.width / 10;
+    let height = area.height - area.height / 5;
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Area of the given size in the bottom right corner of `area`.
+fn popup_area(area: Rect, (width, height): (u16, u16)) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect::new(area.right() - width, area.bottom() - height, width, height)
 }
@@ -0,0 +1,216 @@
+use crate::{
+    buffer::{Buffer, Cursor},
+    config::{Config, LineNumbers},
+    git::{Blame, BlameCommit},
+    sign::SignColumn,
+    theme::{GitElement, Theme},
+};
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Left margin + right margin + border.
+const DECORATION_WIDTH: usize = 3;
+
+/// Sign glyph + space. Always reserved, so the text doesn't shift as lines
+/// with signs scroll in and out of view.
+const SIGN_WIDTH: u16 = 2;
+
+/// Width of the author name in the blame column.
+const AUTHOR_WIDTH: usize = 10;
+
+/// Short hash + author + age, each followed by a space.
+const BLAME_WIDTH: u16 = (7 + 1 + AUTHOR_WIDTH + 1 + 4 + 1) as u16;
+
+/// The columns left of the text holding the signs and line numbers.
+pub struct Gutter<'a> {
+    buffer: &'a Buffer,
+    /// The cursor of the window drawn, which numbers count from.
+    cursor: Cursor,
+    config: &'a Config,
+    theme: &'a Theme,
+    signs: &'a SignColumn<'a>,
+    blame: Option<&'a Blame>,
+}
+
+impl<'a> Gutter<'a> {
+    pub fn new(
+        buffer: &'a Buffer,
+        cursor: Cursor,
+        config: &'a Config,
+        theme: &'a Theme,
+        signs: &'a SignColumn<'a>,
+    ) -> Self {
+        Gutter {
+            buffer,
+            cursor,
+            config,
+            theme,
+            signs,
+            blame: None,
+        }
+    }
+
+    /// Annotate each line with the commit that last changed it.
+    pub fn with_blame(mut self, blame: Option<&'a Blame>) -> Self {
+        self.blame = blame;
+
+        self
+    }
+
+    pub fn width(&self) -> u16 {
+        self.blame_width() + self.sign_width() + self.line_number_width()
+    }
+
+    fn blame_width(&self) -> u16 {
+        match self.blame {
+            Some(_) => BLAME_WIDTH,
+            None => 0,
+        }
+    }
+
+    fn sign_width(&self) -> u16 {
+        match self.config.layout.sign_column {
+            true => SIGN_WIDTH,
+            false => 0,
+        }
+    }
+
+    /// Width of the line number column, or zero when line numbers are off.
+    fn line_number_width(&self) -> u16 {
+        let cursor_y = self.cursor.y;
+        let last_line = self.buffer.len_lines().saturating_sub(1);
+        let widest = match self.config.line_numbers {
+            LineNumbers::None => return 0,
+            LineNumbers::Absolute | LineNumbers::Hybrid => last_line + 1,
+            // Only as wide as the furthest line from the cursor needs.
+            LineNumbers::Relative => {
+                std::cmp::max(cursor_y, last_line.saturating_sub(cursor_y))
+            }
+        };
+
+        (digits(widest) + DECORATION_WIDTH) as u16
+    }
+
+    /// The gutter for the line, drawn over `base`.
+    pub fn line(&self, line_idx: usize, base: Style) -> Line<'static> {
+        let mut spans = vec![];
+        if let Some(blame) = self.blame {
+            spans.extend(self.blame(blame, line_idx, base));
+        }
+        if self.config.layout.sign_column {
+            spans.push(self.sign(line_idx, base));
+        }
+        if let Some(numb) = self.line_number(line_idx, base) {
+            spans.push(numb);
+        }
+
+        Line::from(spans)
+    }
+
+    /// Hash, author and age of the commit, or padding for lines added since
+    /// the blame was read.
+    fn blame(
+        &self,
+        blame: &Blame,
+        line_idx: usize,
+        base: Style,
+    ) -> Vec<Span<'static>> {
+        let hash_style = base.patch(self.theme.git(GitElement::Hash));
+        let style = base.patch(self.theme.git(GitElement::Subject));
+        let width = BLAME_WIDTH as usize;
+        match blame.commit(line_idx) {
+            Some(commit) if commit.is_committed() => vec![
+                Span::styled(format!("{} ", commit.short_hash()), hash_style),
+                Span::styled(
+                    format!(
+                        "{:<author$.author$} {:>4} ",
+                        commit.author,
+                        age(commit),
+                        author = AUTHOR_WIDTH,
+                    ),
+                    style,
+                ),
+            ],
+            Some(_) => {
+                vec![Span::styled(
+                    format!("{:<width$}", "not committed"),
+                    style,
+                )]
+            }
+            None => vec![Span::styled(" ".repeat(width), base)],
+        }
+    }
+
+    fn sign(&self, line_idx: usize, base: Style) -> Span<'static> {
+        match self.signs.sign(line_idx) {
+            Some(kind) => {
+                let (glyph, style) = self.theme.sign(kind);
+                Span::styled(format!("{} ", glyph), base.patch(style))
+            }
+            None => Span::styled(" ".repeat(SIGN_WIDTH as usize), base),
+        }
+    }
+
+    /// The line number, styled differently on the cursor line.
+    fn line_number(
+        &self,
+        line_idx: usize,
+        base: Style,
+    ) -> Option<Span<'static>> {
+        let cursor_y = self.cursor.y;
+        let label = line_number(self.config.line_numbers, line_idx, cursor_y)?
+            .to_string();
+        let style = match line_idx == cursor_y {
+            true => self.theme.cursor_line_number(),
+            false => self.theme.line_number(),
+        };
+
+        let padding = (self.line_number_width() as usize)
+            .saturating_sub(DECORATION_WIDTH + label.len());
+        let text = format!(" {}{}┆ ", label, " ".repeat(padding));
+
+        Some(Span::styled(text, base.patch(style)))
+    }
+}
+
+/// Number shown for the line at `line_idx`. Relative numbers count lines away
+/// from the cursor line.
+fn line_number(
+    line_numbers: LineNumbers,
+    line_idx: usize,
+    cursor_y: usize,
+) -> Option<usize> {
+    let relative = line_idx.abs_diff(cursor_y);
+    match line_numbers {
+        LineNumbers::Absolute => Some(line_idx + 1),
+        LineNumbers::Relative => Some(relative),
+        LineNumbers::Hybrid if relative == 0 => Some(line_idx + 1),
+        LineNumbers::Hybrid => Some(relative),
+        LineNumbers::None => None,
+    }
+}
+
+/// Time since the commit, in its largest whole unit.
+fn age(commit: &BlameCommit) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let secs = now.saturating_sub(commit.time);
+    let (minute, hour, day) = (60, 60 * 60, 24 * 60 * 60);
+    match secs {
+        s if s < minute => "now".into(),
+        s if s < hour => format!("{}m", s / minute),
+        s if s < day => format!("{}h", s / hour),
+        s if s < 7 * day => format!("{}d", s / day),
+        s if s < 30 * day => format!("{}w", s / (7 * day)),
+        s if s < 365 * day => format!("{}mo", s / (30 * day)),
+        s => format!("{}y", s / (365 * day)),
+    }
+}
+
+fn digits(numb: usize) -> usize {
+    numb.to_string().len()
+}
@@ -0,0 +1,230 @@
+use crate::{
+    app::App, config::Segment, diagnostic::Severity, handler::Handler, lsp,
+    theme::GitElement,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+};
+use std::path::Path;
+
+/// Columns kept for the message between the segments, even when there is
+/// none, so the segments don't jump around as messages come and go.
+const MIN_MESSAGE_WIDTH: usize = 20;
+
+/// Segments dropped first when the status line is too narrow, least useful
+/// first. The mode is always kept.
+const DROP_ORDER: [Segment; 10] = [
+    Segment::Encoding,
+    Segment::LineEnding,
+    Segment::Lines,
+    Segment::FileType,
+    Segment::Branch,
+    Segment::Percentage,
+    Segment::Diagnostics,
+    Segment::File,
+    Segment::Position,
+    Segment::Keys,
+];
+
+/// The segments the config lists either side of the message.
+pub struct StatusLine {
+    left: Vec<Part>,
+    right: Vec<Part>,
+}
+
+/// A segment's text, padded with a space each side.
+struct Part {
+    segment: Segment,
+    line: Line<'static>,
+    /// Chars after the leading space that can be cut when space is short,
+    /// such as the directories of a file name.
+    shrinkable: usize,
+}
+
+impl Part {
+    fn new(segment: Segment, line: Line<'static>) -> Self {
+        Self {
+            segment,
+            line,
+            shrinkable: 0,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.line.width()
+    }
+
+    /// Cut up to `by` columns from the shrinkable text, marking the cut with
+    /// '…'. Returns the columns saved.
+    fn shorten(&mut self, by: usize) -> usize {
+        // The '…' takes one of the columns cut.
+        let cut = (by + 1).min(self.shrinkable);
+        let Some(span) = self.line.spans.first_mut().filter(|_| cut > 1) else {
+            return 0;
+        };
+        let rest: String = span.content.chars().skip(1 + cut).collect();
+        span.content = format!(" …{}", rest).into();
+        self.shrinkable = 0;
+
+        cut - 1
+    }
+}
+
+impl StatusLine {
+    pub fn new(app: &App, handler: &Handler) -> Self {
+        let config = &app.config().status_line;
+        let parts = |segments: &[Segment]| {
+            segments
+                .iter()
+                .filter_map(|segment| part(*segment, app, handler))
+                .collect()
+        };
+
+        Self {
+            left: parts(&config.left),
+            right: parts(&config.right),
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.left.iter().chain(&self.right).map(Part::width).sum()
+    }
+
+    /// Fit the segments in `area` around a message `message_width` wide,
+    /// dropping segments in `DROP_ORDER` until the message has room. The
+    /// file name is shortened before it is dropped. Returns the area left
+    /// for the message.
+    pub fn render(
+        mut self,
+        area: Rect,
+        message_width: u16,
+        buf: &mut Buffer,
+    ) -> Rect {
+        let message_width = MIN_MESSAGE_WIDTH.max(message_width as usize);
+        let room = (area.width as usize).saturating_sub(message_width);
+        for segment in DROP_ORDER {
+            let Some(excess) = self.width().checked_sub(room) else {
+                break;
+            };
+            let parts = self.left.iter_mut().chain(self.right.iter_mut());
+            for part in parts.filter(|part| part.segment == segment) {
+                part.shorten(excess);
+            }
+            if self.width() > room {
+                self.left.retain(|part| part.segment != segment);
+                self.right.retain(|part| part.segment != segment);
+            }
+        }
+
+        let mut left = area.x;
+        for part in &self.left {
+            let width = part.width().min((area.right() - left) as usize);
+            _ = buf.set_line(left, area.y, &part.line, width as u16);
+            left += width as u16;
+        }
+        let mut right = area.right();
+        for part in self.right.iter().rev() {
+            right -= part.width() as u16;
+            _ = buf.set_line(right, area.y, &part.line, part.width() as u16);
+        }
+
+        Rect::new(left, area.y, right.saturating_sub(left), 1)
+    }
+}
+
+/// The text of `segment`, `None` when it has nothing to show.
+fn part(segment: Segment, app: &App, handler: &Handler) -> Option<Part> {
+    let buffer = &app.buffer;
+    let theme = app.theme();
+    let cursor = buffer.cursor;
+    let text = match segment {
+        Segment::Mode => {
+            let style = theme.mode(app.mode());
+            return Some(Part::new(
+                segment,
+                Line::styled(format!(" {} ", app.mode()), style),
+            ));
+        }
+        Segment::File => {
+            let path = buffer.file_name();
+            let name = Path::new(path).file_name()?.to_string_lossy();
+            let mut spans = vec![Span::raw(format!(" {}", path))];
+            if buffer.is_modified() {
+                spans.push(Span::raw(" [+]"));
+            }
+            spans.push(Span::raw(" "));
+            return Some(Part {
+                segment,
+                line: Line::from(spans),
+                shrinkable: path.chars().count() - name.chars().count(),
+            });
+        }
+        Segment::Position => format!("{}:{}", cursor.y + 1, cursor.x + 1),
+        Segment::Percentage => {
+            format!("{}%", (cursor.y + 1) * 100 / buffer.len_lines())
+        }
+        Segment::Lines => format!("{} lines", buffer.len_lines()),
+        Segment::LineEnding => buffer.line_ending().to_string(),
+        // The text is only ever read in as UTF-8.
+        Segment::Encoding => "utf-8".into(),
+        Segment::FileType => {
+            match lsp::server_for(&app.config().lsp, buffer.file_name()) {
+                Some((language_id, _)) => language_id.into(),
+                None => Path::new(buffer.file_name())
+                    .extension()?
+                    .to_string_lossy()
+                    .into(),
+            }
+        }
+        Segment::Branch => {
+            let branch = app.git()?.status.branch.as_deref()?;
+            return Some(Part::new(
+                segment,
+                Line::styled(
+                    format!(" {} ", branch),
+                    theme.git(GitElement::Branch),
+                ),
+            ));
+        }
+        Segment::Diagnostics => {
+            let diagnostics = app.diagnostics().sorted();
+            let mut spans: Vec<Span> = [
+                Severity::Error,
+                Severity::Warning,
+                Severity::Info,
+                Severity::Hint,
+            ]
+            .into_iter()
+            .filter_map(|severity| {
+                let count = diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity == severity)
+                    .count();
+                let (glyph, style) = theme.sign(severity.sign());
+                (count > 0).then(|| {
+                    Span::styled(format!(" {}{}", glyph, count), style)
+                })
+            })
+            .collect();
+            if spans.is_empty() {
+                return None;
+            }
+            spans.push(Span::raw(" "));
+            return Some(Part::new(segment, Line::from(spans)));
+        }
+        Segment::Keys => {
+            let mut keys = handler
+                .count()
+                .map_or(String::new(), |count| count.to_string());
+            keys.extend(handler.pending().iter().map(|key| key.to_string()));
+            if keys.is_empty() {
+                return None;
+            }
+            keys
+        }
+    };
+
+    Some(Part::new(segment, Line::raw(format!(" {} ", text))))
+}
@@ -1,204 +1,882 @@
+use super::gutter::Gutter;
 use crate::{
-    app::{App, Mode, Notification},
-    buffer::Buffer,
-    logger::Logger,
+    app::{
+        History, Mode, Notification, NotificationType, Popup, Prompt,
+        PromptAction, Selection,
+    },
+    buffer::{Buffer, Cursor},
+    completion::{Completion, Kind},
+    config::Config,
+    diagnostic::Diagnostics,
+    explorer::Explorer,
+    git::{self, FileStatus},
+    grep::Grep,
+    keymap::{Continuation, Key},
+    logger::{Level, LogView, Logger},
+    picker::Picker,
+    sign::SignKind,
+    theme::{GitElement, Theme},
 };
 use ratatui::prelude::*;
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
     text::Line,
-    widgets::Widget,
+    widgets::{Block, Borders, Clear, Widget},
 };
 use std::usize;
 
-#[allow(dead_code)]
+/// Branch and working tree state, with the recent log above it. Drawn bottom
+/// up, so the state sits next to the first line of the file.
 pub struct GitSummary<'a> {
-    app: &'a App,
+    summary: Option<&'a git::Summary>,
+    theme: &'a Theme,
 }
 
 impl<'a> GitSummary<'a> {
-    pub fn new(app: &'a App) -> Self {
-        GitSummary { app }
+    pub fn new(summary: Option<&'a git::Summary>, theme: &'a Theme) -> Self {
+        GitSummary { summary, theme }
+    }
+
+    fn status_line(&self, status: &git::Status) -> Line<'static> {
+        let branch = match &status.branch {
+            Some(branch) => branch.to_string(),
+            None => "(detached)".into(),
+        };
+        let mut spans = vec![Span::styled(
+            format!(" {}", branch),
+            self.theme.git(GitElement::Branch),
+        )];
+
+        if let Some(upstream) = &status.upstream {
+            spans.push(Span::raw(format!(" → {}", upstream)));
+            let sync_style = self.theme.git(GitElement::Sync);
+            if status.ahead > 0 {
+                spans.push(Span::styled(
+                    format!(" ↑{}", status.ahead),
+                    sync_style,
+                ));
+            }
+            if status.behind > 0 {
+                spans.push(Span::styled(
+                    format!(" ↓{}", status.behind),
+                    sync_style,
+                ));
+            }
+        }
+
+        let counts = [
+            ('+', status.staged, GitElement::Staged),
+            ('~', status.unstaged, GitElement::Unstaged),
+            ('?', status.untracked, GitElement::Untracked),
+            ('!', status.conflicted, GitElement::Conflicted),
+        ];
+        spans.push(Span::raw(" "));
+        for (symbol, count, element) in counts {
+            if count > 0 {
+                spans.push(Span::styled(
+                    format!(" {}{}", symbol, count),
+                    self.theme.git(element),
+                ));
+            }
+        }
+
+        Line::from(spans)
+    }
+
+    fn commit_line(&self, commit: &git::Commit) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(
+                format!(" {} ", commit.hash),
+                self.theme.git(GitElement::Hash),
+            ),
+            Span::styled(
+                commit.subject.to_string(),
+                self.theme.git(GitElement::Subject),
+            ),
+        ])
     }
 }
 
 impl<'a> Widget for GitSummary<'a> {
     fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
-        for i in 0..area.height {
-            let ratatui_line = Line::raw("Git info placeholder");
-            _ = buf.set_line(area.x, area.y + i, &ratatui_line, area.width)
+        let lines: Vec<Line> = match self.summary {
+            Some(summary) => std::iter::once(self.status_line(&summary.status))
+                .chain(
+                    summary.log.iter().map(|commit| self.commit_line(commit)),
+                )
+                .collect(),
+            None => vec![Line::styled(
+                " not a git repository",
+                self.theme.git(GitElement::Subject),
+            )],
+        };
+
+        for (i, line) in lines.iter().take(area.height as usize).enumerate() {
+            let y = area.bottom() - 1 - i as u16;
+            _ = buf.set_line(area.x, y, line, area.width)
+        }
+    }
+}
+
+/// What the status line shows between its segments.
+pub enum StatusMessage<'a> {
+    Prompt(PromptLine<'a>),
+    Notification(MessageBlock<'a>),
+    Blame(BlameMessage<'a>),
+}
+
+impl<'a> StatusMessage<'a> {
+    pub fn width(&self) -> u16 {
+        match self {
+            Self::Prompt(prompt) => prompt.width(),
+            Self::Notification(notification) => notification.width(),
+            Self::Blame(blame) => blame.width(),
+        }
+    }
+}
+
+impl<'a> Widget for StatusMessage<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        match self {
+            Self::Prompt(prompt) => prompt.render(area, buf),
+            Self::Notification(notification) => notification.render(area, buf),
+            Self::Blame(blame) => blame.render(area, buf),
         }
     }
 }
 
 pub struct MessageBlock<'a> {
     msg: Option<&'a Notification>,
+    theme: &'a Theme,
 }
 
 impl<'a> MessageBlock<'a> {
-    pub fn new(msg: Option<&'a Notification>) -> Self {
-        MessageBlock { msg }
+    pub fn new(msg: Option<&'a Notification>, theme: &'a Theme) -> Self {
+        MessageBlock { msg, theme }
+    }
+
+    fn line(&self) -> Line<'static> {
+        self.msg
+            .map(|msg| notification_line(msg, Style::default(), self.theme))
+            .unwrap_or_default()
+    }
+
+    pub fn width(&self) -> u16 {
+        self.line().width() as u16
     }
 }
 
 impl<'a> Widget for MessageBlock<'a> {
     fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
-        if let Some(msg) = self.msg {
-            let ratatui_line = Line::raw(msg.to_string());
-            _ = buf.set_line(area.x, area.y, &ratatui_line, area.width)
+        _ = buf.set_line(area.x, area.y, &self.line(), area.width)
+    }
+}
+
+/// The notification's icon in the color of its severity, then its text,
+/// with how many times it came in a row.
+fn notification_line(
+    msg: &Notification,
+    style: Style,
+    theme: &Theme,
+) -> Line<'static> {
+    let icon_style = style.patch(theme.notification(msg.message_type()));
+    let mut spans = vec![
+        Span::styled(msg.icon(), icon_style),
+        Span::styled(format!("  {}", msg.text()), style),
+    ];
+    if msg.count() > 1 {
+        spans.push(Span::styled(format!(" ×{}", msg.count()), icon_style));
+    }
+
+    Line::from(spans)
+}
+
+/// Every notification of the session matching the query, newest first.
+pub struct NotificationHistory<'a> {
+    history: &'a History,
+    matches: &'a [&'a Notification],
+    theme: &'a Theme,
+}
+
+impl<'a> NotificationHistory<'a> {
+    pub fn new(
+        history: &'a History,
+        matches: &'a [&'a Notification],
+        theme: &'a Theme,
+    ) -> Self {
+        NotificationHistory {
+            history,
+            matches,
+            theme,
+        }
+    }
+}
+
+impl<'a> Widget for NotificationHistory<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" notifications {} ", self.matches.len()))
+            .title_bottom(" <C-l> clears ")
+            .style(self.theme.cursor_line());
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        let query = Line::from(vec![
+            Span::raw("> "),
+            Span::raw(self.history.query.as_str()),
+            Span::styled(" ", self.theme.cursor(true)),
+        ]);
+        _ = buf.set_line(inner.x, inner.y, &query, inner.width);
+
+        // Scrolled to keep the selection in view.
+        let height = inner.height.saturating_sub(1) as usize;
+        let first = (self.history.selected + 1).saturating_sub(height);
+        let matches = self.matches.iter().skip(first).take(height);
+        for (i, msg) in matches.enumerate() {
+            let y = inner.y + 1 + i as u16;
+            let style = match first + i == self.history.selected {
+                true => {
+                    self.theme.cursor_line().add_modifier(Modifier::REVERSED)
+                }
+                false => self.theme.cursor_line(),
+            };
+            buf.set_style(Rect::new(inner.x, y, inner.width, 1), style);
+            let line = notification_line(msg, style, self.theme);
+            _ = buf.set_line(inner.x, y, &line, inner.width);
         }
     }
 }
 
+/// Summary of the commit that last changed the cursor line, shown in place of
+/// notifications while blaming.
+pub struct BlameMessage<'a> {
+    commit: &'a git::BlameCommit,
+    theme: &'a Theme,
+}
+
+impl<'a> BlameMessage<'a> {
+    pub fn new(commit: &'a git::BlameCommit, theme: &'a Theme) -> Self {
+        BlameMessage { commit, theme }
+    }
+
+    fn line(&self) -> Line<'static> {
+        let style = self.theme.git(GitElement::Subject);
+        match self.commit.is_committed() {
+            true => Line::from(vec![
+                Span::styled(
+                    format!(" {}", self.commit.short_hash()),
+                    self.theme.git(GitElement::Hash),
+                ),
+                Span::styled(
+                    format!(" {}: {}", self.commit.author, self.commit.summary),
+                    style,
+                ),
+            ]),
+            false => Line::styled(" not committed yet", style),
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.line().width() as u16
+    }
+}
+
+impl<'a> Widget for BlameMessage<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        _ = buf.set_line(area.x, area.y, &self.line(), area.width)
+    }
+}
+
 pub struct UpperTextArea<'a> {
     buffer: &'a Buffer,
+    cursor: Cursor,
+    config: &'a Config,
+    theme: &'a Theme,
+    gutter: &'a Gutter<'a>,
+    diagnostics: &'a Diagnostics,
+    selection: Option<Selection>,
 }
 
 impl<'a> UpperTextArea<'a> {
-    pub fn new(buffer: &'a Buffer) -> Self {
-        UpperTextArea { buffer }
+    pub fn new(
+        buffer: &'a Buffer,
+        cursor: Cursor,
+        config: &'a Config,
+        theme: &'a Theme,
+        gutter: &'a Gutter<'a>,
+        diagnostics: &'a Diagnostics,
+    ) -> Self {
+        UpperTextArea {
+            buffer,
+            cursor,
+            config,
+            theme,
+            gutter,
+            diagnostics,
+            selection: None,
+        }
+    }
+
+    /// Highlight the chars selected in visual mode.
+    pub fn with_selection(mut self, selection: Option<Selection>) -> Self {
+        self.selection = selection;
+
+        self
     }
 }
 
 impl<'a> Widget for UpperTextArea<'a> {
     fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
-        let scroll_pos = self.buffer.cursor.y;
+        let scroll_pos = self.cursor.y;
         // render line cols
+        let gutter = self.gutter;
         for i in 0..area.height {
             let rope_idx = scroll_pos - area.height as usize + i as usize;
-            let line_numb = self.buffer.numb_col(rope_idx);
-            let ratatui_line = Span::raw(line_numb.to_string());
-            _ = buf.set_span(area.x, area.y + i, &ratatui_line, area.width)
+            let ratatui_line = gutter.line(rope_idx, Style::default());
+            _ = buf.set_line(area.x, area.y + i, &ratatui_line, area.width)
         }
 
         for i in 0..area.height {
             let rope_idx = scroll_pos - area.height as usize + i as usize;
-            let line = self
-                .buffer
-                .line(rope_idx)
-                .unwrap()
-                .to_string()
-                .populate_fill_chars();
+            let text = self.buffer.line(rope_idx).unwrap().to_string();
+            let line = text.populate_fill_chars(
+                self.config,
+                self.theme,
+                Style::default(),
+            );
 
-            let ratatui_line = Span::raw(line);
-            _ = buf.set_span(
-                self.buffer.line_numb_col_width() as u16 + area.x,
+            _ = buf.set_line(
+                gutter.width() + area.x,
                 area.y + i,
-                &ratatui_line,
+                &line,
                 area.width,
-            )
+            );
+            let text_area = text_row(area, i, gutter.width());
+            render_selection(
+                self.selection,
+                rope_idx,
+                &text,
+                text_area,
+                self.config,
+                self.theme,
+                buf,
+            );
+            render_diagnostics(
+                self.diagnostics,
+                rope_idx,
+                &text,
+                text_area,
+                self.config,
+                self.theme,
+                buf,
+            );
         }
     }
 }
 
+/// The latest logs, newest first, from the level of the view up.
 pub struct Logs<'a> {
     logger: &'a Logger,
+    view: LogView,
+    theme: &'a Theme,
 }
 
 impl<'a> Logs<'a> {
-    pub fn new(logger: &'a Logger) -> Self {
-        Logs { logger }
+    pub fn new(logger: &'a Logger, view: LogView, theme: &'a Theme) -> Self {
+        Logs {
+            logger,
+            view,
+            theme,
+        }
     }
 }
 
 impl<'a> Widget for Logs<'a> {
     fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
-        for i in 0..area.height {
-            let ratatui_line =
-                match self.logger.logs().iter().rev().nth(i as usize) {
-                    Some(log) => Line::from(log.to_string()),
-                    None => Line::raw("PLACEHOLDER"),
+        let mut area = area;
+        // Say what is hidden, when anything is.
+        if self.view.level > Level::Info || self.view.scroll > 0 {
+            let mut header = format!(" logs from {} up", self.view.level);
+            if self.view.scroll > 0 {
+                header
+                    .push_str(&format!(", {} newer hidden", self.view.scroll));
+            }
+            let style = self.theme.cursor_line();
+            buf.set_style(Rect { height: 1, ..area }, style);
+            _ = buf.set_span(
+                area.x,
+                area.y,
+                &Span::styled(header, style),
+                area.width,
+            );
+            area.y += 1;
+            area.height -= 1;
+        }
+
+        let logs = self
+            .logger
+            .logs()
+            .rev()
+            .filter(|log| log.level() >= self.view.level)
+            .skip(self.view.scroll);
+        for (i, log) in logs.take(area.height as usize).enumerate() {
+            let notification_type = match log.level() {
+                Level::Info => NotificationType::Info,
+                Level::Warning => NotificationType::Warning,
+                Level::Error => NotificationType::Error,
+            };
+            let line = Line::from(vec![
+                Span::raw(format!("{} ", log.timestamp().format("%H:%M:%S"))),
+                Span::styled(
+                    log.level().to_string(),
+                    self.theme.notification(notification_type),
+                ),
+                Span::raw(format!(": {}", log.message())),
+            ]);
+            _ = buf.set_line(area.x, area.y + i as u16, &line, area.width)
+        }
+    }
+}
+
+/// The explorer's tree, entries colored by their git status.
+pub struct FileTree<'a> {
+    explorer: &'a Explorer,
+    status: Option<&'a git::Status>,
+    focused: bool,
+    theme: &'a Theme,
+}
+
+impl<'a> FileTree<'a> {
+    pub fn new(
+        explorer: &'a Explorer,
+        status: Option<&'a git::Status>,
+        focused: bool,
+        theme: &'a Theme,
+    ) -> Self {
+        FileTree {
+            explorer,
+            status,
+            focused,
+            theme,
+        }
+    }
+}
+
+impl<'a> Widget for FileTree<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let root = self
+            .explorer
+            .root()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "/".into());
+        let block = Block::default()
+            .borders(Borders::RIGHT)
+            .title(format!(" {}/ ", root));
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        // Scrolled to keep the selection in view.
+        let height = inner.height as usize;
+        let first = (self.explorer.selected + 1).saturating_sub(height);
+        let entries = self.explorer.entries().iter().skip(first).take(height);
+        for (i, entry) in entries.enumerate() {
+            let y = inner.y + i as u16;
+            let glyph =
+                match (entry.is_dir, self.explorer.is_expanded(&entry.path)) {
+                    (true, true) => "▾ ",
+                    (true, false) => "▸ ",
+                    (false, _) => "  ",
                 };
-            _ = buf.set_line(area.x, area.y + i, &ratatui_line, area.width)
+            let slash = match entry.is_dir {
+                true => "/",
+                false => "",
+            };
+            let file_status = self
+                .status
+                .and_then(|status| status.file_status(&entry.path));
+            let style = match file_status {
+                Some(FileStatus::Conflicted) => {
+                    self.theme.git(GitElement::Conflicted)
+                }
+                Some(FileStatus::Unstaged) => {
+                    self.theme.git(GitElement::Unstaged)
+                }
+                Some(FileStatus::Staged) => self.theme.git(GitElement::Staged),
+                Some(FileStatus::Untracked) => {
+                    self.theme.git(GitElement::Untracked)
+                }
+                None => Style::default(),
+            };
+            if first + i == self.explorer.selected {
+                let selected = match self.focused {
+                    true => self
+                        .theme
+                        .cursor_line()
+                        .add_modifier(Modifier::REVERSED),
+                    false => self.theme.cursor_line(),
+                };
+                buf.set_style(Rect::new(inner.x, y, inner.width, 1), selected);
+            }
+            let line = Line::from(vec![
+                Span::raw(format!("{}{}", "  ".repeat(entry.depth), glyph)),
+                Span::styled(format!("{}{}", entry.name(), slash), style),
+            ]);
+            _ = buf.set_line(inner.x, y, &line, inner.width);
+        }
+    }
+}
+
+/// Name of the file a window shows, below it when there are several. The
+/// focused window's bar takes the colors of the mode.
+pub struct WindowBar<'a> {
+    file_name: &'a str,
+    /// The mode, when the window is focused.
+    focused: Option<Mode>,
+    theme: &'a Theme,
+}
+
+impl<'a> WindowBar<'a> {
+    pub fn new(
+        file_name: &'a str,
+        focused: Option<Mode>,
+        theme: &'a Theme,
+    ) -> Self {
+        WindowBar {
+            file_name,
+            focused,
+            theme,
+        }
+    }
+}
+
+impl<'a> Widget for WindowBar<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let style = match self.focused {
+            Some(mode) => self.theme.mode(mode),
+            None => self.theme.cursor_line(),
+        };
+        buf.set_style(area, style);
+        let name = Span::styled(format!(" {} ", self.file_name), style);
+        _ = buf.set_span(area.x, area.y, &name, area.width);
+    }
+}
+
+/// Matches of the last search, shown in place of the logs. The first row
+/// sums up the search.
+pub struct GrepList<'a> {
+    grep: &'a Grep,
+    theme: &'a Theme,
+}
+
+impl<'a> GrepList<'a> {
+    pub fn new(grep: &'a Grep, theme: &'a Theme) -> Self {
+        GrepList { grep, theme }
+    }
+}
+
+impl<'a> Widget for GrepList<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        if area.height == 0 {
+            return;
+        }
+        let searching = match self.grep.searching() {
+            true => ", searching…",
+            false => "",
+        };
+        let title = format!(
+            " grep {} ({} matches{})",
+            self.grep.pattern(),
+            self.grep.matches().len(),
+            searching
+        );
+        _ = buf.set_line(
+            area.x,
+            area.y,
+            &Line::styled(title, Style::default().add_modifier(Modifier::BOLD)),
+            area.width,
+        );
+
+        // Scrolled to keep the selection in view.
+        let height = area.height as usize - 1;
+        let first = (self.grep.selected + 1).saturating_sub(height);
+        let matches = self.grep.matches().iter().skip(first).take(height);
+        for (i, found) in matches.enumerate() {
+            let y = area.y + 1 + i as u16;
+            let style = match first + i == self.grep.selected {
+                true => {
+                    self.theme.cursor_line().add_modifier(Modifier::REVERSED)
+                }
+                false => Style::default(),
+            };
+            let ratatui_line = Line::from(vec![
+                Span::styled(
+                    format!(
+                        " {}:{}:{} ",
+                        found.path,
+                        found.line_idx + 1,
+                        found.col + 1
+                    ),
+                    self.theme.line_number(),
+                ),
+                Span::raw(found.text.trim()),
+            ]);
+            buf.set_style(Rect::new(area.x, y, area.width, 1), style);
+            _ = buf.set_line(area.x, y, &ratatui_line, area.width);
+        }
+    }
+}
+
+/// Every diagnostic of the buffer, shown in place of the logs.
+pub struct DiagnosticList<'a> {
+    diagnostics: &'a Diagnostics,
+    theme: &'a Theme,
+}
+
+impl<'a> DiagnosticList<'a> {
+    pub fn new(diagnostics: &'a Diagnostics, theme: &'a Theme) -> Self {
+        DiagnosticList { diagnostics, theme }
+    }
+}
+
+impl<'a> Widget for DiagnosticList<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let diagnostics = self.diagnostics.sorted();
+        if diagnostics.is_empty() {
+            _ = buf.set_line(
+                area.x,
+                area.y,
+                &Line::raw(" no diagnostics"),
+                area.width,
+            );
+            return;
+        }
+
+        let lines = diagnostics.iter().take(area.height as usize);
+        for (i, diagnostic) in lines.enumerate() {
+            let (glyph, style) = self.theme.sign(diagnostic.severity.sign());
+            let source = match &diagnostic.source {
+                Some(source) => format!("{}: ", source),
+                None => String::new(),
+            };
+            let ratatui_line = Line::from(vec![
+                Span::styled(format!(" {} ", glyph), style),
+                Span::raw(format!(
+                    "{}:{} {}{}",
+                    diagnostic.start.line + 1,
+                    diagnostic.start.col + 1,
+                    source,
+                    diagnostic.summary()
+                )),
+            ]);
+            _ = buf.set_line(
+                area.x,
+                area.y + i as u16,
+                &ratatui_line,
+                area.width,
+            )
         }
     }
 }
 
 pub struct LowerTextArea<'a> {
     buffer: &'a Buffer,
+    cursor: Cursor,
+    config: &'a Config,
+    theme: &'a Theme,
+    gutter: &'a Gutter<'a>,
+    diagnostics: &'a Diagnostics,
+    selection: Option<Selection>,
 }
 
 impl<'a> LowerTextArea<'a> {
-    pub fn new(buffer: &'a Buffer) -> Self {
-        LowerTextArea { buffer }
+    pub fn new(
+        buffer: &'a Buffer,
+        cursor: Cursor,
+        config: &'a Config,
+        theme: &'a Theme,
+        gutter: &'a Gutter<'a>,
+        diagnostics: &'a Diagnostics,
+    ) -> Self {
+        LowerTextArea {
+            buffer,
+            cursor,
+            config,
+            theme,
+            gutter,
+            diagnostics,
+            selection: None,
+        }
+    }
+
+    /// Highlight the chars selected in visual mode.
+    pub fn with_selection(mut self, selection: Option<Selection>) -> Self {
+        self.selection = selection;
+
+        self
     }
 }
 
 impl<'a> Widget for LowerTextArea<'a> {
     fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
         // render line cols
-        let scroll_pos = self.buffer.cursor.y;
+        let scroll_pos = self.cursor.y;
+        let gutter = self.gutter;
         for i in 0..area.height {
             let rope_idx = scroll_pos + 1 + i as usize;
-            let line_numb = self.buffer.numb_col(rope_idx);
-            let ratatui_line = Span::raw(line_numb.to_string());
-            _ = buf.set_span(area.x, area.y + i, &ratatui_line, area.width)
+            let ratatui_line = gutter.line(rope_idx, Style::default());
+            _ = buf.set_line(area.x, area.y + i, &ratatui_line, area.width)
         }
         for i in 0..area.height {
             let rope_idx = scroll_pos + 1 + i as usize;
-            let line = self
-                .buffer
-                .line(rope_idx)
-                .unwrap()
-                .to_string()
-                .populate_fill_chars(); // panics
+            let text = self.buffer.line(rope_idx).unwrap().to_string(); // panics
+            let line = text.populate_fill_chars(
+                self.config,
+                self.theme,
+                Style::default(),
+            );
 
-            let ratatui_line = Span::raw(line);
-            _ = buf.set_span(
-                self.buffer.line_numb_col_width() as u16 + area.x,
+            _ = buf.set_line(
+                gutter.width() + area.x,
                 area.y + i,
-                &ratatui_line,
+                &line,
                 area.width,
-            )
+            );
+            let text_area = text_row(area, i, gutter.width());
+            render_selection(
+                self.selection,
+                rope_idx,
+                &text,
+                text_area,
+                self.config,
+                self.theme,
+                buf,
+            );
+            render_diagnostics(
+                self.diagnostics,
+                rope_idx,
+                &text,
+                text_area,
+                self.config,
+                self.theme,
+                buf,
+            );
         }
     }
 }
 
-pub struct ModeBlock {
-    mode: Mode,
+/// The latest keys pressed, the newest on the right. Keys alternate in
+/// style so that presses of the same key can be told apart.
+pub struct Keystrokes<'a> {
+    /// Keys with how many came before them, newest first.
+    keys: Vec<(usize, String)>,
+    theme: &'a Theme,
 }
 
-impl ModeBlock {
-    pub fn new(mode: Mode) -> Self {
-        ModeBlock { mode }
+impl<'a> Keystrokes<'a> {
+    pub fn new<'k>(
+        keystrokes: impl DoubleEndedIterator<Item = (usize, &'k Key)>,
+        theme: &'a Theme,
+    ) -> Self {
+        let keys = keystrokes
+            .rev()
+            .map(|(idx, key)| (idx, format!(" {} ", key)))
+            .collect();
+
+        Keystrokes { keys, theme }
+    }
+
+    /// Columns taken by the newest keys that fit in `max`.
+    pub fn width(&self, max: u16) -> u16 {
+        self.shown(max)
+            .map(|(_, key)| key.chars().count() as u16)
+            .sum()
+    }
+
+    /// The newest keys that fit in `max` columns, newest first.
+    fn shown(&self, max: u16) -> impl Iterator<Item = &(usize, String)> {
+        let mut width = 0;
+        self.keys.iter().take_while(move |(_, key)| {
+            width += key.chars().count() as u16;
+            width <= max
+        })
     }
 }
 
-impl<'a> Widget for ModeBlock {
+impl<'a> Widget for Keystrokes<'a> {
     fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
-        let mut text = self.mode.to_string();
-        text.push(' ');
-        text.insert(0, ' ');
-        let style = self.mode.color();
-        let ratatui_line = Span::styled(text, style);
-        _ = buf.set_span(area.x, area.y, &ratatui_line, area.width);
+        let spans: Vec<Span> = self
+            .shown(area.width)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .map(|(idx, key)| {
+                Span::styled(key.as_str(), self.theme.keystroke(*idx))
+            })
+            .collect();
+        let line = Line::from(spans);
+        let x = area.right() - (line.width() as u16).min(area.width);
+        _ = buf.set_line(x, area.y, &line, area.width);
     }
 }
 
 #[allow(dead_code)]
 pub struct CursorLine<'a> {
     buffer: &'a Buffer,
+    cursor: Cursor,
     mode: Mode,
+    config: &'a Config,
+    theme: &'a Theme,
+    gutter: &'a Gutter<'a>,
+    diagnostics: &'a Diagnostics,
+    selection: Option<Selection>,
 }
 
 impl<'a> CursorLine<'a> {
-    pub fn new(buffer: &'a Buffer, mode: Mode) -> Self {
-        CursorLine { buffer, mode }
+    pub fn new(
+        buffer: &'a Buffer,
+        cursor: Cursor,
+        mode: Mode,
+        config: &'a Config,
+        theme: &'a Theme,
+        gutter: &'a Gutter<'a>,
+        diagnostics: &'a Diagnostics,
+    ) -> Self {
+        CursorLine {
+            buffer,
+            cursor,
+            mode,
+            config,
+            theme,
+            gutter,
+            diagnostics,
+            selection: None,
+        }
+    }
+
+    /// Highlight the chars selected in visual mode.
+    pub fn with_selection(mut self, selection: Option<Selection>) -> Self {
+        self.selection = selection;
+
+        self
     }
 }
 
 impl<'a> Widget for CursorLine<'a> {
     fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
-        let cursor = self.buffer.cursor;
+        let cursor = self.cursor;
         let scroll_pos = cursor.y;
 
         // render line cols
-        let line_numb = self.buffer.numb_col(scroll_pos);
-        let ratatui_line = Span::raw(line_numb.to_string());
-        _ = buf.set_span(area.x, area.y, &ratatui_line, area.width);
+        let style = self.theme.cursor_line();
+        buf.set_style(area, style);
 
-        let style = Style::default().fg(Color::White).bg(Color::Black);
+        let gutter = self.gutter;
+        let ratatui_line = gutter.line(scroll_pos, style);
+        _ = buf.set_line(area.x, area.y, &ratatui_line, area.width);
 
         let mut cursor_char =
             self.buffer.char_under_pos(cursor.into()).unwrap_or(' ');
@@ -207,32 +885,48 @@ impl<'a> Widget for CursorLine<'a> {
         } else {
             cursor_char
         };
-        let cursor_style = match self.buffer.in_visual_bounds(cursor.into()) {
-            true => Style::default().fg(Color::White).bg(Color::Blue),
-            false => Style::default().fg(Color::White).bg(Color::Red),
-        };
+        let cursor_style = self
+            .theme
+            .cursor(self.buffer.in_visual_bounds(cursor.into()));
 
-        let line = self
-            .buffer
-            .line(scroll_pos)
-            .unwrap()
-            .to_string()
-            .populate_fill_chars();
-        let ratatui_line = Span::styled(line.to_string(), style);
-        let ratatui_cursor =
-            Span::styled(cursor_char.to_string(), cursor_style);
-        buf.set_style(area, style);
+        let text = self.buffer.line(scroll_pos).unwrap().to_string();
+        let line = text.populate_fill_chars(self.config, self.theme, style);
+        let cursor_width = match cursor_char {
+            '\t' => self.config.tab_width,
+            _ => 1,
+        };
+        let ratatui_cursor = Span::styled(
+            cursor_char
+                .to_string()
+                .replace('\t', " ")
+                .repeat(cursor_width),
+            cursor_style,
+        );
+        let gutter_width = gutter.width();
 
-        _ = buf.set_span(
-            area.x + self.buffer.line_numb_col_width() as u16,
-            area.y,
-            &ratatui_line,
-            area.width,
+        _ = buf.set_line(area.x + gutter_width, area.y, &line, area.width);
+        render_selection(
+            self.selection,
+            scroll_pos,
+            &text,
+            text_row(area, 0, gutter_width),
+            self.config,
+            self.theme,
+            buf,
+        );
+        render_diagnostics(
+            self.diagnostics,
+            scroll_pos,
+            &text,
+            text_row(area, 0, gutter_width),
+            self.config,
+            self.theme,
+            buf,
         );
         _ = buf.set_span(
             area.x
-                + (self.buffer.line_numb_col_width() + self.buffer.cursor.x)
-                    as u16,
+                + gutter_width
+                + visual_col(&text, cursor.x, self.config.tab_width) as u16,
             area.y,
             &ratatui_cursor,
             area.width,
@@ -240,12 +934,551 @@ impl<'a> Widget for CursorLine<'a> {
     }
 }
 
+/// The `HEAD` version of a hunk, drawn below the cursor line.
+pub struct HunkPreview<'a> {
+    base: &'a [String],
+    theme: &'a Theme,
+}
+
+impl<'a> HunkPreview<'a> {
+    pub fn new(base: &'a [String], theme: &'a Theme) -> Self {
+        HunkPreview { base, theme }
+    }
+
+    /// Height needed to show every line, border included.
+    pub fn height(&self) -> u16 {
+        self.base.len().max(1) as u16 + 2
+    }
+}
+
+impl<'a> Widget for HunkPreview<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" HEAD ")
+            .style(self.theme.cursor_line());
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        let (_, removed) = self.theme.sign(SignKind::GitRemoved);
+        let lines: Vec<Line> = match self.base.is_empty() {
+            true => vec![Line::raw("(new lines)")],
+            false => self
+                .base
+                .iter()
+                .map(|line| Line::styled(format!("-{}", line), removed))
+                .collect(),
+        };
+        for (i, line) in lines.iter().take(inner.height as usize).enumerate() {
+            _ = buf.set_line(inner.x, inner.y + i as u16, line, inner.width);
+        }
+    }
+}
+
+/// Text from the language server, such as hover information, drawn below the
+/// cursor line.
+pub struct InfoPopup<'a> {
+    popup: &'a Popup,
+    theme: &'a Theme,
+}
+
+impl<'a> InfoPopup<'a> {
+    pub fn new(popup: &'a Popup, theme: &'a Theme) -> Self {
+        InfoPopup { popup, theme }
+    }
+
+    /// Height needed to show every line, border included.
+    pub fn height(&self) -> u16 {
+        self.popup.lines.len().max(1) as u16 + 2
+    }
+}
+
+impl<'a> Widget for InfoPopup<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(&*self.popup.title)
+            .style(self.theme.cursor_line());
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        let lines = self.popup.lines.iter().take(inner.height as usize);
+        for (i, line) in lines.enumerate() {
+            let line = Line::raw(line.as_str());
+            _ = buf.set_line(inner.x, inner.y + i as u16, &line, inner.width);
+        }
+    }
+}
+
+/// Completions for the text before the cursor, the selected one
+/// highlighted.
+pub struct CompletionMenu<'a> {
+    completion: &'a Completion,
+    theme: &'a Theme,
+}
+
+impl<'a> CompletionMenu<'a> {
+    pub fn new(completion: &'a Completion, theme: &'a Theme) -> Self {
+        CompletionMenu { completion, theme }
+    }
+
+    fn entries(&self) -> Vec<(String, String)> {
+        let (_, items) = self.completion.visible();
+        items
+            .iter()
+            .map(|item| {
+                let tag = match (&item.detail, item.kind) {
+                    (Some(detail), _) => detail.to_string(),
+                    (None, Kind::Word) => "word".into(),
+                    (None, Kind::Path) => "path".into(),
+                    (None, Kind::Lsp) => "lsp".into(),
+                };
+                (format!(" {}", item.label), format!("{} ", tag))
+            })
+            .collect()
+    }
+
+    /// Width and height needed to show the visible items.
+    pub fn size(&self) -> (u16, u16) {
+        let entries = self.entries();
+        let widest = entries
+            .iter()
+            .map(|(label, tag)| label.chars().count() + 2 + tag.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        (widest as u16, entries.len() as u16)
+    }
+}
+
+impl<'a> Widget for CompletionMenu<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        Clear.render(area, buf);
+        let (first, _) = self.completion.visible();
+        let entries = self.entries();
+        for (i, (label, tag)) in entries.iter().enumerate() {
+            if i as u16 >= area.height {
+                break;
+            }
+            let style = match first + i == self.completion.selected {
+                true => {
+                    self.theme.cursor_line().add_modifier(Modifier::REVERSED)
+                }
+                false => self.theme.cursor_line(),
+            };
+            let y = area.y + i as u16;
+            buf.set_style(Rect::new(area.x, y, area.width, 1), style);
+            _ = buf.set_stringn(area.x, y, label, area.width as usize, style);
+            let tag_width = tag.chars().count() as u16;
+            if tag_width < area.width {
+                buf.set_string(
+                    area.right() - tag_width,
+                    y,
+                    tag,
+                    style.add_modifier(Modifier::DIM),
+                );
+            }
+        }
+    }
+}
+
+/// Files matching the query on the left, the start of the selected one on
+/// the right.
+pub struct FilePicker<'a> {
+    picker: &'a Picker,
+    preview: &'a [String],
+    tab_width: usize,
+    theme: &'a Theme,
+}
+
+impl<'a> FilePicker<'a> {
+    pub fn new(
+        picker: &'a Picker,
+        preview: &'a [String],
+        tab_width: usize,
+        theme: &'a Theme,
+    ) -> Self {
+        FilePicker {
+            picker,
+            preview,
+            tab_width,
+            theme,
+        }
+    }
+
+    fn title(&self) -> String {
+        let walking = match self.picker.walking() {
+            true => "…",
+            false => "",
+        };
+
+        format!(
+            " files {}/{}{} ",
+            self.picker.len_matches(),
+            self.picker.len_files(),
+            walking
+        )
+    }
+}
+
+impl<'a> Widget for FilePicker<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title())
+            .style(self.theme.cursor_line());
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        let [list, preview] = Layout::horizontal([
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ])
+        .areas(inner);
+        let query = Line::from(vec![
+            Span::raw("> "),
+            Span::raw(self.picker.query()),
+            Span::styled(" ", self.theme.cursor(true)),
+        ]);
+        _ = buf.set_line(list.x, list.y, &query, list.width);
+
+        // Scrolled to keep the selection in view.
+        let height = list.height.saturating_sub(1) as usize;
+        let first = (self.picker.selected + 1).saturating_sub(height);
+        let matches = self.picker.matches().skip(first).take(height);
+        for (i, path) in matches.enumerate() {
+            let y = list.y + 1 + i as u16;
+            let style = match first + i == self.picker.selected {
+                true => {
+                    self.theme.cursor_line().add_modifier(Modifier::REVERSED)
+                }
+                false => self.theme.cursor_line(),
+            };
+            buf.set_style(Rect::new(list.x, y, list.width, 1), style);
+            _ = buf.set_stringn(list.x, y, path, list.width as usize, style);
+        }
+
+        let block = Block::default()
+            .borders(Borders::LEFT)
+            .title(self.picker.selected().unwrap_or_default());
+        let inner = block.inner(preview);
+        block.render(preview, buf);
+        let lines = self.preview.iter().take(inner.height as usize);
+        for (i, line) in lines.enumerate() {
+            let line = line.replace('\t', &" ".repeat(self.tab_width));
+            _ = buf.set_stringn(
+                inner.x,
+                inner.y + i as u16,
+                line,
+                inner.width as usize,
+                Style::default(),
+            );
+        }
+    }
+}
+
+/// Input being typed into the message bar.
+pub struct PromptLine<'a> {
+    prompt: &'a Prompt,
+    theme: &'a Theme,
+}
+
+impl<'a> PromptLine<'a> {
+    pub fn new(prompt: &'a Prompt, theme: &'a Theme) -> Self {
+        PromptLine { prompt, theme }
+    }
+
+    fn line(&self) -> Line<'a> {
+        let label = match self.prompt.action {
+            PromptAction::Command => " :".to_string(),
+            action => format!(" {}: ", action),
+        };
+        Line::from(vec![
+            Span::raw(label),
+            Span::raw(self.prompt.input.as_str()),
+            Span::styled(" ", self.theme.cursor(true)),
+        ])
+    }
+
+    pub fn width(&self) -> u16 {
+        self.line().width() as u16
+    }
+}
+
+impl<'a> Widget for PromptLine<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        _ = buf.set_line(area.x, area.y, &self.line(), area.width)
+    }
+}
+
+/// Popup listing the keys that can follow a pending key sequence.
+pub struct WhichKey<'a> {
+    pending: &'a [Key],
+    continuations: &'a [(Key, Continuation)],
+    theme: &'a Theme,
+}
+
+impl<'a> WhichKey<'a> {
+    pub fn new(
+        pending: &'a [Key],
+        continuations: &'a [(Key, Continuation)],
+        theme: &'a Theme,
+    ) -> Self {
+        WhichKey {
+            pending,
+            continuations,
+            theme,
+        }
+    }
+
+    fn title(&self) -> String {
+        self.pending.iter().map(|key| key.to_string()).collect()
+    }
+
+    fn entries(&self) -> Vec<String> {
+        self.continuations
+            .iter()
+            .map(|(key, continuation)| match continuation {
+                Continuation::Command(command) => {
+                    format!("{:<8}{}", key.to_string(), command)
+                }
+                Continuation::Prefix(count) => {
+                    format!("{:<8}+{} more", key.to_string(), count)
+                }
+            })
+            .collect()
+    }
+
+    /// Width and height needed to show every entry, border included.
+    pub fn size(&self) -> (u16, u16) {
+        let widest = self
+            .entries()
+            .iter()
+            .map(|entry| entry.chars().count())
+            .chain(std::iter::once(self.title().chars().count()))
+            .max()
+            .unwrap_or(0);
+
+        (widest as u16 + 4, self.continuations.len() as u16 + 2)
+    }
+}
+
+impl<'a> Widget for WhichKey<'a> {
+    fn render(self, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", self.title()))
+            .style(self.theme.cursor_line());
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        for (i, entry) in self.entries().iter().enumerate() {
+            if i as u16 >= inner.height {
+                break;
+            }
+            let ratatui_line = Line::raw(entry.as_str());
+            _ = buf.set_line(
+                inner.x + 1,
+                inner.y + i as u16,
+                &ratatui_line,
+                inner.width.saturating_sub(1),
+            );
+        }
+    }
+}
+
+/// Row `i` of `area`, right of the gutter.
+fn text_row(area: Rect, i: u16, gutter_width: u16) -> Rect {
+    let gutter_width = gutter_width.min(area.width);
+
+    Rect::new(
+        area.x + gutter_width,
+        area.y + i,
+        area.width - gutter_width,
+        1,
+    )
+}
+
+/// Highlight the selected chars of the line `line_idx`, drawn in `area`.
+fn render_selection(
+    selection: Option<Selection>,
+    line_idx: usize,
+    text: &str,
+    area: Rect,
+    config: &Config,
+    theme: &Theme,
+    buf: &mut ratatui::buffer::Buffer,
+) {
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    let Some(cols) = selection
+        .and_then(|selection| selection.cols(line_idx, text.chars().count()))
+    else {
+        return;
+    };
+    let col = |char_idx| visual_col(text, char_idx, config.tab_width) as u16;
+    let start = col(cols.start).min(area.width);
+    let end = col(cols.end).clamp(start, area.width);
+    buf.set_style(
+        Rect::new(area.x + start, area.y, end - start, 1),
+        theme.selection(),
+    );
+}
+
+/// Underline the diagnostics on the line `line_idx`, drawn in `area`, and
+/// put the message of the most severe one after the end of the line.
+fn render_diagnostics(
+    diagnostics: &Diagnostics,
+    line_idx: usize,
+    text: &str,
+    area: Rect,
+    config: &Config,
+    theme: &Theme,
+    buf: &mut ratatui::buffer::Buffer,
+) {
+    let on_line = diagnostics.on_line(line_idx);
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    let line_len = text.chars().count();
+    let col = |char_idx| visual_col(text, char_idx, config.tab_width) as u16;
+
+    // Least severe first, so the most severe is drawn over the rest.
+    for diagnostic in on_line.iter().rev() {
+        let Some(cols) = diagnostic.cols(line_idx, line_len) else {
+            continue;
+        };
+        let (_, sign_style) = theme.sign(diagnostic.severity.sign());
+        let mut style = Style::new().add_modifier(Modifier::UNDERLINED);
+        if let Some(color) = sign_style.fg {
+            style = style.underline_color(color);
+        }
+        let start = col(cols.start).min(area.width);
+        let end = col(cols.end).clamp(start, area.width);
+        buf.set_style(Rect::new(area.x + start, area.y, end - start, 1), style);
+    }
+
+    let Some(diagnostic) = on_line.first() else {
+        return;
+    };
+    let (glyph, style) = theme.sign(diagnostic.severity.sign());
+    // Past the underline of a diagnostic at the end of the line.
+    let x = col(line_len) + 2;
+    if x < area.width {
+        let virtual_text = format!("{} {}", glyph, diagnostic.summary());
+        _ = buf.set_stringn(
+            area.x + x,
+            area.y,
+            virtual_text,
+            (area.width - x) as usize,
+            style,
+        );
+    }
+}
+
+/// Screen column of the char at `char_idx`, once tabs are expanded. Positions
+/// past the end of the line are one column per char.
+pub(super) fn visual_col(
+    text: &str,
+    char_idx: usize,
+    tab_width: usize,
+) -> usize {
+    let mut col = 0;
+    let mut chars = text.chars();
+    for _ in 0..char_idx {
+        col += match chars.next() {
+            Some('\t') => tab_width - col % tab_width,
+            _ => 1,
+        };
+    }
+
+    col
+}
+
+/// The char drawn at screen column `col`, once tabs are expanded. Columns
+/// past the end of the line are one char each.
+pub(super) fn char_idx_at_col(
+    text: &str,
+    col: usize,
+    tab_width: usize,
+) -> usize {
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    let mut end = 0;
+    for (char_idx, char) in text.chars().enumerate() {
+        end += match char {
+            '\t' => tab_width - end % tab_width,
+            _ => 1,
+        };
+        if col < end {
+            return char_idx;
+        }
+    }
+
+    text.chars().count() + col - end
+}
+
+/// Expand tabs to the next multiple of `tab_width`.
+fn expand_tabs(text: &str, tab_width: usize) -> String {
+    let mut res = String::with_capacity(text.len());
+    for char in text.chars() {
+        if char == '\t' {
+            let col = res.chars().count();
+            res.push_str(&" ".repeat(tab_width - col % tab_width));
+        } else {
+            res.push(char);
+        }
+    }
+
+    res
+}
+
 pub trait PopulateFillChars {
-    fn populate_fill_chars(&mut self) -> Self;
+    /// Expand tabs and swap spaces for the theme's whitespace glyph, styled
+    /// apart from the surrounding text.
+    fn populate_fill_chars(
+        &self,
+        config: &Config,
+        theme: &Theme,
+        style: Style,
+    ) -> Line<'static>;
 }
 
 impl PopulateFillChars for String {
-    fn populate_fill_chars(&mut self) -> Self {
-        self.replace(' ', "·")
+    fn populate_fill_chars(
+        &self,
+        config: &Config,
+        theme: &Theme,
+        style: Style,
+    ) -> Line<'static> {
+        let text = expand_tabs(self, config.tab_width);
+        if !config.whitespace.render {
+            return Line::styled(text, style);
+        }
+
+        let whitespace_style = style.patch(theme.whitespace());
+        let mut spans = vec![];
+        let mut rest = text.as_str();
+        while !rest.is_empty() {
+            let on_space = rest.starts_with(' ');
+            let chunk_len = match on_space {
+                true => rest.find(|c| c != ' '),
+                false => rest.find(' '),
+            }
+            .unwrap_or(rest.len());
+            let (chunk, tail) = rest.split_at(chunk_len);
+
+            let span = match on_space {
+                true => Span::styled(
+                    theme.space_glyph().to_string().repeat(chunk.len()),
+                    whitespace_style,
+                ),
+                false => Span::styled(chunk.to_string(), style),
+            };
+            spans.push(span);
+            rest = tail;
+        }
+
+        Line::from(spans)
     }
 }
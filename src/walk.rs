@@ -0,0 +1,20 @@
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// The files under `root`, skipping hidden files and whatever the
+/// `.gitignore` files in it ignore, in order of their names. Each comes with
+/// its path relative to `root`; files whose path isn't UTF-8 are left out.
+pub fn files(root: &Path) -> impl Iterator<Item = (PathBuf, Box<str>)> {
+    let root = root.to_path_buf();
+    WalkBuilder::new(&root)
+        // Ignore files apply outside of repositories too.
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .filter_map(move |entry| {
+            let path = entry.path().strip_prefix(&root).ok()?.to_str()?.into();
+            Some((entry.into_path(), path))
+        })
+}
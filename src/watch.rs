@@ -0,0 +1,140 @@
+use crate::event::Waker;
+use notify::{
+    event::{AccessKind, AccessMode, ModifyKind},
+    EventKind, RecommendedWatcher, RecursiveMode, Watcher as _,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+};
+
+/// Each watched directory, with the names of the files watched in it.
+type Dirs = HashMap<PathBuf, HashSet<OsString>>;
+
+/// Watches open files for changes made to them by other programs, waking the
+/// main loop when one is, for `changed` to pick it up. The directories of the
+/// files are watched rather than the files, as a file replaced by another
+/// stops being watched. Dropping it stops the watching.
+#[derive(Debug)]
+pub struct Watcher {
+    changed: Receiver<PathBuf>,
+    inner: RecommendedWatcher,
+    dirs: Arc<Mutex<Dirs>>,
+}
+
+impl Watcher {
+    pub fn new(waker: Waker) -> io::Result<Self> {
+        let (sender, changed) = mpsc::channel();
+        let dirs = Arc::new(Mutex::new(Dirs::new()));
+        let watched = Arc::clone(&dirs);
+        let inner = notify::recommended_watcher(
+            move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if !is_change(event.kind) {
+                    return;
+                }
+                let dirs = watched.lock().unwrap();
+                let mut changed = false;
+                for path in event.paths {
+                    let (Some(dir), Some(name)) =
+                        (path.parent(), path.file_name())
+                    else {
+                        continue;
+                    };
+                    if dirs.get(dir).is_some_and(|names| names.contains(name)) {
+                        _ = sender.send(path);
+                        changed = true;
+                    }
+                }
+                if changed {
+                    waker.wake();
+                }
+            },
+        )
+        .map_err(io_error)?;
+
+        Ok(Self {
+            changed,
+            inner,
+            dirs,
+        })
+    }
+
+    /// Watch the file at `path`, which needn't exist yet.
+    pub fn watch(&mut self, path: &Path) -> io::Result<()> {
+        let path = watched_path(path)?;
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return Err(io::ErrorKind::InvalidInput.into());
+        };
+        let mut dirs = self.dirs.lock().unwrap();
+        if !dirs.contains_key(dir) {
+            self.inner
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(io_error)?;
+        }
+        dirs.entry(dir.to_path_buf())
+            .or_default()
+            .insert(name.to_os_string());
+
+        Ok(())
+    }
+
+    /// Files changed since the last call, each once, by their
+    /// `watched_path`.
+    pub fn changed(&self) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = vec![];
+        while let Ok(path) = self.changed.try_recv() {
+            if !changed.contains(&path) {
+                changed.push(path);
+            }
+        }
+
+        changed
+    }
+}
+
+/// The path a watched file is known by: its directory resolved, so the file
+/// matches however it was opened, and its name as is, so it still matches
+/// once the file is deleted.
+pub fn watched_path(path: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    Ok(dir.canonicalize()?.join(name))
+}
+
+/// Written, created, moved or deleted. Reads, and writes still going on,
+/// aren't changes yet.
+fn is_change(kind: EventKind) -> bool {
+    match kind {
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Access(_) | EventKind::Other => false,
+        EventKind::Modify(ModifyKind::Data(_)) => {
+            // A write still going on, unless the backend can't tell when
+            // one is done.
+            !cfg!(target_os = "linux")
+        }
+        _ => true,
+    }
+}
+
+fn io_error(err: notify::Error) -> io::Error {
+    match err.kind {
+        notify::ErrorKind::Io(err) => err,
+        notify::ErrorKind::PathNotFound => io::ErrorKind::NotFound.into(),
+        _ => io::Error::other(err),
+    }
+}
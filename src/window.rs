@@ -0,0 +1,312 @@
+use crate::buffer::Cursor;
+use ratatui::layout::{Constraint, Layout, Rect};
+
+/// Percentage of a split either side keeps at least.
+const MIN_RATIO: u16 = 10;
+
+/// Area the windows are laid out in to find which is next to which.
+/// Only the proportions matter, so it is big to keep rounding out of it.
+const NAVIGATION_AREA: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 1000,
+    height: 1000,
+};
+
+/// A view onto a buffer, with its own cursor and scroll. While a window is
+/// focused its cursor and scroll live in the app, and are stored back here
+/// when focus moves on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Window {
+    /// File of the buffer shown.
+    pub file_name: Box<str>,
+    pub cursor: Cursor,
+    pub scroll: usize,
+}
+
+impl Window {
+    pub fn new(file_name: &str) -> Self {
+        Self {
+            file_name: file_name.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// How a split lays out its two sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    /// One above the other.
+    Horizontal,
+    /// Side by side.
+    Vertical,
+}
+
+/// Where a window is from the focused one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Down,
+    Up,
+    Right,
+}
+
+#[derive(Debug)]
+enum Node {
+    Window(usize),
+    Split {
+        split: Split,
+        /// Percentage of the split the first side takes.
+        ratio: u16,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    fn contains(&self, id: usize) -> bool {
+        match self {
+            Node::Window(window_id) => *window_id == id,
+            Node::Split { first, second, .. } => {
+                first.contains(id) || second.contains(id)
+            }
+        }
+    }
+
+    /// The window of the first side, all the way down.
+    fn first_window(&self) -> usize {
+        match self {
+            Node::Window(id) => *id,
+            Node::Split { first, .. } => first.first_window(),
+        }
+    }
+
+    fn areas(&self, area: Rect, areas: &mut Vec<(usize, Rect)>) {
+        let (split, ratio, first, second) = match self {
+            Node::Window(id) => return areas.push((*id, area)),
+            Node::Split {
+                split,
+                ratio,
+                first,
+                second,
+            } => (split, ratio, first, second),
+        };
+        let constraints = [Constraint::Percentage(*ratio), Constraint::Fill(1)];
+        let [first_area, second_area] = match split {
+            Split::Horizontal => Layout::vertical(constraints).areas(area),
+            // A column between the sides keeps their text apart.
+            Split::Vertical => {
+                Layout::horizontal(constraints).spacing(1).areas(area)
+            }
+        };
+        first.areas(first_area, areas);
+        second.areas(second_area, areas);
+    }
+
+    /// Replace the window `id` with a split of it and `new`.
+    fn split(&mut self, id: usize, split: Split, new: usize) -> bool {
+        match self {
+            Node::Window(window_id) if *window_id == id => {
+                *self = Node::Split {
+                    split,
+                    ratio: 50,
+                    first: Box::new(Node::Window(id)),
+                    second: Box::new(Node::Window(new)),
+                };
+                true
+            }
+            Node::Window(_) => false,
+            Node::Split { first, second, .. } => {
+                first.split(id, split, new) || second.split(id, split, new)
+            }
+        }
+    }
+
+    /// Remove the window `id`, its sibling taking the place of their
+    /// split. Returns the window the sibling starts with.
+    fn remove(&mut self, id: usize) -> Option<usize> {
+        let Node::Split { first, second, .. } = self else {
+            return None;
+        };
+        let is_window =
+            |node: &Node| matches!(node, Node::Window(w) if *w == id);
+        let sibling = match (is_window(first), is_window(second)) {
+            (true, _) => std::mem::replace(&mut **second, Node::Window(id)),
+            (_, true) => std::mem::replace(&mut **first, Node::Window(id)),
+            _ => return first.remove(id).or_else(|| second.remove(id)),
+        };
+        *self = sibling;
+
+        Some(self.first_window())
+    }
+
+    /// Move the nearest split of the kind `split` holding the window `id`,
+    /// so the window's side grows by `delta` percent.
+    fn resize(&mut self, id: usize, kind: Split, delta: i16) -> bool {
+        let Node::Split {
+            split,
+            ratio,
+            first,
+            second,
+        } = self
+        else {
+            return false;
+        };
+        let in_first = first.contains(id);
+        if !in_first && !second.contains(id) {
+            return false;
+        }
+        let inner = match in_first {
+            true => first.resize(id, kind, delta),
+            false => second.resize(id, kind, delta),
+        };
+        if inner || *split != kind {
+            return inner;
+        }
+        let delta = match in_first {
+            true => delta,
+            false => -delta,
+        };
+        *ratio = (*ratio as i16 + delta)
+            .clamp(MIN_RATIO as i16, 100 - MIN_RATIO as i16)
+            as u16;
+
+        true
+    }
+}
+
+/// The windows on screen, laid out by splitting one in two.
+#[derive(Debug)]
+pub struct Windows {
+    layout: Node,
+    /// Windows by id, `None` once closed.
+    windows: Vec<Option<Window>>,
+    focused: usize,
+}
+
+impl Default for Windows {
+    fn default() -> Self {
+        Self::new(Window::default())
+    }
+}
+
+impl Windows {
+    pub fn new(window: Window) -> Self {
+        Self {
+            layout: Node::Window(0),
+            windows: vec![Some(window)],
+            focused: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Id of the focused window.
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    pub fn window(&self, id: usize) -> Option<&Window> {
+        self.windows.get(id)?.as_ref()
+    }
+
+    pub fn window_mut(&mut self, id: usize) -> Option<&mut Window> {
+        self.windows.get_mut(id)?.as_mut()
+    }
+
+    /// The windows still open.
+    pub fn iter(&self) -> impl Iterator<Item = &Window> {
+        self.windows.iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Window> {
+        self.windows.iter_mut().flatten()
+    }
+
+    /// Id and area of each window laid out in `area`, from the top left.
+    pub fn areas(&self, area: Rect) -> Vec<(usize, Rect)> {
+        let mut areas = vec![];
+        self.layout.areas(area, &mut areas);
+
+        areas
+    }
+
+    /// Split the focused window, putting `window` below or right of it.
+    /// Returns the new window's id, which isn't focused yet.
+    pub fn split(&mut self, split: Split, window: Window) -> usize {
+        let id = self.windows.len();
+        self.windows.push(Some(window));
+        self.layout.split(self.focused, split, id);
+
+        id
+    }
+
+    pub fn focus(&mut self, id: usize) {
+        if self.window(id).is_some() {
+            self.focused = id;
+        }
+    }
+
+    /// Close the focused window and focus the one taking its place.
+    /// `None` when it is the last.
+    pub fn close(&mut self) -> Option<usize> {
+        let next = self.layout.remove(self.focused)?;
+        self.windows[self.focused] = None;
+        self.focused = next;
+
+        Some(next)
+    }
+
+    /// The window next to the focused one in `direction`, the one
+    /// overlapping it the most when there are several.
+    pub fn neighbour(&self, direction: Direction) -> Option<usize> {
+        let areas = self.areas(NAVIGATION_AREA);
+        let (_, from) = areas.iter().find(|(id, _)| *id == self.focused)?;
+        let overlap = |a: (u16, u16), b: (u16, u16)| {
+            a.1.min(b.1).saturating_sub(a.0.max(b.0))
+        };
+        let horizontal = (from.left(), from.right());
+        let vertical = (from.top(), from.bottom());
+
+        areas
+            .iter()
+            .filter(|(id, _)| *id != self.focused)
+            .filter_map(|(id, to)| {
+                let (gap, shared) = match direction {
+                    Direction::Left if to.right() <= from.left() => (
+                        from.left() - to.right(),
+                        overlap(vertical, (to.top(), to.bottom())),
+                    ),
+                    Direction::Right if to.left() >= from.right() => (
+                        to.left() - from.right(),
+                        overlap(vertical, (to.top(), to.bottom())),
+                    ),
+                    Direction::Up if to.bottom() <= from.top() => (
+                        from.top() - to.bottom(),
+                        overlap(horizontal, (to.left(), to.right())),
+                    ),
+                    Direction::Down if to.top() >= from.bottom() => (
+                        to.top() - from.bottom(),
+                        overlap(horizontal, (to.left(), to.right())),
+                    ),
+                    _ => return None,
+                };
+                (shared > 0).then_some((gap, std::cmp::Reverse(shared), *id))
+            })
+            .min()
+            .map(|(_, _, id)| id)
+    }
+
+    /// Grow the focused window by `delta` percent of the split it is in,
+    /// or shrink it for a negative `delta`. Returns whether there was a
+    /// split of the kind to resize.
+    pub fn resize(&mut self, split: Split, delta: i16) -> bool {
+        self.layout.resize(self.focused, split, delta)
+    }
+}
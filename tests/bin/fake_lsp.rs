@@ -0,0 +1,350 @@
+//! A tiny language server for the integration tests. It keeps the documents it
+//! is sent in sync and answers requests by looking at the word under the
+//! position:
+//!
+//! - hover gives the word and the text of its line,
+//! - definition is the first place the word appears, looking first in the
+//!   file given with `--also`,
+//! - references are every place it appears,
+//! - rename replaces it everywhere, also in the file given with `--also`,
+//! - formatting trims trailing whitespace,
+//! - completion offers a `fake_fn` snippet and a `fake_value`, also after a
+//!   `.`.
+//!
+//! Whenever a document changes every `FIXME` in it is published as an error
+//! and every `TODO` as a warning.
+//!
+//! With `--log <path>` every message received is appended to the file, one
+//! per line.
+
+use editor::lsp::{path_to_uri, read_message, write_message};
+use ropey::Rope;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, BufReader, Write},
+    path::PathBuf,
+};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let arg = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|idx| args.get(idx + 1))
+            .map(PathBuf::from)
+    };
+    let log = arg("--log");
+    let also = arg("--also");
+
+    let mut server = Server {
+        docs: HashMap::new(),
+        also,
+        stdout: io::stdout(),
+    };
+    let mut stdin = BufReader::new(io::stdin());
+    while let Ok(Some(message)) = read_message(&mut stdin) {
+        if let Some(log) = &log {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(log)
+                .unwrap();
+            writeln!(file, "{}", message).unwrap();
+        }
+        if !server.handle(&message) {
+            return;
+        }
+    }
+}
+
+struct Server {
+    docs: HashMap<String, Rope>,
+    also: Option<PathBuf>,
+    stdout: io::Stdout,
+}
+
+impl Server {
+    /// Returns false on `exit`.
+    fn handle(&mut self, message: &Value) -> bool {
+        let method = message.get("method").and_then(Value::as_str);
+        let params = message.get("params").cloned().unwrap_or_default();
+        let Some(id) = message.get("id").cloned() else {
+            return self.notification(method.unwrap_or_default(), &params);
+        };
+        let Some(method) = method else {
+            // An answer to a request of ours.
+            return true;
+        };
+
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 2,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "renameProvider": true,
+                    "documentFormattingProvider": true,
+                    "completionProvider": { "triggerCharacters": ["."] },
+                },
+            }),
+            "textDocument/hover" => self.hover(&params),
+            "textDocument/definition" => self.definition(&params),
+            "textDocument/references" => self.occurrences(&params).into(),
+            "textDocument/rename" => self.rename(&params),
+            "textDocument/formatting" => self.formatting(&params),
+            "textDocument/completion" => completion(),
+            _ => Value::Null,
+        };
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+
+        true
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> bool {
+        match method {
+            "initialized" => {
+                // Servers may ask things of the client at any time.
+                self.send(json!({
+                    "jsonrpc": "2.0",
+                    "id": "progress",
+                    "method": "window/workDoneProgress/create",
+                    "params": { "token": "fake" },
+                }));
+                self.send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "window/showMessage",
+                    "params": { "type": 3, "message": "fake-lsp ready" },
+                }));
+            }
+            "textDocument/didOpen" => {
+                let doc = params.get("textDocument").unwrap();
+                let uri = doc.get("uri").and_then(Value::as_str).unwrap();
+                let text = doc.get("text").and_then(Value::as_str).unwrap();
+                self.docs.insert(uri.into(), Rope::from_str(text));
+                self.publish_diagnostics(uri);
+            }
+            "textDocument/didChange" => {
+                let uri = params
+                    .pointer("/textDocument/uri")
+                    .and_then(Value::as_str)
+                    .unwrap();
+                let doc = self.docs.get_mut(uri).unwrap();
+                let changes = params.get("contentChanges").unwrap();
+                for change in changes.as_array().unwrap() {
+                    let text =
+                        change.get("text").and_then(Value::as_str).unwrap();
+                    match change.get("range") {
+                        Some(range) => {
+                            let start =
+                                char_idx(doc, range.get("start").unwrap());
+                            let end = char_idx(doc, range.get("end").unwrap());
+                            doc.remove(start..end);
+                            doc.insert(start, text);
+                        }
+                        None => *doc = Rope::from_str(text),
+                    }
+                }
+                self.publish_diagnostics(uri);
+            }
+            "exit" => return false,
+            _ => {}
+        }
+
+        true
+    }
+
+    fn send(&mut self, message: Value) {
+        write_message(&mut self.stdout, &message).unwrap();
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str) {
+        let doc = &self.docs[uri];
+        let mut diagnostics = vec![];
+        for (word, severity) in [("FIXME", 1_u64), ("TODO", 2)] {
+            for range in ranges(doc, word) {
+                diagnostics.push(json!({
+                    "range": range,
+                    "severity": severity,
+                    "source": "fake",
+                    "message": format!("{} left in", word),
+                }));
+            }
+        }
+
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }));
+    }
+
+    /// The document and the word at the position of a request.
+    fn word(&self, params: &Value) -> Option<(String, &Rope, String)> {
+        let uri = params.pointer("/textDocument/uri")?.as_str()?;
+        let doc = self.docs.get(uri)?;
+        let char_idx = char_idx(doc, params.get("position")?);
+        let chars: Vec<char> = doc.chars().collect();
+        let is_word = |char: &char| char.is_alphanumeric() || *char == '_';
+        if !chars.get(char_idx).is_some_and(is_word) {
+            return None;
+        }
+        let start = chars[..char_idx]
+            .iter()
+            .rposition(|char| !is_word(char))
+            .map_or(0, |idx| idx + 1);
+        let end = chars[char_idx..]
+            .iter()
+            .position(|char| !is_word(char))
+            .map_or(chars.len(), |idx| char_idx + idx);
+
+        Some((uri.into(), doc, chars[start..end].iter().collect()))
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((_, doc, word)) = self.word(params) else {
+            return Value::Null;
+        };
+        let line_idx = params
+            .pointer("/position/line")
+            .and_then(Value::as_u64)
+            .unwrap() as usize;
+        let line = doc.line(line_idx).to_string();
+
+        json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!("`{}`\nline: {}", word, line.trim_end()),
+            },
+        })
+    }
+
+    fn occurrences(&self, params: &Value) -> Vec<Value> {
+        let Some((uri, doc, word)) = self.word(params) else {
+            return vec![];
+        };
+
+        ranges(doc, &word)
+            .into_iter()
+            .map(|range| json!({ "uri": uri, "range": range }))
+            .collect()
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let Some((uri, doc, word)) = self.word(params) else {
+            return Value::Null;
+        };
+        if let Some(also) = &self.also {
+            let text = fs::read_to_string(also).unwrap();
+            let also = fs::canonicalize(also).unwrap();
+            if let Some(range) = ranges(&Rope::from_str(&text), &word).first() {
+                return json!({ "uri": path_to_uri(&also), "range": range });
+            }
+        }
+
+        ranges(doc, &word)
+            .first()
+            .map(|range| json!({ "uri": uri, "range": range }))
+            .unwrap_or_default()
+    }
+
+    fn rename(&self, params: &Value) -> Value {
+        let Some((uri, doc, word)) = self.word(params) else {
+            return Value::Null;
+        };
+        let new_name = params.get("newName").cloned().unwrap_or_default();
+        let edits = |doc: &Rope| -> Value {
+            ranges(doc, &word)
+                .into_iter()
+                .map(|range| json!({ "range": range, "newText": new_name }))
+                .collect::<Vec<_>>()
+                .into()
+        };
+
+        let mut changes = json!({ uri: edits(doc) });
+        if let Some(also) = &self.also {
+            let text = fs::read_to_string(also).unwrap();
+            let also = fs::canonicalize(also).unwrap();
+            changes[path_to_uri(&also)] = edits(&Rope::from_str(&text));
+        }
+
+        json!({ "changes": changes })
+    }
+
+    fn formatting(&self, params: &Value) -> Value {
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .unwrap();
+        let doc = &self.docs[uri];
+
+        let mut edits = vec![];
+        for (line_idx, line) in doc.lines().enumerate() {
+            let line = line.to_string();
+            let line = line.trim_end_matches('\n');
+            let trimmed = line.trim_end();
+            if trimmed.len() < line.len() {
+                edits.push(json!({
+                    "range": range(line_idx, trimmed.len(), line.len()),
+                    "newText": "",
+                }));
+            }
+        }
+
+        edits.into()
+    }
+}
+
+fn completion() -> Value {
+    json!({
+        "isIncomplete": false,
+        "items": [
+            {
+                "label": "fake_fn",
+                "insertText": "fake_fn(${1:arg}, $2)$0",
+                "insertTextFormat": 2,
+                "detail": "fn",
+            },
+            { "label": "fake_value" },
+        ],
+    })
+}
+
+fn char_idx(doc: &Rope, position: &Value) -> usize {
+    let line = position.get("line").and_then(Value::as_u64).unwrap() as usize;
+    let character =
+        position.get("character").and_then(Value::as_u64).unwrap() as usize;
+    if line >= doc.len_lines() {
+        return doc.len_chars();
+    }
+
+    doc.line_to_char(line) + doc.line(line).utf16_cu_to_char(character)
+}
+
+/// Ranges of every whole word match, assuming ASCII lines.
+fn ranges(doc: &Rope, word: &str) -> Vec<Value> {
+    let is_word = |char: char| char.is_alphanumeric() || char == '_';
+    let mut ranges = vec![];
+    for (line_idx, line) in doc.lines().enumerate() {
+        let line = line.to_string();
+        for (start, _) in line.match_indices(word) {
+            let end = start + word.len();
+            let before = line[..start].chars().next_back();
+            let after = line[end..].chars().next();
+            if !before.is_some_and(is_word) && !after.is_some_and(is_word) {
+                ranges.push(range(line_idx, start, end));
+            }
+        }
+    }
+
+    ranges
+}
+
+fn range(line: usize, start: usize, end: usize) -> Value {
+    let position =
+        |character: usize| json!({ "line": line, "character": character });
+
+    json!({ "start": position(start), "end": position(end) })
+}
@@ -0,0 +1,109 @@
+use super::{app_with, labels, last_line};
+use crate::helpers::{
+    draw, type_text, CTRL_N_KEY, CTRL_P_KEY, CURSOR_ROW, ENTER_KEY, ESC_KEY,
+};
+
+#[test]
+fn ctrl_n_should_offer_buffer_words_ranked() {
+    let (_dir, mut app, mut handler) =
+        app_with("foo_bar fizzbuzz\nformat\nfrobnicate\n");
+
+    type_text("fo", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    assert_eq!(labels(&app), ["foo_bar", "format", "frobnicate"]);
+}
+
+#[test]
+fn typing_should_narrow_the_menu() {
+    let (_dir, mut app, mut handler) = app_with("format\nfrobnicate\n");
+    type_text("f", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    type_text("rb", &mut app, &mut handler);
+
+    assert_eq!(labels(&app), ["frobnicate"]);
+}
+
+#[test]
+fn menu_should_close_when_nothing_matches() {
+    let (_dir, mut app, mut handler) = app_with("format\n");
+    type_text("f", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    type_text("z", &mut app, &mut handler);
+
+    assert!(app.completion().is_none());
+}
+
+#[test]
+fn ctrl_n_and_ctrl_p_should_move_the_selection() {
+    let (_dir, mut app, mut handler) = app_with("format\nfrobnicate\n");
+    type_text("f", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+    assert_eq!(app.completion().unwrap().selected, 1);
+    handler.handle_key_event(CTRL_P_KEY, &mut app);
+    handler.handle_key_event(CTRL_P_KEY, &mut app);
+    assert_eq!(app.completion().unwrap().selected, 1);
+}
+
+#[test]
+fn enter_should_replace_typed_word() {
+    let (_dir, mut app, mut handler) = app_with("format\nfrobnicate\n");
+    type_text("frb", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(app.completion().is_none());
+    assert_eq!(last_line(&app), "frobnicate");
+    assert_eq!(app.buffer.cursor, (10, 2).into());
+}
+
+#[test]
+fn esc_should_close_the_menu() {
+    let (_dir, mut app, mut handler) = app_with("format\n");
+    type_text("f", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    handler.handle_key_event(ESC_KEY, &mut app);
+
+    assert!(app.completion().is_none());
+}
+
+#[test]
+fn ctrl_n_with_nothing_to_complete_should_warn() {
+    let (_dir, mut app, mut handler) = app_with("1\n");
+
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    assert!(app.completion().is_none());
+    assert!(app
+        .notifs()
+        .last()
+        .unwrap()
+        .to_string()
+        .ends_with("no completions"));
+}
+
+#[test]
+fn menu_should_be_drawn_below_the_cursor() {
+    let (_dir, mut app, mut handler) = app_with("format\nfrobnicate\n");
+    type_text("f", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let row = |y| -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).symbol())
+            .collect()
+    };
+    // Lined up with the word being completed, after the gutter.
+    assert!(row(CURSOR_ROW).starts_with("   3┆ f "));
+    assert!(row(CURSOR_ROW + 1)[6..].starts_with(" format      word "));
+    assert!(row(CURSOR_ROW + 2)[6..].starts_with(" frobnicate  word "));
+}
@@ -0,0 +1,38 @@
+use crate::helpers::{project, I_KEY};
+use editor::{app::App, handler::Handler};
+use tempfile::TempDir;
+
+mod menu;
+mod paths;
+mod snippets;
+
+/// App editing `file.txt` in a new dir, in insert mode on an empty line
+/// after `contents`.
+fn app_with(contents: &str) -> (TempDir, App, Handler) {
+    // The last new line of a file isn't a line of the buffer.
+    let contents = format!("{}\n", contents);
+    let (dir, mut app, mut handler) = project(&[("file.txt", &contents)]);
+
+    let last_line = app.buffer.len_lines() - 1;
+    app.buffer.cursor = (0, last_line).into();
+    handler.handle_key_event(I_KEY, &mut app);
+
+    (dir, app, handler)
+}
+
+fn labels(app: &App) -> Vec<String> {
+    app.completion()
+        .map(|completion| {
+            completion
+                .items
+                .iter()
+                .map(|item| item.label.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn last_line(app: &App) -> String {
+    let line = app.buffer.line(app.buffer.len_lines() - 1).unwrap();
+    line.to_string()
+}
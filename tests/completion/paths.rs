@@ -0,0 +1,38 @@
+use super::{app_with, labels, last_line};
+use crate::helpers::{type_text, ENTER_KEY};
+use std::fs;
+
+#[test]
+fn slash_should_list_the_directory() {
+    let (dir, mut app, mut handler) = app_with("\n");
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "").unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+    fs::create_dir(dir.path().join("src/ui")).unwrap();
+    fs::write(dir.path().join("src/.hidden"), "").unwrap();
+
+    type_text("src/", &mut app, &mut handler);
+
+    assert_eq!(labels(&app), ["lib.rs", "main.rs", "ui/"]);
+}
+
+#[test]
+fn path_should_complete_the_file_name() {
+    let (dir, mut app, mut handler) = app_with("\n");
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "").unwrap();
+    type_text("./src/ma", &mut app, &mut handler);
+
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert_eq!(last_line(&app), "./src/main.rs");
+}
+
+#[test]
+fn comment_slashes_should_not_list_anything() {
+    let (_dir, mut app, mut handler) = app_with("\n");
+
+    type_text("//", &mut app, &mut handler);
+
+    assert!(app.completion().is_none());
+}
@@ -0,0 +1,66 @@
+use super::{app_with, last_line};
+use crate::helpers::{type_text, CTRL_N_KEY, ENTER_KEY, TAB_KEY};
+use editor::completion::{Item, Kind, Snippet, Source};
+
+#[test]
+fn snippet_should_fill_in_placeholders() {
+    let snippet = Snippet::parse("fn ${1:name}(${2}) {$0}");
+
+    assert_eq!(snippet.text, "fn name() {}");
+    assert_eq!(snippet.stops, [3..7, 8..8, 11..11]);
+}
+
+#[test]
+fn snippet_should_handle_escapes_choices_and_variables() {
+    let snippet = Snippet::parse(r"\$1 ${1|one,two|} $TM_FILENAME${2:x}");
+
+    assert_eq!(snippet.text, "$1 one x");
+    assert_eq!(snippet.stops, [3..6, 7..8]);
+}
+
+/// Completes every word to a `call(arg, more)` snippet.
+#[derive(Debug)]
+struct Calls;
+
+impl Source for Calls {
+    fn complete(&self, ctx: &editor::completion::Context) -> Vec<Item> {
+        vec![Item {
+            label: "call".into(),
+            text: "call(${1:arg}, $2)$0".into(),
+            snippet: true,
+            start: editor::completion::word_start(ctx.line),
+            kind: Kind::Word,
+            detail: None,
+        }]
+    }
+}
+
+#[test]
+fn tab_should_visit_tab_stops_in_order() {
+    let (_dir, mut app, mut handler) = app_with("\n");
+    app.add_completion_source(Box::new(Calls));
+    type_text("ca", &mut app, &mut handler);
+    handler.handle_key_event(CTRL_N_KEY, &mut app);
+
+    handler.handle_key_event(ENTER_KEY, &mut app);
+    assert_eq!(last_line(&app), "call(arg, )");
+    assert_eq!(app.buffer.cursor, (5, 1).into());
+
+    type_text("x", &mut app, &mut handler);
+    handler.handle_key_event(TAB_KEY, &mut app);
+    assert_eq!(app.buffer.cursor, (11, 1).into());
+    type_text("y", &mut app, &mut handler);
+    handler.handle_key_event(TAB_KEY, &mut app);
+
+    assert_eq!(last_line(&app), "call(xarg, y)");
+    assert_eq!(app.buffer.cursor, (13, 1).into());
+}
+
+#[test]
+fn tab_without_snippet_should_insert_tab() {
+    let (_dir, mut app, mut handler) = app_with("\n");
+
+    handler.handle_key_event(TAB_KEY, &mut app);
+
+    assert_eq!(last_line(&app), "\t");
+}
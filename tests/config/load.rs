@@ -0,0 +1,120 @@
+use editor::{
+    config::{Config, LineNumbers, Segment},
+    logger::Level,
+};
+use std::path::PathBuf;
+
+#[test]
+fn empty_config_should_use_defaults() {
+    let (config, errors) = Config::from_toml("");
+
+    assert!(errors.is_empty());
+    assert_eq!(config.tab_width, 4);
+    assert_eq!(config.line_numbers, LineNumbers::Absolute);
+    assert!(config.layout.centered);
+}
+
+#[test]
+fn missing_config_file_should_use_defaults() {
+    let (config, errors) =
+        Config::load_from(PathBuf::from("tests/helpers/does_not_exist.toml"));
+
+    assert!(errors.is_empty());
+    assert_eq!(&*config.theme, "default");
+}
+
+#[test]
+fn config_should_set_options() {
+    let (config, errors) = Config::from_toml(
+        r#"
+        tab_width = 2
+        scrolloff = 3
+        line_numbers = "none"
+        theme = "gruvbox"
+        log_level = "warning"
+
+        [whitespace]
+        render = false
+
+        [layout]
+        centered = false
+        logs = false
+        screencast = true
+
+        [status_line]
+        left = ["mode", "lines"]
+        "#,
+    );
+
+    assert!(errors.is_empty());
+    assert_eq!(config.tab_width, 2);
+    assert_eq!(config.scrolloff, 3);
+    assert_eq!(config.line_numbers, LineNumbers::None);
+    assert_eq!(&*config.theme, "gruvbox");
+    assert_eq!(config.log_level, Level::Warning);
+    assert!(!config.whitespace.render);
+    assert!(!config.layout.centered);
+    assert!(!config.layout.logs);
+    assert!(config.layout.git_summary);
+    assert!(config.layout.screencast);
+    assert_eq!(config.status_line.left, [Segment::Mode, Segment::Lines]);
+    assert_eq!(config.status_line.right.len(), 7);
+}
+
+#[test]
+fn invalid_tab_width_should_fall_back_to_default() {
+    let (config, errors) = Config::from_toml("tab_width = 0\nscrolloff = 1");
+
+    assert_eq!(config.tab_width, 4);
+    assert_eq!(config.scrolloff, 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "Config error: tab_width is 0, expected a width from 1 to 16"
+    );
+}
+
+#[test]
+fn unknown_theme_should_fall_back_to_default() {
+    let (config, errors) = Config::from_toml("theme = \"not-a-theme\"");
+
+    assert_eq!(&*config.theme, "default");
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn unknown_option_should_error() {
+    let (_, errors) = Config::from_toml("tabwidth = 2");
+
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn bad_option_should_keep_the_other_options() {
+    let (config, errors) = Config::from_toml(
+        "tabwidth = 2\nscrolloff = \"far\"\ntab_width = 8\n\
+         [layout]\ncentered = 1\nlogs = false",
+    );
+
+    assert_eq!(errors.len(), 3);
+    assert_eq!(config.tab_width, 8);
+    assert_eq!(config.scrolloff, 5);
+    assert!(config.layout.centered);
+    assert!(!config.layout.logs);
+}
+
+#[test]
+fn unknown_status_line_segment_should_error() {
+    let (config, errors) =
+        Config::from_toml("[status_line]\nright = [\"clock\"]");
+
+    assert_eq!(errors.len(), 1);
+    assert!(config.status_line.right.contains(&Segment::Position));
+}
+
+#[test]
+fn malformed_config_should_error() {
+    let (config, errors) = Config::from_toml("tab_width = ");
+
+    assert_eq!(config.tab_width, 4);
+    assert_eq!(errors.len(), 1);
+}
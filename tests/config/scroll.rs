@@ -0,0 +1,55 @@
+use crate::helpers::app_default;
+use editor::config::Config;
+
+fn app_scrolling_with_scrolloff(
+    scrolloff: usize,
+) -> (editor::app::App, editor::handler::Handler) {
+    let (mut app, handler) = app_default();
+    let (mut config, _) = Config::from_toml("[layout]\ncentered = false");
+    config.scrolloff = scrolloff;
+    app.configure(config).unwrap();
+
+    (app, handler)
+}
+
+#[test]
+fn cursor_within_view_should_not_scroll() {
+    let (mut app, _) = app_scrolling_with_scrolloff(1);
+    app.move_down(2).unwrap();
+
+    app.scroll_to_cursor(5);
+
+    assert_eq!(app.scroll(), 0)
+}
+
+#[test]
+fn cursor_inside_scrolloff_at_bottom_should_scroll() {
+    let (mut app, _) = app_scrolling_with_scrolloff(1);
+    app.move_down(4).unwrap();
+
+    app.scroll_to_cursor(5);
+
+    assert_eq!(app.scroll(), 1)
+}
+
+#[test]
+fn cursor_inside_scrolloff_at_top_should_scroll_back() {
+    let (mut app, _) = app_scrolling_with_scrolloff(1);
+    app.move_down(6).unwrap();
+    app.scroll_to_cursor(3);
+    app.move_up(5).unwrap();
+
+    app.scroll_to_cursor(3);
+
+    assert_eq!(app.scroll(), 0)
+}
+
+#[test]
+fn scrolloff_larger_than_view_should_center_cursor() {
+    let (mut app, _) = app_scrolling_with_scrolloff(10);
+    app.move_down(4).unwrap();
+
+    app.scroll_to_cursor(5);
+
+    assert_eq!(app.scroll(), 2)
+}
@@ -0,0 +1,44 @@
+use super::{app_with_diagnostics, row};
+use crate::helpers::{app_default, draw, D_KEY, SPACE_KEY};
+use editor::{app::App, handler::Handler};
+
+fn toggle_list(app: &mut App, handler: &mut Handler) {
+    for key in [SPACE_KEY, D_KEY] {
+        handler.handle_key_event(key, app);
+    }
+}
+
+#[test]
+fn list_should_show_every_diagnostic_in_order() {
+    let (mut app, mut handler) = app_with_diagnostics();
+
+    toggle_list(&mut app, &mut handler);
+    let terminal = draw(&app, &handler);
+
+    assert!(app.diagnostic_list());
+    assert!(row(&terminal, 12).contains("● 1:1 check: bad start"));
+    assert!(row(&terminal, 13).contains("● 2:5 check: odd word"));
+    assert!(row(&terminal, 14).contains("● 3:1 check: blank line"));
+}
+
+#[test]
+fn empty_list_should_say_so() {
+    let (mut app, mut handler) = app_default();
+
+    toggle_list(&mut app, &mut handler);
+    let terminal = draw(&app, &handler);
+
+    assert!(row(&terminal, 12).contains("no diagnostics"));
+}
+
+#[test]
+fn toggling_twice_should_hide_list() {
+    let (mut app, mut handler) = app_with_diagnostics();
+
+    toggle_list(&mut app, &mut handler);
+    toggle_list(&mut app, &mut handler);
+    let terminal = draw(&app, &handler);
+
+    assert!(!app.diagnostic_list());
+    assert!(!row(&terminal, 12).contains("bad start"));
+}
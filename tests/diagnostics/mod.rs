@@ -0,0 +1,50 @@
+use crate::helpers::app_default;
+use editor::{
+    app::App,
+    diagnostic::{Diagnostic, Pos, Severity},
+    handler::Handler,
+};
+use ratatui::{backend::TestBackend, Terminal};
+
+mod list;
+mod navigation;
+mod render;
+
+/// The default app, checked by an external checker that found an error at
+/// the start of the first line, a warning on the second and a hint on the
+/// empty third.
+fn app_with_diagnostics() -> (App, Handler) {
+    let (mut app, handler) = app_default();
+    app.set_diagnostics(
+        "check",
+        vec![
+            diagnostic((0, 0), (0, 2), Severity::Error, "bad start"),
+            diagnostic((1, 4), (1, 8), Severity::Warning, "odd word"),
+            diagnostic((2, 0), (2, 0), Severity::Hint, "blank line"),
+        ],
+    );
+
+    (app, handler)
+}
+
+fn diagnostic(
+    (start_line, start_col): (usize, usize),
+    (end_line, end_col): (usize, usize),
+    severity: Severity,
+    message: &str,
+) -> Diagnostic {
+    Diagnostic {
+        start: Pos::new(start_line, start_col),
+        end: Pos::new(end_line, end_col),
+        severity,
+        message: message.into(),
+        source: Some("check".into()),
+    }
+}
+
+fn row(terminal: &Terminal<TestBackend>, y: u16) -> String {
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.width)
+        .map(|x| buffer.get(x, y).symbol())
+        .collect()
+}
@@ -0,0 +1,39 @@
+use super::app_with_diagnostics;
+use crate::helpers::{key, press, DOWN_KEY, D_KEY};
+
+#[test]
+fn next_should_move_to_start_of_next_diagnostic() {
+    let (mut app, mut handler) = app_with_diagnostics();
+
+    press(&[key(']'), D_KEY], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.cursor, (4, 1).into());
+    assert_eq!(&*app.diagnostic_under_cursor().unwrap().message, "odd word");
+}
+
+#[test]
+fn prev_should_move_to_start_of_prev_diagnostic() {
+    let (mut app, mut handler) = app_with_diagnostics();
+    press(&[DOWN_KEY, DOWN_KEY, DOWN_KEY], &mut app, &mut handler);
+
+    press(&[key('['), D_KEY], &mut app, &mut handler);
+    assert_eq!(app.buffer.cursor, (0, 2).into());
+    press(&[key('['), D_KEY], &mut app, &mut handler);
+    assert_eq!(app.buffer.cursor, (4, 1).into());
+}
+
+#[test]
+fn next_after_last_diagnostic_should_warn() {
+    let (mut app, mut handler) = app_with_diagnostics();
+    press(&[DOWN_KEY, DOWN_KEY, DOWN_KEY], &mut app, &mut handler);
+
+    press(&[key(']'), D_KEY], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.cursor, (0, 3).into());
+    assert!(app
+        .notifs()
+        .last()
+        .unwrap()
+        .to_string()
+        .ends_with("no more diagnostics"));
+}
@@ -0,0 +1,58 @@
+use super::{app_with_diagnostics, row};
+use crate::helpers::{draw, CURSOR_ROW, DOWN_KEY};
+use ratatui::style::Modifier;
+
+#[test]
+fn error_should_sign_its_line() {
+    let (app, handler) = app_with_diagnostics();
+
+    let terminal = draw(&app, &handler);
+
+    assert_eq!(terminal.backend().buffer().get(0, CURSOR_ROW).symbol(), "●");
+}
+
+#[test]
+fn range_should_be_underlined() {
+    let (app, handler) = app_with_diagnostics();
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let underlined = (0..buffer.area.width)
+        .filter(|x| {
+            buffer
+                .get(*x, CURSOR_ROW + 1)
+                .modifier
+                .contains(Modifier::UNDERLINED)
+        })
+        .count();
+    assert_eq!(underlined, 4);
+}
+
+#[test]
+fn message_should_follow_line_end() {
+    let (app, handler) = app_with_diagnostics();
+
+    let terminal = draw(&app, &handler);
+
+    assert!(row(&terminal, CURSOR_ROW + 2).contains("  ● blank line"));
+}
+
+#[test]
+fn message_bar_should_show_diagnostic_under_cursor() {
+    let (app, handler) = app_with_diagnostics();
+
+    let terminal = draw(&app, &handler);
+
+    assert!(row(&terminal, 15).contains("check: bad start"));
+}
+
+#[test]
+fn message_bar_should_not_show_diagnostic_off_cursor() {
+    let (mut app, mut handler) = app_with_diagnostics();
+    handler.handle_key_event(DOWN_KEY, &mut app);
+
+    let terminal = draw(&app, &handler);
+
+    assert!(!row(&terminal, 15).contains("odd word"));
+}
@@ -0,0 +1,97 @@
+use crate::helpers::{app_default, DOWN_KEY};
+use crossterm::event::{Event as TerminalEvent, KeyEvent, KeyEventKind};
+use editor::{
+    app::{App, Notification, NotificationType},
+    buffer::Buffer,
+    event::{Event, EventBus},
+    swap::SwapDir,
+};
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+#[test]
+fn listen_should_handle_every_event_queued() {
+    let (mut app, mut handler) = app_default();
+    let bus = EventBus::new();
+    bus.send(Event::Terminal(TerminalEvent::Key(DOWN_KEY)));
+    bus.send(Event::Terminal(TerminalEvent::Key(DOWN_KEY)));
+
+    let redraw = handler.listen(&mut app, &bus).unwrap();
+
+    assert!(redraw);
+    assert_eq!(app.buffer.cursor, (0, 2).into());
+}
+
+#[test]
+fn key_releases_should_not_redraw() {
+    let (mut app, mut handler) = app_default();
+    let bus = EventBus::new();
+    let release = KeyEvent {
+        kind: KeyEventKind::Release,
+        ..DOWN_KEY
+    };
+    bus.send(Event::Terminal(TerminalEvent::Key(release)));
+
+    let redraw = handler.listen(&mut app, &bus).unwrap();
+
+    assert!(!redraw);
+    assert_eq!(app.buffer.cursor, (0, 0).into());
+}
+
+#[test]
+fn listen_should_wake_when_the_notification_times_out() {
+    let (mut app, mut handler) = app_default();
+    let mut config = app.config().clone();
+    config.notifications.timeout = 20;
+    app.configure(config).unwrap();
+    app.push_notif(Notification::new(NotificationType::Info, "saved"));
+
+    let start = Instant::now();
+    let redraw = handler.listen(&mut app, &EventBus::new()).unwrap();
+
+    assert!(redraw);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert!(app.current_notif().is_none());
+}
+
+#[test]
+fn workers_should_wake_the_loop_with_what_they_found() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+    let path = dir.path().join("main.rs").to_string_lossy().into();
+    let mut app = App::new(Buffer::from_file(path).unwrap());
+    let mut handler = editor::handler::Handler::new();
+    let bus = EventBus::new();
+    app.set_waker(bus.waker());
+
+    app.open_picker(dir.path());
+    let start = Instant::now();
+    while app.picker().is_some_and(|picker| picker.walking()) {
+        assert!(start.elapsed() < Duration::from_secs(5), "walk timed out");
+        handler.listen(&mut app, &bus).unwrap();
+    }
+
+    assert_eq!(app.picker().unwrap().matches().count(), 1);
+}
+
+#[test]
+fn ticks_should_write_the_swap_files_due() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+    let path = dir.path().join("main.rs");
+    let mut app =
+        App::new(Buffer::from_file(path.to_string_lossy().into()).unwrap());
+    let mut handler = editor::handler::Handler::new();
+    let swap = SwapDir::new(dir.path().join("swap"));
+    app.set_swap_dir(Some(swap.clone()));
+    app.buffer.insert(0, "x").unwrap();
+    let bus = EventBus::new();
+
+    bus.send(Event::Tick);
+    handler.listen(&mut app, &bus).unwrap();
+
+    assert_eq!(swap.find(&path).unwrap().unwrap(), "xfn main() {}\n");
+}
@@ -0,0 +1,53 @@
+use super::sized_app;
+use crate::helpers::I_KEY;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use editor::{app::App, buffer::Buffer, config::Config, handler::Handler};
+use std::fs;
+use tempfile::TempDir;
+
+fn edited_file(auto_save: bool) -> (TempDir, App, Handler) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("file.txt");
+    fs::write(&path, "text\n").unwrap();
+    let mut app =
+        App::new(Buffer::from_file(path.to_string_lossy().into()).unwrap());
+    let (config, _) = Config::from_toml(&format!("auto_save = {}", auto_save));
+    app.configure(config).unwrap();
+    let mut handler = Handler::new();
+    handler.handle_key_event(I_KEY, &mut app);
+    let x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE);
+    handler.handle_key_event(x, &mut app);
+
+    (dir, app, handler)
+}
+
+#[test]
+fn resize_should_keep_the_new_size() {
+    let (mut app, mut handler) = sized_app();
+
+    handler.handle_event(Event::Resize(80, 24), &mut app);
+
+    assert_eq!(app.size(), (80, 24));
+}
+
+#[test]
+fn focus_lost_should_save_with_auto_save() {
+    let (dir, mut app, mut handler) = edited_file(true);
+
+    handler.handle_event(Event::FocusLost, &mut app);
+
+    let contents = fs::read_to_string(dir.path().join("file.txt")).unwrap();
+    assert_eq!(contents, "xtext\n");
+    assert!(!app.buffer.is_modified());
+}
+
+#[test]
+fn focus_lost_should_not_save_without_auto_save() {
+    let (dir, mut app, mut handler) = edited_file(false);
+
+    handler.handle_event(Event::FocusLost, &mut app);
+
+    let contents = fs::read_to_string(dir.path().join("file.txt")).unwrap();
+    assert_eq!(contents, "text\n");
+    assert!(app.buffer.is_modified());
+}
@@ -0,0 +1,42 @@
+use crate::helpers::{app_default, draw};
+use crossterm::event::{Event, KeyModifiers, MouseEvent, MouseEventKind};
+use editor::{app::App, handler::Handler};
+
+mod bus;
+mod focus;
+mod mouse;
+
+/// The default app, sized like the test terminal.
+fn sized_app() -> (App, Handler) {
+    let (mut app, mut handler) = app_default();
+    handler.handle_event(Event::Resize(40, 16), &mut app);
+
+    (app, handler)
+}
+
+fn mouse(
+    kind: MouseEventKind,
+    column: u16,
+    row: u16,
+    app: &mut App,
+    handler: &mut Handler,
+) {
+    let event = MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    };
+    handler.handle_event(Event::Mouse(event), app);
+}
+
+/// Column the text starts at, right of the gutter.
+fn text_column(app: &App, handler: &Handler) -> u16 {
+    let terminal = draw(app, handler);
+    let buffer = terminal.backend().buffer();
+    let row = crate::helpers::CURSOR_ROW;
+
+    (0..40)
+        .find(|x| buffer.get(*x, row).symbol() == "P")
+        .expect("the first line starts with P")
+}
@@ -0,0 +1,163 @@
+use super::{mouse, sized_app, text_column};
+use crate::helpers::{draw, CURSOR_ROW};
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind,
+};
+use editor::app::Mode;
+
+const DOWN: MouseEventKind = MouseEventKind::Down(MouseButton::Left);
+const DRAG: MouseEventKind = MouseEventKind::Drag(MouseButton::Left);
+
+#[test]
+fn click_should_move_the_cursor_to_the_char_under_it() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+
+    mouse(DOWN, x + 3, CURSOR_ROW + 1, &mut app, &mut handler);
+
+    assert_eq!(app.buffer.cursor, (3, 1).into());
+}
+
+#[test]
+fn click_past_the_end_of_a_line_should_stop_on_its_last_char() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+
+    mouse(DOWN, x + 30, CURSOR_ROW + 6, &mut app, &mut handler);
+
+    // "l!c7tudin."
+    assert_eq!(app.buffer.cursor, (9, 6).into());
+}
+
+#[test]
+fn click_outside_the_text_should_leave_the_cursor() {
+    let (mut app, mut handler) = sized_app();
+
+    // The git summary, above the first line.
+    mouse(DOWN, 10, CURSOR_ROW - 1, &mut app, &mut handler);
+
+    assert_eq!(app.buffer.cursor, (0, 0).into());
+}
+
+#[test]
+fn click_in_another_window_should_focus_it() {
+    let (mut app, mut handler) = sized_app();
+    let ctrl_w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+    handler.handle_key_event(ctrl_w, &mut app);
+    let v = KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE);
+    handler.handle_key_event(v, &mut app);
+    assert_eq!(app.windows().focused(), 1);
+
+    // The bars below the windows take a row, moving the cursor line up.
+    mouse(DOWN, 8, CURSOR_ROW, &mut app, &mut handler);
+
+    assert_eq!(app.windows().focused(), 0);
+    assert_eq!(app.buffer.cursor.y, 1);
+}
+
+#[test]
+fn drag_should_select_in_visual_mode() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+
+    mouse(DOWN, x + 2, CURSOR_ROW, &mut app, &mut handler);
+    mouse(DRAG, x + 4, CURSOR_ROW + 1, &mut app, &mut handler);
+
+    assert_eq!(app.mode(), Mode::Visual);
+    let selection = app.selection().unwrap();
+    assert_eq!((selection.start.line, selection.start.col), (0, 2));
+    assert_eq!((selection.end.line, selection.end.col), (1, 4));
+}
+
+#[test]
+fn drag_back_past_the_start_should_select_backwards() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+    mouse(DOWN, x, CURSOR_ROW + 1, &mut app, &mut handler);
+
+    // The click moved line 1 to the cursor row.
+    mouse(DRAG, x + 5, CURSOR_ROW - 1, &mut app, &mut handler);
+
+    let selection = app.selection().unwrap();
+    assert_eq!((selection.start.line, selection.start.col), (0, 5));
+    assert_eq!((selection.end.line, selection.end.col), (1, 0));
+}
+
+#[test]
+fn click_without_drag_should_stay_in_normal_mode() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+
+    mouse(DOWN, x + 2, CURSOR_ROW, &mut app, &mut handler);
+    mouse(DRAG, x + 2, CURSOR_ROW, &mut app, &mut handler);
+
+    assert_eq!(app.mode(), Mode::Normal);
+    assert_eq!(app.selection(), None);
+}
+
+#[test]
+fn click_should_leave_visual_mode() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+    mouse(DOWN, x, CURSOR_ROW, &mut app, &mut handler);
+    mouse(DRAG, x + 3, CURSOR_ROW, &mut app, &mut handler);
+
+    mouse(DOWN, x, CURSOR_ROW, &mut app, &mut handler);
+
+    assert_eq!(app.mode(), Mode::Normal);
+    assert_eq!(app.selection(), None);
+}
+
+#[test]
+fn selection_should_be_highlighted() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+
+    mouse(DOWN, x + 1, CURSOR_ROW, &mut app, &mut handler);
+    mouse(DRAG, x + 3, CURSOR_ROW, &mut app, &mut handler);
+
+    let terminal = draw(&app, &handler);
+    let buffer = terminal.backend().buffer();
+    let selected = app.theme().selection().bg.unwrap();
+    assert_ne!(buffer.get(x, CURSOR_ROW).bg, selected);
+    assert_eq!(buffer.get(x + 1, CURSOR_ROW).bg, selected);
+    assert_eq!(buffer.get(x + 2, CURSOR_ROW).bg, selected);
+    assert_ne!(buffer.get(x + 4, CURSOR_ROW).bg, selected);
+}
+
+#[test]
+fn d_should_delete_the_selection() {
+    let (mut app, mut handler) = sized_app();
+    let x = text_column(&app, &handler);
+    mouse(DOWN, x, CURSOR_ROW, &mut app, &mut handler);
+    mouse(DRAG, x + 3, CURSOR_ROW, &mut app, &mut handler);
+
+    let d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE);
+    handler.handle_key_event(d, &mut app);
+
+    assert_eq!(app.mode(), Mode::Normal);
+    let line = app.buffer.line(0).unwrap().to_string();
+    assert!(line.starts_with("7i¢or l¢"));
+}
+
+#[test]
+fn wheel_should_scroll_the_cursor_along() {
+    let (mut app, mut handler) = sized_app();
+
+    mouse(MouseEventKind::ScrollDown, 10, 3, &mut app, &mut handler);
+    assert_eq!(app.buffer.cursor.y, 3);
+
+    mouse(MouseEventKind::ScrollUp, 10, 3, &mut app, &mut handler);
+    assert_eq!(app.buffer.cursor.y, 0);
+}
+
+#[test]
+fn wheel_should_stop_at_the_last_line() {
+    let (mut app, mut handler) = sized_app();
+
+    for _ in 0..5 {
+        mouse(MouseEventKind::ScrollDown, 10, 3, &mut app, &mut handler);
+    }
+
+    assert_eq!(app.buffer.cursor.y, app.buffer.len_lines() - 1);
+}
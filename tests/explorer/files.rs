@@ -0,0 +1,61 @@
+use super::{names, project, selected};
+use crate::helpers::{type_text, BACKSPACE_KEY, ENTER_KEY};
+
+#[test]
+fn a_should_create_a_file_next_to_the_selection() {
+    let (dir, mut app, mut handler) = project();
+
+    type_text("anew.txt", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(dir.path().join("new.txt").is_file());
+    assert_eq!(selected(&app), "new.txt");
+}
+
+#[test]
+fn trailing_slash_should_create_a_dir_in_the_selected_dir() {
+    let (dir, mut app, mut handler) = project();
+    type_text("kk", &mut app, &mut handler);
+
+    type_text("adrafts/", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(dir.path().join("docs/drafts").is_dir());
+    assert_eq!(
+        names(&app),
+        ["docs", "  drafts", "  guide.md", "src", "file.txt"]
+    );
+}
+
+#[test]
+fn r_should_rename_and_move_the_open_buffer() {
+    let (dir, mut app, mut handler) = project();
+
+    type_text("r", &mut app, &mut handler);
+    assert_eq!(app.prompt().unwrap().input, "file.txt");
+    for _ in 0.."txt".len() {
+        handler.handle_key_event(BACKSPACE_KEY, &mut app);
+    }
+    type_text("md", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(!dir.path().join("file.txt").exists());
+    assert!(dir.path().join("file.md").is_file());
+    assert!(app.buffer.file_name().ends_with("file.md"));
+    assert_eq!(selected(&app), "file.md");
+}
+
+#[test]
+fn d_should_delete_once_confirmed() {
+    let (dir, mut app, mut handler) = project();
+    type_text("kk", &mut app, &mut handler);
+
+    type_text("dn", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+    assert!(dir.path().join("docs").exists());
+
+    type_text("dy", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+    assert!(!dir.path().join("docs").exists());
+    assert_eq!(names(&app), ["src", "file.txt"]);
+}
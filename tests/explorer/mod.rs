@@ -0,0 +1,34 @@
+use crate::helpers;
+use editor::{app::App, handler::Handler};
+use tempfile::TempDir;
+
+mod files;
+mod render;
+mod tree;
+
+/// A project with `file.txt` open and the explorer focused on it.
+fn project() -> (TempDir, App, Handler) {
+    let (dir, mut app, handler) = helpers::project(&[
+        ("file.txt", "text\n"),
+        ("src/main.rs", "fn main() {}\n"),
+        ("src/lib.rs", "\n"),
+        ("docs/guide.md", "# guide\n"),
+    ]);
+    app.toggle_explorer().unwrap();
+
+    (dir, app, handler)
+}
+
+/// The tree as it is drawn, without the glyphs.
+fn names(app: &App) -> Vec<String> {
+    app.explorer()
+        .unwrap()
+        .entries()
+        .iter()
+        .map(|entry| format!("{}{}", "  ".repeat(entry.depth), entry.name()))
+        .collect()
+}
+
+fn selected(app: &App) -> String {
+    app.explorer().unwrap().selected().unwrap().name()
+}
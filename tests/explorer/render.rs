@@ -0,0 +1,44 @@
+use super::project;
+use crate::helpers::draw;
+use editor::theme::GitElement;
+use std::process::Command;
+
+#[test]
+fn tree_should_be_drawn_left_of_the_text() {
+    let (_dir, app, handler) = project();
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let row =
+        |y| -> String { (0..13).map(|x| buffer.get(x, y).symbol()).collect() };
+    assert!(row(1).starts_with("▸ docs/"));
+    assert!(row(2).starts_with("▸ src/"));
+    assert!(row(3).starts_with("  file.txt"));
+    assert!(row(1).ends_with('│'));
+}
+
+#[test]
+fn entries_should_be_colored_by_git_status() {
+    let (dir, mut app, handler) = project();
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-b", "main"]);
+    git(&["add", "file.txt"]);
+    app.refresh_git().unwrap();
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let staged = app.theme().git(GitElement::Staged);
+    let untracked = app.theme().git(GitElement::Untracked);
+    let y = (0..16).find(|y| buffer.get(2, *y).symbol() == "f").unwrap();
+    assert_eq!(buffer.get(2, y).fg, staged.fg.unwrap());
+    assert_eq!(buffer.get(2, 1).fg, untracked.fg.unwrap());
+}
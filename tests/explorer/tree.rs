@@ -0,0 +1,62 @@
+use super::{names, project, selected};
+use crate::helpers::{type_text, ENTER_KEY, ESC_KEY};
+
+#[test]
+fn explorer_should_list_dirs_first_with_the_file_selected() {
+    let (_dir, app, _) = project();
+
+    assert_eq!(names(&app), ["docs", "src", "file.txt"]);
+    assert_eq!(selected(&app), "file.txt");
+    assert!(app.explorer_focused());
+}
+
+#[test]
+fn l_should_expand_and_h_collapse() {
+    let (_dir, mut app, mut handler) = project();
+    type_text("k", &mut app, &mut handler);
+
+    type_text("l", &mut app, &mut handler);
+    assert_eq!(
+        names(&app),
+        ["docs", "src", "  lib.rs", "  main.rs", "file.txt"]
+    );
+
+    type_text("jh", &mut app, &mut handler);
+    assert_eq!(names(&app), ["docs", "src", "file.txt"]);
+    assert_eq!(selected(&app), "src");
+}
+
+#[test]
+fn enter_on_a_file_should_open_it() {
+    let (_dir, mut app, mut handler) = project();
+    type_text("kljj", &mut app, &mut handler);
+
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(app.buffer.file_name().ends_with("main.rs"));
+    assert!(app.explorer().is_some());
+    assert!(!app.explorer_focused());
+}
+
+#[test]
+fn esc_should_give_keys_back_to_the_buffer() {
+    let (_dir, mut app, mut handler) = project();
+
+    handler.handle_key_event(ESC_KEY, &mut app);
+    type_text("j", &mut app, &mut handler);
+
+    assert!(!app.explorer_focused());
+    assert_eq!(selected(&app), "file.txt");
+}
+
+#[test]
+fn toggle_should_focus_then_close() {
+    let (_dir, mut app, _) = project();
+    app.unfocus_explorer();
+
+    app.toggle_explorer().unwrap();
+    assert!(app.explorer_focused());
+
+    app.toggle_explorer().unwrap();
+    assert!(app.explorer().is_none());
+}
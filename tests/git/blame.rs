@@ -0,0 +1,151 @@
+use super::{app_in, TempRepo};
+use crate::helpers::{draw, wait_while, CURSOR_ROW, DOWN_KEY, SPACE_KEY};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use editor::{app::App, git::Repo, handler::Handler};
+
+const G_KEY: KeyEvent = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+const B_KEY: KeyEvent = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE);
+
+/// A repository where the second line was changed by another author.
+fn repo_with_two_authors() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.write("file.txt", "first line\nsecond line changed\n");
+    repo.git(&[
+        "-c",
+        "user.name=Other",
+        "commit",
+        "-am",
+        "Change second line",
+    ]);
+
+    repo
+}
+
+fn toggle_blame(app: &mut App, handler: &mut Handler) {
+    for key in [SPACE_KEY, G_KEY, B_KEY] {
+        handler.handle_key_event(key, app);
+    }
+}
+
+fn wait_for_blame(app: &mut App) {
+    wait_while(app, App::blame_loading, App::update_blame);
+}
+
+fn row(
+    terminal: &ratatui::Terminal<ratatui::backend::TestBackend>,
+    y: u16,
+) -> String {
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.width)
+        .map(|x| buffer.get(x, y).symbol())
+        .collect()
+}
+
+#[test]
+fn blame_should_find_commit_of_each_line() {
+    let repo = repo_with_two_authors();
+    let path = repo.path("file.txt");
+
+    let blame = Repo::discover(&path)
+        .unwrap()
+        .blame(&path, "first line\nsecond line changed\n")
+        .unwrap();
+
+    let first = blame.commit(0).unwrap();
+    let second = blame.commit(1).unwrap();
+    assert_eq!(blame.len_lines(), 2);
+    assert_eq!(&*first.author, "Test");
+    assert_eq!(&*first.summary, "Add file");
+    assert_eq!(&*second.author, "Other");
+    assert_eq!(&*second.summary, "Change second line");
+    assert!(first.is_committed());
+}
+
+#[test]
+fn unsaved_lines_should_not_be_committed() {
+    let repo = TempRepo::new();
+    let path = repo.path("file.txt");
+
+    let blame = Repo::discover(&path)
+        .unwrap()
+        .blame(&path, "first line\nnew line\nsecond line\n")
+        .unwrap();
+
+    assert!(blame.commit(0).unwrap().is_committed());
+    assert!(!blame.commit(1).unwrap().is_committed());
+    assert!(blame.commit(2).unwrap().is_committed());
+}
+
+#[test]
+fn blame_should_load_in_background() {
+    let repo = TempRepo::new();
+    let (mut app, mut handler) = app_in(&repo);
+
+    toggle_blame(&mut app, &mut handler);
+
+    assert!(app.blame_loading());
+    assert!(app.blame().is_none());
+    wait_for_blame(&mut app);
+    assert!(app.blame().is_some());
+}
+
+#[test]
+fn blame_should_annotate_lines() {
+    let repo = repo_with_two_authors();
+    let (mut app, mut handler) = app_in(&repo);
+    let hash = repo.git(&["rev-parse", "--short=7", "HEAD"]);
+
+    toggle_blame(&mut app, &mut handler);
+    wait_for_blame(&mut app);
+    let terminal = draw(&app, &handler);
+
+    let first_line = row(&terminal, CURSOR_ROW);
+    let second_line = row(&terminal, CURSOR_ROW + 1);
+    assert!(first_line.contains("Test"));
+    assert!(first_line.contains(" now "));
+    assert!(second_line.starts_with(&format!("{} Other", hash.trim())));
+}
+
+#[test]
+fn message_bar_should_show_summary_of_cursor_line() {
+    let repo = repo_with_two_authors();
+    let (mut app, mut handler) = app_in(&repo);
+
+    toggle_blame(&mut app, &mut handler);
+    wait_for_blame(&mut app);
+    handler.handle_key_event(DOWN_KEY, &mut app);
+    let terminal = draw(&app, &handler);
+
+    let message_bar =
+        row(&terminal, terminal.backend().buffer().area.height - 1);
+    assert!(message_bar.contains("Other: Change second"));
+}
+
+#[test]
+fn toggling_again_should_turn_blame_off() {
+    let repo = TempRepo::new();
+    let (mut app, mut handler) = app_in(&repo);
+    toggle_blame(&mut app, &mut handler);
+    wait_for_blame(&mut app);
+
+    toggle_blame(&mut app, &mut handler);
+
+    assert!(app.blame().is_none());
+    assert!(!app.blame_loading());
+}
+
+#[test]
+fn untracked_file_should_report_error() {
+    let repo = TempRepo::new();
+    repo.write("new.txt", "text\n");
+    let path = repo.path("new.txt").to_string_lossy().into_owned();
+    let mut app = App::new(editor::buffer::Buffer::from_file(path).unwrap());
+    let mut handler = Handler::new();
+
+    toggle_blame(&mut app, &mut handler);
+    wait_for_blame(&mut app);
+
+    assert!(app.blame().is_none());
+    let most_recent_notif = app.notifs().last().unwrap().to_string();
+    assert!(most_recent_notif.contains("Git error"));
+}
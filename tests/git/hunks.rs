@@ -0,0 +1,213 @@
+use super::{app_in, TempRepo};
+use crate::helpers::{
+    draw, key, press, A_KEY, CAP_G_KEY, DOWN_KEY, D_KEY, ESC_KEY, I_KEY,
+    SPACE_KEY,
+};
+use editor::{
+    app::App,
+    buffer::Buffer,
+    diff::{Diff, Hunk},
+    sign::SignKind,
+};
+use ropey::Rope;
+
+const BASE: &str = "one\ntwo\nthree\nfour\nfive\nsix\n";
+
+fn text(app: &App) -> String {
+    (0..app.buffer.len_lines())
+        .map(|idx| app.buffer.line(idx).unwrap().to_string())
+        .collect()
+}
+
+fn hunks(app: &App) -> Vec<Hunk> {
+    app.buffer.diff().unwrap().hunks().to_vec()
+}
+
+#[test]
+fn unchanged_file_should_have_no_hunks() {
+    let repo = TempRepo::with_file(BASE);
+    let (app, _) = app_in(&repo);
+
+    assert!(hunks(&app).is_empty())
+}
+
+#[test]
+fn uncommitted_file_should_be_all_added_lines() {
+    let repo = TempRepo::with_file(BASE);
+    repo.write("new.txt", "a\nb\n");
+    let path = repo.path("new.txt").to_string_lossy().into_owned();
+    let mut app = App::new(Buffer::from_file(path).unwrap());
+
+    app.refresh_git().unwrap();
+
+    assert_eq!(
+        hunks(&app),
+        [Hunk {
+            before: 0..0,
+            after: 0..2
+        }]
+    );
+    assert_eq!(app.sign_column().sign(0), Some(SignKind::GitAdded));
+    assert_eq!(app.sign_column().sign(1), Some(SignKind::GitAdded))
+}
+
+#[test]
+fn empty_file_should_have_no_hunks() {
+    let repo = TempRepo::with_file("");
+    let (app, _) = app_in(&repo);
+
+    assert!(hunks(&app).is_empty())
+}
+
+#[test]
+fn edited_line_should_be_marked_changed() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+
+    press(&[DOWN_KEY, I_KEY, A_KEY, ESC_KEY], &mut app, &mut handler);
+
+    assert_eq!(
+        hunks(&app),
+        [Hunk {
+            before: 1..2,
+            after: 1..2
+        }]
+    );
+    assert_eq!(app.sign_column().sign(1), Some(SignKind::GitChanged))
+}
+
+#[test]
+fn new_line_should_be_marked_added() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, _) = app_in(&repo);
+
+    app.buffer.insert(0, "zero\n").unwrap();
+
+    assert_eq!(
+        hunks(&app),
+        [Hunk {
+            before: 0..0,
+            after: 0..1
+        }]
+    );
+    assert_eq!(app.sign_column().sign(0), Some(SignKind::GitAdded))
+}
+
+#[test]
+fn deleted_line_should_be_marked_removed_above() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, _) = app_in(&repo);
+
+    app.buffer.delete_line(2).unwrap();
+
+    assert_eq!(
+        hunks(&app),
+        [Hunk {
+            before: 2..3,
+            after: 2..2
+        }]
+    );
+    assert_eq!(app.sign_column().sign(1), Some(SignKind::GitRemoved))
+}
+
+#[test]
+fn incremental_diff_should_match_full_diff() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+
+    app.buffer.insert(0, "zero\n").unwrap();
+    press(&[DOWN_KEY, DOWN_KEY, D_KEY, D_KEY], &mut app, &mut handler);
+    app.buffer
+        .insert(app.buffer.len(), "\nseven\neight")
+        .unwrap();
+    press(&[DOWN_KEY, I_KEY, A_KEY, ESC_KEY], &mut app, &mut handler);
+    app.buffer.remove(0..5).unwrap();
+
+    let full = Diff::new(BASE, &Rope::from_str(&text(&app)));
+    assert_eq!(hunks(&app), full.hunks())
+}
+
+#[test]
+fn next_and_prev_should_jump_between_hunks() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+    app.buffer.insert(0, "zero\n").unwrap();
+    app.buffer.insert(app.buffer.len(), "\nseven").unwrap();
+
+    press(&[key(']'), key('c')], &mut app, &mut handler);
+    assert_eq!(app.buffer.cursor.y, 7);
+
+    press(&[key('['), key('c')], &mut app, &mut handler);
+    assert_eq!(app.buffer.cursor.y, 0);
+}
+
+#[test]
+fn no_hunk_below_should_warn() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+
+    press(&[key(']'), key('c')], &mut app, &mut handler);
+
+    let most_recent_notif = app.notifs().last().unwrap().to_string();
+    assert!(most_recent_notif.ends_with("no more hunks"))
+}
+
+#[test]
+fn revert_should_restore_head_lines() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+    press(&[DOWN_KEY, D_KEY, D_KEY], &mut app, &mut handler);
+    press(&[CAP_G_KEY, I_KEY, A_KEY, ESC_KEY], &mut app, &mut handler);
+
+    press(&[SPACE_KEY, key('h'), key('r')], &mut app, &mut handler);
+    press(&[key('['), key('c')], &mut app, &mut handler);
+    press(&[SPACE_KEY, key('h'), key('r')], &mut app, &mut handler);
+
+    assert_eq!(text(&app), BASE.trim_end());
+    assert!(hunks(&app).is_empty())
+}
+
+#[test]
+fn revert_should_remove_added_lines_at_end() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+    app.buffer.insert(app.buffer.len(), "\nseven").unwrap();
+    press(&[CAP_G_KEY], &mut app, &mut handler);
+
+    app.revert_hunk().unwrap();
+
+    assert_eq!(text(&app), BASE.trim_end())
+}
+
+#[test]
+fn stage_should_add_hunk_to_index() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+    press(&[DOWN_KEY, I_KEY, A_KEY, ESC_KEY], &mut app, &mut handler);
+    app.buffer.insert(app.buffer.len(), "\nseven").unwrap();
+
+    press(&[SPACE_KEY, key('h'), key('s')], &mut app, &mut handler);
+
+    let staged = repo.git(&["diff", "--cached", "--unified=0"]);
+    assert!(staged.contains("-two\n+atwo\n"));
+    assert!(!staged.contains("seven"));
+    assert_eq!(app.git().unwrap().status.staged, 1)
+}
+
+#[test]
+fn preview_should_show_head_lines() {
+    let repo = TempRepo::with_file(BASE);
+    let (mut app, mut handler) = app_in(&repo);
+    press(&[I_KEY, A_KEY, ESC_KEY], &mut app, &mut handler);
+
+    press(&[SPACE_KEY, key('h'), key('p')], &mut app, &mut handler);
+
+    let terminal = draw(&app, &handler);
+    let buffer = terminal.backend().buffer();
+    let screen: String = (0..buffer.area.height)
+        .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+        .map(|(x, y)| buffer.get(x, y).symbol())
+        .collect();
+    assert!(screen.contains("HEAD"));
+    assert!(screen.contains("-one"))
+}
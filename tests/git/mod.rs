@@ -0,0 +1,70 @@
+use editor::{app::App, buffer::Buffer, handler::Handler};
+use std::{fs, path::PathBuf, process::Command};
+use tempfile::TempDir;
+
+mod blame;
+mod hunks;
+mod save;
+mod status;
+
+/// App editing `file.txt` of the repository.
+fn app_in(repo: &TempRepo) -> (App, Handler) {
+    let path = repo.path("file.txt").to_string_lossy().into_owned();
+    let mut app = App::new(Buffer::from_file(path).unwrap());
+    app.refresh_git().unwrap();
+
+    (app, Handler::new())
+}
+
+/// A throwaway repository with one committed file, `file.txt`.
+struct TempRepo {
+    dir: TempDir,
+}
+
+impl TempRepo {
+    fn new() -> Self {
+        Self::with_file("first line\nsecond line\n")
+    }
+
+    /// A repository with `file.txt` committed with the given contents.
+    fn with_file(contents: &str) -> Self {
+        let repo = Self::empty();
+        repo.write("file.txt", contents);
+        repo.git(&["add", "file.txt"]);
+        repo.git(&["commit", "-m", "Add file"]);
+
+        repo
+    }
+
+    /// A repository without any commits.
+    fn empty() -> Self {
+        let repo = Self {
+            dir: TempDir::new().unwrap(),
+        };
+        repo.git(&["init", "-b", "main"]);
+        repo.git(&["config", "user.name", "Test"]);
+        repo.git(&["config", "user.email", "test@example.com"]);
+        repo.git(&["config", "commit.gpgsign", "false"]);
+
+        repo
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    fn write(&self, name: &str, contents: &str) {
+        fs::write(self.path(name), contents).unwrap();
+    }
+
+    fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(self.dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+
+        String::from_utf8(output.stdout).unwrap()
+    }
+}
@@ -0,0 +1,48 @@
+use super::{app_in, TempRepo};
+use crate::helpers::{draw, A_KEY, CURSOR_ROW, ESC_KEY, I_KEY};
+use std::fs;
+
+#[test]
+fn save_should_write_file_unchanged() {
+    let repo = TempRepo::new();
+    let (mut app, _) = app_in(&repo);
+
+    app.save().unwrap();
+
+    assert_eq!(
+        fs::read_to_string(repo.path("file.txt")).unwrap(),
+        "first line\nsecond line\n"
+    )
+}
+
+#[test]
+fn save_should_refresh_git_summary() {
+    let repo = TempRepo::new();
+    let (mut app, mut handler) = app_in(&repo);
+    assert_eq!(app.git().unwrap().status.unstaged, 0);
+
+    handler.handle_key_event(I_KEY, &mut app);
+    handler.handle_key_event(A_KEY, &mut app);
+    handler.handle_key_event(ESC_KEY, &mut app);
+    app.save().unwrap();
+
+    assert_eq!(app.git().unwrap().status.unstaged, 1);
+    assert!(fs::read_to_string(repo.path("file.txt"))
+        .unwrap()
+        .starts_with("afirst line"))
+}
+
+#[test]
+fn summary_should_show_branch_above_first_line() {
+    let repo = TempRepo::new();
+    let (app, handler) = app_in(&repo);
+
+    let terminal = draw(&app, &handler);
+    let buffer = terminal.backend().buffer();
+
+    let row_above_text = CURSOR_ROW - 1;
+    let line: String = (0..buffer.area.width)
+        .map(|x| buffer.get(x, row_above_text).symbol())
+        .collect();
+    assert!(line.starts_with(" main"))
+}
@@ -0,0 +1,138 @@
+use super::TempRepo;
+use editor::git::{self, Repo};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn clean_repo_should_have_no_changes() {
+    let repo = TempRepo::new();
+
+    let summary = Repo::discover(repo.path("file.txt"))
+        .unwrap()
+        .summary(10)
+        .unwrap();
+
+    assert_eq!(summary.status.branch.as_deref(), Some("main"));
+    assert_eq!(summary.status.staged, 0);
+    assert_eq!(summary.status.unstaged, 0);
+    assert_eq!(summary.status.untracked, 0);
+}
+
+#[test]
+fn log_should_list_newest_commit_first() {
+    let repo = TempRepo::new();
+    repo.write("file.txt", "changed\n");
+    repo.git(&["commit", "-am", "Change file"]);
+
+    let log = Repo::discover(repo.path("file.txt"))
+        .unwrap()
+        .log(10)
+        .unwrap();
+
+    let subjects: Vec<&str> =
+        log.iter().map(|commit| &*commit.subject).collect();
+    assert_eq!(subjects, ["Change file", "Add file"])
+}
+
+#[test]
+fn changes_should_be_counted() {
+    let repo = TempRepo::new();
+    repo.write("file.txt", "changed\n");
+    repo.write("staged.txt", "new\n");
+    repo.git(&["add", "staged.txt"]);
+    repo.write("untracked.txt", "new\n");
+
+    let status = Repo::discover(repo.path("file.txt"))
+        .unwrap()
+        .status()
+        .unwrap();
+
+    assert_eq!(status.staged, 1);
+    assert_eq!(status.unstaged, 1);
+    assert_eq!(status.untracked, 1);
+}
+
+#[test]
+fn branch_ahead_of_upstream_should_be_counted() {
+    let repo = TempRepo::new();
+    repo.git(&["checkout", "-b", "feature", "--track", "main"]);
+    repo.write("file.txt", "changed\n");
+    repo.git(&["commit", "-am", "Change file"]);
+
+    let status = Repo::discover(repo.path("file.txt"))
+        .unwrap()
+        .status()
+        .unwrap();
+
+    assert_eq!(status.upstream.as_deref(), Some("main"));
+    assert_eq!(status.ahead, 1);
+    assert_eq!(status.behind, 0);
+}
+
+#[test]
+fn detached_head_should_have_no_branch() {
+    let repo = TempRepo::new();
+    repo.git(&["checkout", "--detach"]);
+
+    let status = Repo::discover(repo.path("file.txt"))
+        .unwrap()
+        .status()
+        .unwrap();
+
+    assert_eq!(status.branch, None)
+}
+
+#[test]
+fn repo_without_commits_should_have_empty_log() {
+    let repo = TempRepo::empty();
+    repo.write("file.txt", "first line\n");
+
+    let summary = Repo::discover(repo.path("file.txt"))
+        .unwrap()
+        .summary(10)
+        .unwrap();
+
+    assert!(summary.log.is_empty());
+    assert_eq!(summary.status.untracked, 1);
+}
+
+#[test]
+fn dir_outside_repo_should_not_be_found() {
+    let dir = TempDir::new().unwrap();
+
+    let res = Repo::discover(dir.path());
+
+    assert!(matches!(res, Err(git::Error::NotARepo(_))))
+}
+
+#[test]
+fn head_contents_should_be_none_for_uncommitted_files() {
+    let repo = TempRepo::empty();
+    repo.write("file.txt", "text\n");
+    let git = Repo::discover(repo.path("file.txt")).unwrap();
+
+    assert_eq!(git.head_contents(repo.path("file.txt")).unwrap(), None);
+
+    repo.git(&["add", "file.txt"]);
+    repo.git(&["commit", "-m", "Add file"]);
+    repo.write("new.txt", "text\n");
+
+    assert_eq!(git.head_contents(repo.path("new.txt")).unwrap(), None);
+    assert_eq!(
+        git.head_contents(repo.path("file.txt")).unwrap().as_deref(),
+        Some("text\n")
+    );
+}
+
+#[test]
+fn head_contents_should_fail_when_the_repo_is_broken() {
+    let repo = TempRepo::new();
+    let blob = repo.git(&["rev-parse", "HEAD:file.txt"]);
+    let (dir, name) = blob.trim().split_at(2);
+    fs::remove_file(repo.path(".git/objects").join(dir).join(name)).unwrap();
+    let git = Repo::discover(repo.path("file.txt")).unwrap();
+
+    let res = git.head_contents(repo.path("file.txt"));
+
+    assert!(matches!(res, Err(git::Error::Command { .. })))
+}
@@ -0,0 +1,62 @@
+use super::{grep, project};
+use crate::helpers::{draw, type_text, ENTER_KEY, ESC_KEY};
+use editor::app::PromptAction;
+
+#[test]
+fn grep_prompt_should_search_on_enter() {
+    let (_dir, mut app, mut handler) = project();
+    app.open_prompt(PromptAction::Grep, String::new());
+
+    type_text("zzz", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert_eq!(app.grep().unwrap().pattern(), "zzz");
+    assert!(app.grep_list());
+}
+
+#[test]
+fn enter_should_open_the_selected_match() {
+    let (dir, mut app, mut handler) = project();
+    grep(&dir, "needle", &mut app);
+
+    type_text("j", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(!app.grep_list());
+    assert!(app.buffer.file_name().ends_with("c.txt"));
+    assert_eq!(app.buffer.cursor, (2, 1).into());
+}
+
+#[test]
+fn esc_should_close_the_list_and_keep_the_results() {
+    let (dir, mut app, mut handler) = project();
+    grep(&dir, "needle", &mut app);
+
+    handler.handle_key_event(ESC_KEY, &mut app);
+
+    assert!(!app.grep_list());
+    assert_eq!(app.grep().unwrap().matches().len(), 2);
+}
+
+#[test]
+fn list_should_show_matches_in_place_of_logs() {
+    let (dir, mut app, handler) = project();
+    grep(&dir, "needle", &mut app);
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect()
+        })
+        .collect();
+    let title = rows
+        .iter()
+        .position(|row| row.starts_with(" grep needle (2 matches)"))
+        .unwrap();
+    assert!(rows[title + 1].starts_with(" a.txt:1:5 one needle"));
+    assert!(rows[title + 2].starts_with(" b/c.txt:2:3 needle two"));
+}
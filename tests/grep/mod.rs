@@ -0,0 +1,32 @@
+use crate::helpers::{self, wait_while};
+use editor::{app::App, handler::Handler};
+use tempfile::TempDir;
+
+mod list;
+mod search;
+mod step;
+
+/// A project with `needle` in two files and an ignored one, `a.txt` open.
+fn project() -> (TempDir, App, Handler) {
+    helpers::project(&[
+        ("a.txt", "one needle\nnothing\n"),
+        ("b/c.txt", "x\n  needle two\n"),
+        ("ignored.log", "needle\n"),
+        (".gitignore", "*.log\n"),
+    ])
+}
+
+/// Search the project for `pattern` and pick up every match.
+fn grep(dir: &TempDir, pattern: &str, app: &mut App) {
+    app.start_grep(pattern, dir.path()).unwrap();
+
+    wait_while(
+        app,
+        |app| app.grep().is_some_and(|grep| grep.searching()),
+        App::update_background,
+    );
+}
+
+fn last_notif(app: &App) -> String {
+    app.notifs().last().unwrap().to_string()
+}
@@ -0,0 +1,38 @@
+use super::{grep, project};
+
+#[test]
+fn grep_should_find_matches_in_every_file() {
+    let (dir, mut app, _) = project();
+
+    grep(&dir, "needle", &mut app);
+
+    let matches: Vec<_> = app
+        .grep()
+        .unwrap()
+        .matches()
+        .iter()
+        .map(|found| (&*found.path, found.line_idx, found.col))
+        .collect();
+    assert_eq!(matches, [("a.txt", 0, 4), ("b/c.txt", 1, 2)]);
+}
+
+#[test]
+fn grep_should_take_a_regex() {
+    let (dir, mut app, _) = project();
+
+    grep(&dir, "^no", &mut app);
+
+    let matches = app.grep().unwrap().matches();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(&*matches[0].text, "nothing");
+}
+
+#[test]
+fn invalid_pattern_should_be_an_error() {
+    let (dir, mut app, _) = project();
+
+    let result = app.start_grep("needle(", dir.path());
+
+    assert!(result.is_err());
+    assert!(app.grep().is_none());
+}
@@ -0,0 +1,77 @@
+use super::{grep, last_notif, project};
+use crate::helpers::{type_text, ENTER_KEY};
+
+#[test]
+fn cnext_should_visit_matches_in_order() {
+    let (dir, mut app, mut handler) = project();
+    grep(&dir, "needle", &mut app);
+    app.close_grep_list();
+
+    type_text("]q", &mut app, &mut handler);
+    assert!(app.buffer.file_name().ends_with("a.txt"));
+    assert_eq!(app.buffer.cursor, (4, 0).into());
+
+    type_text("]q", &mut app, &mut handler);
+    assert!(app.buffer.file_name().ends_with("c.txt"));
+    assert_eq!(app.buffer.cursor, (2, 1).into());
+}
+
+#[test]
+fn cprev_should_go_back() {
+    let (dir, mut app, mut handler) = project();
+    grep(&dir, "needle", &mut app);
+    app.close_grep_list();
+    type_text("]q]q", &mut app, &mut handler);
+
+    type_text("[q", &mut app, &mut handler);
+
+    assert!(app.buffer.file_name().ends_with("a.txt"));
+    assert_eq!(app.buffer.cursor, (4, 0).into());
+}
+
+#[test]
+fn cnext_past_the_last_match_should_warn() {
+    let (dir, mut app, mut handler) = project();
+    grep(&dir, "needle", &mut app);
+    app.close_grep_list();
+    type_text("]q]q", &mut app, &mut handler);
+
+    type_text("]q", &mut app, &mut handler);
+
+    assert!(app.buffer.file_name().ends_with("c.txt"));
+    assert!(last_notif(&app).ends_with("no more matches"));
+}
+
+#[test]
+fn cnext_should_run_from_the_command_line() {
+    let (dir, mut app, mut handler) = project();
+    grep(&dir, "needle", &mut app);
+    app.close_grep_list();
+
+    type_text(":cnext", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+    type_text(":cnext", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(app.prompt().is_none());
+    assert!(app.buffer.file_name().ends_with("c.txt"));
+}
+
+#[test]
+fn unknown_command_should_be_reported() {
+    let (_dir, mut app, mut handler) = project();
+
+    type_text(":nope", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(last_notif(&app).ends_with("no command named nope"));
+}
+
+#[test]
+fn cnext_without_search_should_warn() {
+    let (_dir, mut app, mut handler) = project();
+
+    type_text("]q", &mut app, &mut handler);
+
+    assert!(last_notif(&app).ends_with("nothing was searched for"));
+}
@@ -2,8 +2,14 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use editor::{
     app::{self, Mode},
     buffer::Buffer,
-    handler,
+    handler, ui,
 };
+use ratatui::{backend::TestBackend, Terminal};
+use std::{
+    fs, thread,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
 
 #[derive(Default)]
 pub enum MockFile {
@@ -11,14 +17,12 @@ pub enum MockFile {
     Basic,
     Empty,
     SingleLine,
-    Sparse,
 }
 
 fn init_app(file: MockFile) -> (app::App, handler::Handler) {
     let file_name: String = match file {
         MockFile::Basic => "tests/helpers/mock_files/basic.txt".into(),
         MockFile::Empty => "tests/helpers/mock_files/empty.txt".into(),
-        MockFile::Sparse => "tests/helpers/mock_files/sparse.txt".into(),
         MockFile::SingleLine => {
             "tests/helpers/mock_files/single_line.txt".into()
         }
@@ -71,15 +75,6 @@ pub fn app_default() -> (app::App, handler::Handler) {
 pub fn app_with_cursor_on_end_of_first_line() -> (app::App, handler::Handler) {
     AppBuilder::new_default().press_key(END_KEY).build()
 }
-pub fn app_with_cursor_near_end_of_first_line() -> (app::App, handler::Handler)
-{
-    AppBuilder::new_default()
-        .press_key(END_KEY)
-        .press_key(THREE_KEY)
-        .press_key(LEFT_KEY)
-        .build()
-}
-
 pub fn app_with_cursor_on_start_of_last_line() -> (app::App, handler::Handler) {
     AppBuilder::new_default()
         .press_key(CAP_G_KEY)
@@ -87,13 +82,6 @@ pub fn app_with_cursor_on_start_of_last_line() -> (app::App, handler::Handler) {
         .build()
 }
 
-pub fn app_with_cursor_on_end_of_file() -> (app::App, handler::Handler) {
-    AppBuilder::new_default()
-        .press_key(CAP_G_KEY)
-        .press_key(HOME_KEY)
-        .build()
-}
-
 pub fn app_with_cursor_on_rope_tail() -> (app::App, handler::Handler) {
     AppBuilder::new_default()
         .press_key(CAP_G_KEY)
@@ -108,13 +96,6 @@ pub fn app_with_cursor_on_newline_char() -> (app::App, handler::Handler) {
         .build()
 }
 
-pub fn app_with_cursor_on_empty_line() -> (app::App, handler::Handler) {
-    AppBuilder::new_default()
-        .press_key(DOWN_KEY)
-        .press_key(DOWN_KEY)
-        .build()
-}
-
 pub fn app_with_cursor_out_of_rope_bounds() -> (app::App, handler::Handler) {
     AppBuilder::new_default()
         .press_key(END_KEY)
@@ -153,12 +134,6 @@ pub fn app_in_delete_mode() -> (app::App, handler::Handler) {
     AppBuilder::new_default().press_key(D_KEY).build()
 }
 
-pub fn app_in_delete_mode_on_last_line() -> (app::App, handler::Handler) {
-    AppBuilder::new_default().press_key(CAP_G_KEY).build();
-
-    AppBuilder::new_default().press_key(D_KEY).build()
-}
-
 pub fn app_in_insert_mode() -> (app::App, handler::Handler) {
     AppBuilder::new_default().press_key(I_KEY).build()
 }
@@ -182,15 +157,81 @@ pub fn app_in_goto_mode() -> (app::App, handler::Handler) {
     AppBuilder::new_default().press_key(G_KEY).build()
 }
 
-pub fn app_in_goto_mode_on_newline_char() -> (app::App, handler::Handler) {
-    AppBuilder::new_default().press_key(G_KEY).build()
+/// Draw the app on a 40x16 test terminal.
+pub fn draw(
+    app: &app::App,
+    handler: &handler::Handler,
+) -> Terminal<TestBackend> {
+    let mut terminal = Terminal::new(TestBackend::new(40, 16)).unwrap();
+    terminal
+        .draw(|frame| ui::render(app, handler, frame))
+        .unwrap();
+
+    terminal
 }
 
-pub fn app_with_empty_file_and_cursor_moved_right(
-) -> (app::App, handler::Handler) {
-    AppBuilder::from_file(MockFile::Empty)
-        .press_key(RIGHT_KEY)
-        .build()
+/// A temp dir holding `files`, each by its path in the dir and contents,
+/// with the first of them open.
+pub fn project(
+    files: &[(&str, &str)],
+) -> (TempDir, app::App, handler::Handler) {
+    let dir = TempDir::new().unwrap();
+    for (name, contents) in files {
+        let path = dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    let path = dir.path().join(files[0].0);
+    let buffer = Buffer::from_file(path.to_string_lossy().into()).unwrap();
+    (dir, app::App::new(buffer), handler::Handler::new())
+}
+
+/// The key typed for `char`.
+pub fn key(char: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(char), KeyModifiers::NONE)
+}
+
+/// `char` typed with Ctrl held.
+pub fn ctrl(char: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(char), KeyModifiers::CONTROL)
+}
+
+pub fn press(
+    keys: &[KeyEvent],
+    app: &mut app::App,
+    handler: &mut handler::Handler,
+) {
+    for key in keys {
+        handler.handle_key_event(*key, app);
+    }
+}
+
+/// Type each char of `text` as its key.
+pub fn type_text(
+    text: &str,
+    app: &mut app::App,
+    handler: &mut handler::Handler,
+) {
+    for char in text.chars() {
+        handler.handle_key_event(key(char), app);
+    }
+}
+
+/// Keep calling `update` while the app is `busy`, failing after a few
+/// seconds.
+pub fn wait_while(
+    app: &mut app::App,
+    busy: impl Fn(&app::App) -> bool,
+    update: impl Fn(&mut app::App),
+) {
+    let start = Instant::now();
+    update(app);
+    while busy(app) {
+        assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+        thread::sleep(Duration::from_millis(5));
+        update(app);
+    }
 }
 
 // Key events
@@ -230,14 +271,30 @@ pub const B_KEY: KeyEvent =
 pub const END_KEY: KeyEvent = KeyEvent::new(KeyCode::End, KeyModifiers::NONE);
 pub const HOME_KEY: KeyEvent = KeyEvent::new(KeyCode::Home, KeyModifiers::NONE);
 pub const ESC_KEY: KeyEvent = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+pub const CTRL_T_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+pub const CTRL_N_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
+pub const CTRL_P_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+pub const ENTER_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+pub const BACKSPACE_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE);
+pub const TAB_KEY: KeyEvent = KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE);
+
+pub const M_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE);
+pub const SPACE_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
 
 // Notable positions
 pub const END_OF_FIRST_LINE: (usize, usize) = (33, 0);
 pub const START_OF_LAST_LINE: (usize, usize) = (0, 6);
-pub const END_OF_LAST_LINE: (usize, usize) = (9, 6);
+
+// Row the cursor line is drawn on by `draw`, in the middle of the text area.
+pub const CURSOR_ROW: u16 = 7;
 
 // Modes
 pub const NORMAL: Mode = Mode::Normal;
 pub const INSERT: Mode = Mode::Insert;
-pub const GOTO: Mode = Mode::GoTo;
-pub const DELETE: Mode = Mode::Delete;
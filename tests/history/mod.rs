@@ -0,0 +1,228 @@
+use crate::helpers::{ctrl, key, press, ESC_KEY, G_KEY};
+use editor::{
+    action::{delta::BufferDelta, history::HistoryStore, undo::UndoTree},
+    app::App,
+    buffer::Buffer,
+    handler::Handler,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// A store in the temp dir, and a file beside it to keep history for.
+fn store() -> (TempDir, HistoryStore, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let store = HistoryStore::new(dir.path().join("undo"));
+    let file = dir.path().join("file.txt");
+    fs::write(&file, "ab\n").unwrap();
+
+    (dir, store, file)
+}
+
+/// A step undone, and another branching off where it was undone to.
+fn tree() -> UndoTree {
+    let mut tree = UndoTree::default();
+    tree.record(
+        BufferDelta::Insert {
+            position: 0,
+            text: "a".into(),
+        },
+        BufferDelta::Delete {
+            position: 0,
+            length: 1,
+        },
+    );
+    tree.undo();
+    tree.record(
+        BufferDelta::Replace {
+            position: 1,
+            length: 1,
+            text: "b\n".into(),
+        },
+        BufferDelta::Replace {
+            position: 1,
+            length: 2,
+            text: "b".into(),
+        },
+    );
+    tree.end_step();
+
+    tree
+}
+
+#[test]
+fn history_should_be_loaded_back_for_the_same_text() {
+    let (_dir, store, file) = store();
+    let tree = tree();
+
+    store.save(&file, "ab\n", &tree).unwrap();
+
+    assert_eq!(store.load(&file, "ab\n").unwrap(), tree);
+}
+
+#[test]
+fn history_that_cant_be_read_back_should_be_dropped() {
+    let (_dir, store, file) = store();
+    store.save(&file, "ab\n", &tree()).unwrap();
+    fs::write(store.path(&file).unwrap(), "hash = 1").unwrap();
+
+    assert!(store.load(&file, "ab\n").is_err());
+    assert!(!store.path(&file).unwrap().exists());
+    assert!(store.load(&file, "ab\n").unwrap().is_empty());
+}
+
+#[test]
+fn history_should_be_dropped_once_the_text_changed() {
+    let (_dir, store, file) = store();
+    store.save(&file, "ab\n", &tree()).unwrap();
+
+    assert!(store.load(&file, "abc\n").unwrap().is_empty());
+    assert!(!store.path(&file).unwrap().exists());
+}
+
+#[test]
+fn files_without_history_should_have_none() {
+    let (_dir, store, file) = store();
+
+    assert!(store.load(&file, "ab\n").unwrap().is_empty());
+}
+
+#[test]
+fn each_file_should_keep_its_own_history() {
+    let (dir, store, file) = store();
+    let other = dir.path().join("other.txt");
+    fs::write(&other, "ab\n").unwrap();
+
+    store.save(&file, "ab\n", &tree()).unwrap();
+
+    assert_ne!(store.path(&file).unwrap(), store.path(&other).unwrap());
+    assert!(store.load(&other, "ab\n").unwrap().is_empty());
+}
+
+#[test]
+fn paths_flattening_alike_should_not_share_history() {
+    let (dir, store, _file) = store();
+    let nested = dir.path().join("a").join("b");
+    let flat = dir.path().join("a%b");
+    fs::create_dir(dir.path().join("a")).unwrap();
+    fs::write(&nested, "ab\n").unwrap();
+    fs::write(&flat, "ab\n").unwrap();
+
+    store.save(&nested, "ab\n", &tree()).unwrap();
+
+    assert_ne!(store.path(&nested).unwrap(), store.path(&flat).unwrap());
+    assert!(store.load(&flat, "ab\n").unwrap().is_empty());
+}
+
+/// An app on `file`, keeping undo history in `store`, as when the editor is
+/// started on it.
+fn open(store: &HistoryStore, file: &Path) -> (App, Handler) {
+    let buffer = Buffer::from_file(file.to_string_lossy().into()).unwrap();
+    let mut app = App::new(buffer);
+    app.set_history_store(Some(store.clone()));
+    app.load_history().unwrap();
+
+    (app, Handler::new())
+}
+
+#[test]
+fn edits_from_the_last_session_should_be_undone_once_reopened() {
+    let (_dir, store, file) = store();
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[key('i'), key('x'), ESC_KEY], &mut app, &mut handler);
+    app.save().unwrap();
+    app.quit();
+
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "ab\n");
+    assert!(app.buffer.is_modified());
+}
+
+#[test]
+fn undone_edits_should_be_redone_once_reopened() {
+    let (_dir, store, file) = store();
+    let (mut app, mut handler) = open(&store, &file);
+    press(
+        &[key('i'), key('x'), ESC_KEY, key('u')],
+        &mut app,
+        &mut handler,
+    );
+    app.quit();
+
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[ctrl('r')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "xab\n");
+}
+
+#[test]
+fn undone_branches_should_be_kept_once_reopened() {
+    let (_dir, store, file) = store();
+    let (mut app, mut handler) = open(&store, &file);
+    press(
+        &[key('i'), key('x'), ESC_KEY, key('u')],
+        &mut app,
+        &mut handler,
+    );
+    press(&[key('i'), key('y'), ESC_KEY], &mut app, &mut handler);
+    app.save().unwrap();
+    app.quit();
+
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[G_KEY, key('-')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "xab\n");
+}
+
+#[test]
+fn history_should_not_be_picked_up_once_the_file_changed_elsewhere() {
+    let (_dir, store, file) = store();
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[key('i'), key('x'), ESC_KEY], &mut app, &mut handler);
+    app.save().unwrap();
+    app.quit();
+    fs::write(&file, "xyab\n").unwrap();
+
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "xyab\n");
+    assert!(!store.path(&file).unwrap().exists());
+}
+
+#[test]
+fn files_opened_later_should_pick_up_their_history() {
+    let (dir, store, file) = store();
+    let other = dir.path().join("other.txt");
+    fs::write(&other, "cd\n").unwrap();
+    let (mut app, mut handler) = open(&store, &other);
+    press(&[key('i'), key('x'), ESC_KEY], &mut app, &mut handler);
+    app.save().unwrap();
+    app.quit();
+
+    let (mut app, mut handler) = open(&store, &file);
+    app.open_file(&other).unwrap();
+    press(&[key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "cd\n");
+}
+
+#[test]
+fn reloading_should_keep_the_history_for_the_text_on_disk() {
+    let (_dir, store, file) = store();
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[key('i'), key('x'), ESC_KEY], &mut app, &mut handler);
+    app.save().unwrap();
+    fs::write(&file, "yxab\n").unwrap();
+    app.check_file().unwrap();
+    app.quit();
+
+    let (mut app, mut handler) = open(&store, &file);
+    press(&[key('u'), key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "ab\n");
+}
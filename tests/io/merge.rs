@@ -0,0 +1,52 @@
+use editor::diff::{map_line, merge};
+
+const NAMES: [&str; 3] = ["ours", "base", "theirs"];
+
+#[test]
+fn changes_to_different_lines_should_both_be_kept() {
+    let merge = merge("a\nb\nc\nd\n", "A\nb\nc\nd\n", "a\nb\nc\nD\n", NAMES);
+
+    assert_eq!(merge.text, "A\nb\nc\nD\n");
+    assert!(merge.conflicts.is_empty());
+}
+
+#[test]
+fn the_same_change_on_both_sides_should_be_kept_once() {
+    let merge = merge("a\nb\n", "a\nB\n", "a\nB\n", NAMES);
+
+    assert_eq!(merge.text, "a\nB\n");
+    assert!(merge.conflicts.is_empty());
+}
+
+#[test]
+fn lines_added_on_one_side_should_be_kept() {
+    let merge = merge("a\nb\nc\n", "a\nb\nc\nd\n", "z\na\nb\nc\n", NAMES);
+
+    assert_eq!(merge.text, "z\na\nb\nc\nd\n");
+}
+
+#[test]
+fn clashing_changes_should_be_left_between_markers() {
+    let merge = merge("a\nb\nc\n", "a\nx\nc\n", "a\ny\nc\n", NAMES);
+
+    assert_eq!(
+        merge.text,
+        "a\n<<<<<<< ours\nx\n||||||| base\nb\n=======\ny\n>>>>>>> theirs\nc\n"
+    );
+    assert_eq!(merge.conflicts, vec![1]);
+}
+
+#[test]
+fn map_line_should_follow_lines_moved_down() {
+    assert_eq!(map_line("a\nb\nc\n", "x\ny\na\nb\nc\n", 1), 3);
+}
+
+#[test]
+fn map_line_should_keep_a_changed_line_in_place() {
+    assert_eq!(map_line("a\nb\nc\n", "a\nB\nc\n", 1), 1);
+}
+
+#[test]
+fn map_line_should_stop_at_the_last_line() {
+    assert_eq!(map_line("a\nb\nc\n", "a\n", 2), 0);
+}
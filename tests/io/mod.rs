@@ -1 +1,24 @@
+use editor::{app::App, buffer::Buffer};
+use std::{fs, path::PathBuf};
+use tempfile::TempDir;
+
 mod file_load;
+mod merge;
+mod reload;
+mod swap;
+
+/// An app on `file.txt`, holding `contents`, in a directory of its own.
+fn app_on(contents: &str) -> (TempDir, PathBuf, App) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("file.txt");
+    fs::write(&path, contents).unwrap();
+    let buffer = Buffer::from_file(path.to_string_lossy().into()).unwrap();
+
+    (dir, path, App::new(buffer))
+}
+
+/// Type `input` into the prompt and submit it.
+fn answer(app: &mut App, input: &str) {
+    app.prompt_mut().unwrap().input = input.into();
+    app.submit_prompt().unwrap();
+}
@@ -0,0 +1,145 @@
+use super::{answer, app_on};
+use editor::{
+    app::{Error, PromptAction},
+    event::{Event, EventBus},
+};
+use std::{fs, time::Duration};
+
+#[test]
+fn watcher_should_wake_the_loop_when_the_file_changes() {
+    let (_dir, path, mut app) = app_on("one\ntwo\n");
+    let bus = EventBus::new();
+    app.set_waker(bus.waker());
+    app.watch_files().unwrap();
+
+    fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+    let event = bus.recv(Some(Duration::from_secs(5)));
+    assert!(matches!(event, Some(Event::Wake)));
+    app.update_background();
+    assert_eq!(app.buffer.contents(), "one\ntwo\nthree\n");
+    assert!(!app.buffer.is_modified());
+}
+
+#[test]
+fn reload_should_keep_the_cursor_on_its_line() {
+    let (_dir, path, mut app) = app_on("a\nb\nc\n");
+    app.buffer.cursor = (0, 2).into();
+
+    fs::write(&path, "new\na\nb\nc\n").unwrap();
+    app.check_file().unwrap();
+
+    assert_eq!(app.buffer.contents(), "new\na\nb\nc\n");
+    assert_eq!(app.buffer.cursor, (0, 3).into());
+}
+
+#[test]
+fn own_saves_should_not_count_as_changes() {
+    let (_dir, _path, mut app) = app_on("a\n");
+    app.buffer.insert(0, "b").unwrap();
+
+    app.save().unwrap();
+    app.check_file().unwrap();
+
+    assert!(app.prompt().is_none());
+    assert_eq!(app.buffer.contents(), "ba\n");
+}
+
+#[test]
+fn changes_under_edits_should_ask_what_to_do() {
+    let (_dir, path, mut app) = app_on("a\nb\n");
+    app.buffer.insert(0, "x").unwrap();
+
+    fs::write(&path, "a\nc\n").unwrap();
+    app.check_file().unwrap();
+
+    assert_eq!(app.prompt().unwrap().action, PromptAction::FileChanged);
+    assert_eq!(app.buffer.contents(), "xa\nb\n");
+}
+
+#[test]
+fn r_should_drop_the_edits_for_the_file() {
+    let (_dir, path, mut app) = app_on("a\nb\n");
+    app.buffer.insert(0, "x").unwrap();
+    fs::write(&path, "a\nc\n").unwrap();
+    app.check_file().unwrap();
+
+    answer(&mut app, "r");
+
+    assert_eq!(app.buffer.contents(), "a\nc\n");
+    assert!(!app.buffer.is_modified());
+}
+
+#[test]
+fn k_should_keep_the_edits_to_save_over_the_file() {
+    let (_dir, path, mut app) = app_on("a\nb\n");
+    app.buffer.insert(0, "x").unwrap();
+    fs::write(&path, "a\nc\n").unwrap();
+    app.check_file().unwrap();
+
+    answer(&mut app, "k");
+    app.save().unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "xa\nb\n");
+}
+
+#[test]
+fn m_should_merge_the_edits_with_the_changes_on_disk() {
+    let (_dir, path, mut app) = app_on("a\nb\nc\n");
+    app.buffer.insert(0, "x").unwrap();
+    fs::write(&path, "a\nb\nd\n").unwrap();
+    app.check_file().unwrap();
+
+    answer(&mut app, "m");
+
+    assert_eq!(app.buffer.contents(), "xa\nb\nd\n");
+    assert!(app.buffer.is_modified());
+}
+
+#[test]
+fn m_should_leave_conflicts_between_markers() {
+    let (_dir, path, mut app) = app_on("a\nb\n");
+    app.buffer.insert(2, "x").unwrap();
+    fs::write(&path, "a\nby\n").unwrap();
+    app.check_file().unwrap();
+
+    answer(&mut app, "m");
+
+    assert_eq!(
+        app.buffer.contents(),
+        "a\n<<<<<<< buffer\nxb\n||||||| saved\nb\n=======\nby\n>>>>>>> disk\n"
+    );
+    assert_eq!(app.buffer.cursor.y, 1);
+}
+
+#[test]
+fn d_should_show_both_sides_of_the_change_and_ask_again() {
+    let (_dir, path, mut app) = app_on("a\n");
+    app.buffer.insert(0, "x").unwrap();
+    fs::write(&path, "b\n").unwrap();
+    app.check_file().unwrap();
+
+    answer(&mut app, "d");
+
+    let popup = app.popup().unwrap();
+    let buffer = popup.lines.iter().position(|line| line == "+++ buffer");
+    let disk = popup.lines.iter().position(|line| line == "+++ disk");
+    assert!(buffer.unwrap() < disk.unwrap());
+    assert_eq!(popup.lines.iter().filter(|line| *line == "-a").count(), 2);
+    assert!(popup.lines.contains(&"+xa".to_string()));
+    assert!(popup.lines.contains(&"+b".to_string()));
+    assert_eq!(app.prompt().unwrap().action, PromptAction::FileChanged);
+}
+
+#[test]
+fn save_should_not_write_over_changes_on_disk() {
+    let (_dir, path, mut app) = app_on("a\n");
+    app.buffer.insert(0, "x").unwrap();
+    fs::write(&path, "b\n").unwrap();
+
+    let result = app.save();
+
+    assert!(matches!(result, Err(Error::ChangedOnDisk)));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "b\n");
+    assert_eq!(app.prompt().unwrap().action, PromptAction::FileChanged);
+}
@@ -0,0 +1,194 @@
+use super::{answer, app_on};
+use editor::{
+    app::{App, PromptAction},
+    buffer::Buffer,
+    swap::SwapDir,
+};
+use std::{
+    fs::{self, File},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+use tempfile::TempDir;
+
+/// The app writing swap files into a directory of the temp dir.
+fn with_swap(dir: &TempDir, app: &mut App) -> SwapDir {
+    let swap = SwapDir::new(dir.path().join("swap"));
+    app.set_swap_dir(Some(swap.clone()));
+
+    swap
+}
+
+/// A swap of `file.txt` holding `text`, left behind as by a crash.
+fn crashed_with(text: &str) -> (TempDir, App, SwapDir) {
+    let (dir, path, mut app) = app_on("a\nb\n");
+    let swap = with_swap(&dir, &mut app);
+    swap.write(&path, text).unwrap();
+    app.check_swap().unwrap();
+
+    (dir, app, swap)
+}
+
+fn swap_of(swap: &SwapDir, path: &Path) -> Option<String> {
+    fs::read_to_string(swap.path(path).unwrap()).ok()
+}
+
+#[test]
+fn swap_files_should_be_written_for_buffers_with_edits() {
+    let (dir, path, mut app) = app_on("a\n");
+    let swap = with_swap(&dir, &mut app);
+    assert_eq!(app.swap_remaining(), None);
+
+    app.buffer.insert(0, "x").unwrap();
+    assert_eq!(app.swap_remaining(), Some(Duration::ZERO));
+    app.write_swaps();
+
+    assert_eq!(swap_of(&swap, &path).unwrap(), "xa\n");
+    assert!(app.swap_remaining().unwrap() > Duration::ZERO);
+}
+
+#[test]
+fn save_should_remove_the_swap_file() {
+    let (dir, path, mut app) = app_on("a\n");
+    let swap = with_swap(&dir, &mut app);
+    app.buffer.insert(0, "x").unwrap();
+    app.write_swaps();
+
+    app.save().unwrap();
+
+    assert_eq!(swap_of(&swap, &path), None);
+}
+
+#[test]
+fn quit_should_remove_the_swap_files_of_clean_buffers() {
+    let (dir, path, mut app) = app_on("a\n");
+    let swap = with_swap(&dir, &mut app);
+    swap.write(&path, "x\n").unwrap();
+
+    app.quit();
+
+    assert_eq!(swap_of(&swap, &path), None);
+}
+
+#[test]
+fn quit_should_keep_unsaved_edits_in_the_swap_files() {
+    let (dir, path, mut app) = app_on("a\n");
+    let swap = with_swap(&dir, &mut app);
+    app.buffer.insert(0, "x").unwrap();
+    app.write_swaps();
+    app.buffer.insert(0, "y").unwrap();
+
+    app.quit();
+
+    assert_eq!(swap_of(&swap, &path).unwrap(), "yxa\n");
+}
+
+#[test]
+fn swaps_of_deeply_nested_files_should_be_written() {
+    let (dir, _, mut app) = app_on("a\n");
+    let swap = with_swap(&dir, &mut app);
+    let nested = dir.path().join(["directory"; 30].join("/"));
+    fs::create_dir_all(&nested).unwrap();
+    let path = nested.join("file.txt");
+    fs::write(&path, "a\n").unwrap();
+
+    swap.write(&path, "xa\n").unwrap();
+
+    let name = swap.path(&path).unwrap();
+    assert!(name.file_name().unwrap().len() <= 255);
+    assert_eq!(swap_of(&swap, &path).unwrap(), "xa\n");
+    assert_ne!(name, swap.path(&nested.join("other.txt")).unwrap());
+}
+
+#[test]
+fn a_swap_newer_than_the_file_should_offer_recovery() {
+    let (_dir, app, _) = crashed_with("a\nb\nc\n");
+
+    assert_eq!(app.prompt().unwrap().action, PromptAction::Recover);
+    assert_eq!(app.buffer.contents(), "a\nb\n");
+}
+
+#[test]
+fn a_swap_older_than_the_file_should_be_ignored() {
+    let (dir, path, mut app) = app_on("a\nb\n");
+    let swap = with_swap(&dir, &mut app);
+    swap.write(&path, "a\nb\nc\n").unwrap();
+    let later = SystemTime::now() + Duration::from_secs(60);
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+
+    app.check_swap().unwrap();
+
+    assert!(app.prompt().is_none());
+}
+
+#[test]
+fn r_should_recover_the_edits() {
+    let (_dir, mut app, _) = crashed_with("a\nb\nc\n");
+
+    answer(&mut app, "r");
+
+    assert_eq!(app.buffer.contents(), "a\nb\nc\n");
+    assert!(app.buffer.is_modified());
+}
+
+#[test]
+fn r_should_recover_the_undo_history_from_the_journal() {
+    let (dir, path, mut app) = app_on("a\n");
+    let swap = with_swap(&dir, &mut app);
+    app.buffer.insert(0, "x").unwrap();
+    app.end_undo_step();
+    app.buffer.insert(0, "y").unwrap();
+    app.write_swaps();
+    let buffer = Buffer::from_file(path.to_string_lossy().into()).unwrap();
+    let mut app = App::new(buffer);
+    with_swap(&dir, &mut app);
+    app.check_swap().unwrap();
+
+    answer(&mut app, "r");
+
+    assert_eq!(app.buffer.contents(), "yxa\n");
+    app.undo(1).unwrap();
+    assert_eq!(app.buffer.contents(), "xa\n");
+    app.undo(1).unwrap();
+    assert_eq!(app.buffer.contents(), "a\n");
+    assert!(swap.journal().path(&path).unwrap().exists());
+}
+
+#[test]
+fn removing_the_swap_should_remove_its_journal() {
+    let (dir, path, mut app) = app_on("a\n");
+    let swap = with_swap(&dir, &mut app);
+    app.buffer.insert(0, "x").unwrap();
+    app.write_swaps();
+    assert!(swap.journal().path(&path).unwrap().exists());
+
+    app.save().unwrap();
+
+    assert!(!swap.journal().path(&path).unwrap().exists());
+}
+
+#[test]
+fn d_should_show_the_diff_and_ask_again() {
+    let (_dir, mut app, _) = crashed_with("a\nb\nc\n");
+
+    answer(&mut app, "d");
+
+    assert!(app.popup().unwrap().lines.contains(&"+c".to_string()));
+    assert_eq!(app.prompt().unwrap().action, PromptAction::Recover);
+}
+
+#[test]
+fn x_should_discard_the_swap_file() {
+    let (_dir, mut app, swap) = crashed_with("a\nb\nc\n");
+
+    answer(&mut app, "x");
+
+    let path = Path::new(app.buffer.file_name());
+    assert_eq!(swap_of(&swap, path), None);
+    assert_eq!(app.buffer.contents(), "a\nb\n");
+}
@@ -0,0 +1,12 @@
+use editor::{config::Config, keymap::Keymap};
+
+mod parse;
+mod remap;
+mod sequence;
+
+fn keymap_from(src: &str) -> Keymap {
+    let (config, errors) = Config::from_toml(src);
+    assert!(errors.is_empty());
+
+    Keymap::from_config(&config.keys).0
+}
@@ -0,0 +1,60 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use editor::keymap::{parse_keys, Key};
+
+#[test]
+fn single_chars_should_parse_as_keys() {
+    let keys = parse_keys("gd", &[]).unwrap();
+
+    assert_eq!(
+        keys,
+        vec![
+            Key::new(KeyCode::Char('g'), KeyModifiers::NONE),
+            Key::new(KeyCode::Char('d'), KeyModifiers::NONE),
+        ]
+    )
+}
+
+#[test]
+fn named_keys_should_parse() {
+    let keys = parse_keys("<esc><C-t><A-x>", &[]).unwrap();
+
+    assert_eq!(
+        keys,
+        vec![
+            Key::new(KeyCode::Esc, KeyModifiers::NONE),
+            Key::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+            Key::new(KeyCode::Char('x'), KeyModifiers::ALT),
+        ]
+    )
+}
+
+#[test]
+fn leader_should_expand() {
+    let leader = parse_keys("<space>", &[]).unwrap();
+    let keys = parse_keys("<leader>q", &leader).unwrap();
+
+    assert_eq!(
+        keys,
+        vec![
+            Key::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            Key::new(KeyCode::Char('q'), KeyModifiers::NONE),
+        ]
+    )
+}
+
+#[test]
+fn unclosed_key_name_should_be_an_error() {
+    assert!(parse_keys("<esc", &[]).is_err())
+}
+
+#[test]
+fn unknown_key_name_should_be_an_error() {
+    assert!(parse_keys("<nope>", &[]).is_err())
+}
+
+#[test]
+fn keys_should_display_as_written() {
+    let keys = parse_keys("<C-t>", &[]).unwrap();
+
+    assert_eq!(keys[0].to_string(), "<C-t>")
+}
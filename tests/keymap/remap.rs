@@ -0,0 +1,81 @@
+use super::keymap_from;
+use crate::helpers::{app_default, I_KEY, W_KEY};
+use editor::{
+    app::Mode,
+    config::Config,
+    keymap::{parse_keys, Command, Lookup},
+};
+
+#[test]
+fn config_should_rebind_key() {
+    let keymap = keymap_from(
+        r#"
+        [keys.normal]
+        w = "move_down"
+        "#,
+    );
+    let keys = parse_keys("w", &[]).unwrap();
+
+    assert_eq!(
+        keymap.lookup(Mode::Normal, &keys),
+        Lookup::Command(Command::MoveDown)
+    )
+}
+
+#[test]
+fn none_should_unbind_key() {
+    let keymap = keymap_from(
+        r#"
+        [keys.normal]
+        i = "none"
+        "#,
+    );
+    let keys = parse_keys("i", &[]).unwrap();
+
+    assert_eq!(keymap.lookup(Mode::Normal, &keys), Lookup::Unmapped)
+}
+
+#[test]
+fn unknown_command_should_be_reported() {
+    let (_, errors) = Config::from_toml(
+        r#"
+        [keys.normal]
+        w = "fly_away"
+        "#,
+    );
+
+    assert_eq!(
+        errors[0].to_string(),
+        "Config error: Keymap error: no command named fly_away"
+    )
+}
+
+#[test]
+fn rebound_key_should_drive_handler() {
+    let (mut app, mut handler) = app_default();
+    handler.set_keymap(keymap_from(
+        r#"
+        [keys.normal]
+        w = "insert_mode"
+        "#,
+    ));
+
+    handler.handle_key_event(W_KEY, &mut app);
+
+    assert_eq!(app.mode(), Mode::Insert)
+}
+
+#[test]
+fn unbound_key_should_do_nothing() {
+    let (mut app, mut handler) = app_default();
+    handler.set_keymap(keymap_from(
+        r#"
+        [keys.normal]
+        i = "none"
+        "#,
+    ));
+
+    handler.handle_key_event(I_KEY, &mut app);
+
+    assert_eq!(app.mode(), Mode::Normal)
+}
@@ -0,0 +1,92 @@
+use super::keymap_from;
+use crate::helpers::{
+    app_default, app_in_insert_mode, A_KEY, CAP_G_KEY, SPACE_KEY,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use editor::{
+    app::{Mode, RunningState},
+    keymap::{Command, Continuation, Key},
+};
+
+const J_KEY: KeyEvent = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+const Q_KEY: KeyEvent = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+
+#[test]
+fn leader_should_wait_for_next_key() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(SPACE_KEY, &mut app);
+
+    assert_eq!(handler.pending(), &[Key::from(SPACE_KEY)])
+}
+
+#[test]
+fn leader_sequence_should_run_command() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(SPACE_KEY, &mut app);
+    handler.handle_key_event(Q_KEY, &mut app);
+
+    assert!(handler.pending().is_empty());
+    assert_eq!(app.running_state(), RunningState::Done)
+}
+
+#[test]
+fn pending_sequence_should_list_continuations() {
+    let (app, handler) = app_default();
+    let leader = [Key::from(SPACE_KEY)];
+
+    let continuations = handler
+        .keymap()
+        .trie(app.mode())
+        .and_then(|trie| trie.search(&leader))
+        .unwrap()
+        .continuations();
+
+    assert!(continuations
+        .contains(&(Key::from(Q_KEY), Continuation::Command(Command::Quit))))
+}
+
+#[test]
+fn dead_end_sequence_should_retry_last_key() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(SPACE_KEY, &mut app);
+    handler.handle_key_event(CAP_G_KEY, &mut app);
+
+    assert!(handler.pending().is_empty());
+    assert_eq!(app.buffer.cursor.y, 6)
+}
+
+#[test]
+fn insert_sequence_should_run_command() {
+    let (mut app, mut handler) = app_in_insert_mode();
+    handler.set_keymap(keymap_from(
+        r#"
+        [keys.insert]
+        jj = "normal_mode"
+        "#,
+    ));
+
+    handler.handle_key_event(J_KEY, &mut app);
+    handler.handle_key_event(J_KEY, &mut app);
+
+    assert_eq!(app.mode(), Mode::Normal)
+}
+
+#[test]
+fn failed_insert_sequence_should_type_keys() {
+    let (mut app, mut handler) = app_in_insert_mode();
+    handler.set_keymap(keymap_from(
+        r#"
+        [keys.insert]
+        jj = "normal_mode"
+        "#,
+    ));
+
+    handler.handle_key_event(J_KEY, &mut app);
+    handler.handle_key_event(A_KEY, &mut app);
+
+    assert_eq!(app.mode(), Mode::Insert);
+    assert!(app.buffer.line(0).unwrap().to_string().starts_with("ja"))
+}
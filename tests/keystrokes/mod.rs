@@ -0,0 +1,47 @@
+use crate::helpers::{
+    app_default, ctrl, draw, press, type_text, DOWN_KEY, THREE_KEY,
+};
+use editor::{app::App, handler::Handler};
+
+mod screencast;
+
+fn row(app: &App, handler: &Handler, y: u16) -> String {
+    let terminal = draw(app, handler);
+    let buffer = terminal.backend().buffer();
+    (0..40).map(|x| buffer.get(x, y).symbol()).collect()
+}
+
+fn status_line(app: &App, handler: &Handler) -> String {
+    row(app, handler, 15)
+}
+
+#[test]
+fn status_line_should_show_the_count_typed() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(THREE_KEY, &mut app);
+
+    assert_eq!(handler.count(), Some(3));
+    assert!(status_line(&app, &handler).ends_with(" 3 "));
+}
+
+#[test]
+fn status_line_should_show_the_count_and_pending_keys() {
+    let (mut app, mut handler) = app_default();
+
+    type_text("2", &mut app, &mut handler);
+    press(&[ctrl('w')], &mut app, &mut handler);
+
+    assert!(status_line(&app, &handler).ends_with(" 2<C-w> "));
+}
+
+#[test]
+fn pending_keys_should_leave_the_status_line_once_run() {
+    let (mut app, mut handler) = app_default();
+
+    type_text("3", &mut app, &mut handler);
+    handler.handle_key_event(DOWN_KEY, &mut app);
+
+    assert_eq!(handler.count(), None);
+    assert!(status_line(&app, &handler).ends_with(" 4:1 "));
+}
@@ -0,0 +1,72 @@
+use super::row;
+use crate::helpers::{app_default, ctrl, draw, press, type_text, SPACE_KEY};
+use editor::{app::App, handler::Handler};
+
+fn screencasting() -> (App, Handler) {
+    let (mut app, mut handler) = app_default();
+    handler.handle_key_event(SPACE_KEY, &mut app);
+    type_text("k", &mut app, &mut handler);
+
+    (app, handler)
+}
+
+#[test]
+fn screencast_should_be_off_by_default() {
+    let (mut app, mut handler) = app_default();
+
+    type_text("j", &mut app, &mut handler);
+
+    assert!(!handler.screencast);
+    assert!(!row(&app, &handler, 14).contains(" j "));
+}
+
+#[test]
+fn screencast_should_show_the_latest_keys_with_modifiers() {
+    let (mut app, mut handler) = screencasting();
+
+    press(&[ctrl('w')], &mut app, &mut handler);
+    type_text("v", &mut app, &mut handler);
+
+    assert!(handler.screencast);
+    assert!(row(&app, &handler, 14).ends_with(" <space>  k  <C-w>  v "));
+}
+
+#[test]
+fn repeated_keys_should_alternate_in_style() {
+    let (mut app, mut handler) = screencasting();
+
+    type_text("l", &mut app, &mut handler);
+    type_text("l", &mut app, &mut handler);
+
+    let terminal = draw(&app, &handler);
+    let buffer = terminal.backend().buffer();
+    // The last two keys, ` l  l `, end the row.
+    assert_eq!(buffer.get(35, 14).symbol(), "l");
+    assert_eq!(buffer.get(38, 14).symbol(), "l");
+    assert_ne!(buffer.get(35, 14).style(), buffer.get(38, 14).style());
+}
+
+#[test]
+fn oldest_keys_should_be_dropped_when_out_of_room() {
+    let (mut app, mut handler) = screencasting();
+
+    for _ in 0..20 {
+        type_text("l", &mut app, &mut handler);
+    }
+
+    // As many of the 3 column keys as fit in the 40 columns.
+    let row = row(&app, &handler, 14);
+    assert!(row.ends_with(&" l ".repeat(13)));
+    assert!(!row.contains("space"));
+}
+
+#[test]
+fn which_key_should_open_above_the_screencast() {
+    let (mut app, mut handler) = screencasting();
+
+    press(&[ctrl('w')], &mut app, &mut handler);
+
+    let row = row(&app, &handler, 14);
+    assert!(row.ends_with(" <C-w> "));
+    assert!(!row.contains('─'));
+}
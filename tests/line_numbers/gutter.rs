@@ -0,0 +1,56 @@
+use super::{app_with_line_numbers, gutters};
+use crate::helpers::{draw, CURSOR_ROW};
+
+#[test]
+fn absolute_should_number_from_one() {
+    let (app, handler) = app_with_line_numbers("absolute");
+
+    assert_eq!(
+        gutters(&app, &handler),
+        [" 1┆", " 2┆", " 3┆", " 4┆", " 5┆", " 6┆", " 7┆"]
+    )
+}
+
+#[test]
+fn relative_should_count_from_cursor() {
+    let (app, handler) = app_with_line_numbers("relative");
+
+    assert_eq!(
+        gutters(&app, &handler),
+        [" 3┆", " 2┆", " 1┆", " 0┆", " 1┆", " 2┆", " 3┆"]
+    )
+}
+
+#[test]
+fn hybrid_should_show_absolute_on_cursor_line() {
+    let (app, handler) = app_with_line_numbers("hybrid");
+
+    assert_eq!(
+        gutters(&app, &handler),
+        [" 3┆", " 2┆", " 1┆", " 4┆", " 1┆", " 2┆", " 3┆"]
+    )
+}
+
+#[test]
+fn none_should_hide_gutter() {
+    let (app, handler) = app_with_line_numbers("none");
+
+    assert!(gutters(&app, &handler)
+        .iter()
+        .all(|line| !line.contains('┆')))
+}
+
+#[test]
+fn cursor_line_number_should_use_its_own_style() {
+    let (app, handler) = app_with_line_numbers("absolute");
+
+    let terminal = draw(&app, &handler);
+    let buffer = terminal.backend().buffer();
+
+    let cursor_line_number = app.theme().cursor_line_number();
+    assert_eq!(buffer.get(1, CURSOR_ROW).fg, cursor_line_number.fg.unwrap());
+    assert_ne!(
+        buffer.get(1, CURSOR_ROW - 1).fg,
+        cursor_line_number.fg.unwrap()
+    )
+}
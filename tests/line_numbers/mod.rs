@@ -0,0 +1,37 @@
+use crate::helpers::{app_default, draw, CURSOR_ROW};
+use editor::{app::App, config::Config, handler::Handler};
+
+mod gutter;
+
+/// App with the cursor on the 4th line, drawn with the given line numbers and
+/// no sign column.
+fn app_with_line_numbers(line_numbers: &str) -> (App, Handler) {
+    let (mut app, handler) = app_default();
+    let (config, errors) = Config::from_toml(&format!(
+        "line_numbers = \"{}\"\n[layout]\nsign_column = false",
+        line_numbers
+    ));
+    assert!(errors.is_empty());
+    app.configure(config).unwrap();
+    app.move_down(3).unwrap();
+
+    (app, handler)
+}
+
+/// Draw the app and return the gutter of each of the buffer's lines.
+fn gutters(app: &App, handler: &Handler) -> Vec<String> {
+    let terminal = draw(app, handler);
+    let buffer = terminal.backend().buffer();
+    let first_row = CURSOR_ROW - app.buffer.cursor.y as u16;
+    (first_row..first_row + app.buffer.len_lines() as u16)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+                .split_inclusive('┆')
+                .next()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
@@ -0,0 +1,77 @@
+use editor::logger::{Level, Logger};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn flush_should_append_only_what_is_new() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("state/editor.log");
+    let mut logger = Logger::with_file(path.clone());
+
+    logger.log(Level::Info, "first".into());
+    logger.flush().unwrap();
+    logger.log(Level::Error, "second".into());
+    logger.flush().unwrap();
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with(" info: first"));
+    assert!(lines[1].ends_with(" error: second"));
+}
+
+#[test]
+fn file_should_be_appended_to_across_loggers() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("editor.log");
+    fs::write(&path, "earlier\n").unwrap();
+    let mut logger = Logger::with_file(path.clone());
+
+    logger.log(Level::Info, "later".into());
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.starts_with("earlier\n"));
+    assert!(contents.trim_end().ends_with("later"));
+}
+
+#[test]
+fn big_file_should_be_rotated() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("editor.log");
+    let rotated = |idx: usize| dir.path().join(format!("editor.log.{}", idx));
+    let mut logger = Logger::with_file(path.clone()).with_max_file_size(10);
+
+    for idx in 0..5 {
+        logger.log(Level::Info, format!("log {}", idx));
+        logger.flush().unwrap();
+    }
+
+    // Three rotated files are kept, the newest first.
+    assert!(fs::read_to_string(&path).unwrap().contains("log 4"));
+    assert!(fs::read_to_string(rotated(1)).unwrap().contains("log 3"));
+    assert!(fs::read_to_string(rotated(3)).unwrap().contains("log 1"));
+    assert!(!rotated(4).exists());
+}
+
+#[test]
+fn logger_without_a_file_should_flush_nothing() {
+    let mut logger = Logger::new();
+    logger.log(Level::Info, "kept in memory".into());
+
+    assert!(logger.flush().is_ok());
+    assert!(logger.path().is_none());
+}
+
+#[test]
+fn logs_should_be_written_as_they_are_logged() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("editor.log");
+    let mut logger = Logger::with_file(path.clone());
+
+    logger.log(Level::Warning, "unflushed".into());
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.trim_end().ends_with(" warning: unflushed"));
+}
@@ -0,0 +1,52 @@
+use crate::helpers::app_default;
+use editor::{
+    app::{Notification, NotificationType},
+    event::{self, EventBus},
+    logger::{Level, Logger},
+};
+
+mod file;
+mod view;
+
+#[test]
+fn logs_below_the_min_level_should_be_dropped() {
+    let mut logger = Logger::new();
+    logger.set_min_level(Level::Warning);
+
+    logger.log(Level::Info, "info".into());
+    logger.log(Level::Error, "error".into());
+
+    let messages: Vec<&str> = logger.logs().map(|log| log.message()).collect();
+    assert_eq!(messages, ["error"]);
+}
+
+#[test]
+fn oldest_logs_should_be_dropped_past_the_limit() {
+    let mut logger = Logger::new();
+
+    for idx in 0..1005 {
+        logger.log(Level::Info, idx.to_string());
+    }
+
+    assert_eq!(logger.logs().count(), 1000);
+    assert_eq!(logger.logs().next().unwrap().message(), "5");
+    assert_eq!(logger.logs().last().unwrap().message(), "1004");
+}
+
+#[test]
+fn notifications_should_be_logged_at_their_level() {
+    let (mut app, mut handler) = app_default();
+    app.push_notif(Notification::new(NotificationType::Error, "it broke"));
+    app.push_notif(Notification::new(NotificationType::Success, "saved"));
+    let bus = EventBus::new();
+    bus.send(event::Event::Wake);
+
+    handler.listen(&mut app, &bus).unwrap();
+
+    let logs: Vec<(Level, &str)> = handler
+        .logger
+        .logs()
+        .map(|log| (log.level(), log.message()))
+        .collect();
+    assert_eq!(logs, [(Level::Error, "it broke"), (Level::Info, "saved")]);
+}
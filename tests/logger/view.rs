@@ -0,0 +1,70 @@
+use crate::helpers::{app_with_empty_file, draw, type_text, SPACE_KEY};
+use editor::{app::App, handler::Handler, logger::Level};
+
+/// The rows of the screen holding logs.
+fn log_rows(app: &App, handler: &Handler) -> Vec<String> {
+    let terminal = draw(app, handler);
+    let buffer = terminal.backend().buffer();
+    (0..16)
+        .map(|y| (0..40).map(|x| buffer.get(x, y).symbol()).collect())
+        .filter(|row: &String| row.contains(": log") || row.contains(" logs"))
+        .collect()
+}
+
+fn logged() -> (App, Handler) {
+    // Below an empty file the logs have the lower half to themselves.
+    let (app, mut handler) = app_with_empty_file();
+    handler.logger.log(Level::Info, "log one".into());
+    handler.logger.log(Level::Error, "log two".into());
+    handler.logger.log(Level::Warning, "log three".into());
+
+    (app, handler)
+}
+
+#[test]
+fn logs_should_be_shown_newest_first() {
+    let (app, handler) = logged();
+
+    let rows = log_rows(&app, &handler);
+
+    assert!(rows[0].contains("warning: log three"));
+    assert!(rows[1].contains("error: log two"));
+    assert!(rows[2].contains("info: log one"));
+}
+
+#[test]
+fn cycling_the_level_should_hide_the_logs_below_it() {
+    let (mut app, mut handler) = logged();
+
+    handler.handle_key_event(SPACE_KEY, &mut app);
+    type_text("L", &mut app, &mut handler);
+    handler.handle_key_event(SPACE_KEY, &mut app);
+    type_text("L", &mut app, &mut handler);
+
+    let rows = log_rows(&app, &handler);
+    assert_eq!(handler.log_view.level, Level::Error);
+    assert!(rows[0].contains("logs from error up"));
+    assert!(rows[1].contains("log two"));
+    assert_eq!(rows.len(), 2);
+}
+
+#[test]
+fn scrolling_should_skip_the_newest_logs_and_stop_at_the_oldest() {
+    let (mut app, mut handler) = logged();
+
+    type_text("]", &mut app, &mut handler);
+    type_text("l", &mut app, &mut handler);
+    let rows = log_rows(&app, &handler);
+    assert!(rows[0].contains("1 newer hidden"));
+    assert!(rows[1].contains("log two"));
+
+    for _ in 0..5 {
+        type_text("]", &mut app, &mut handler);
+        type_text("l", &mut app, &mut handler);
+    }
+    assert_eq!(handler.log_view.scroll, 2);
+
+    type_text("[", &mut app, &mut handler);
+    type_text("l", &mut app, &mut handler);
+    assert_eq!(handler.log_view.scroll, 1);
+}
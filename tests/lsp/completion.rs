@@ -0,0 +1,49 @@
+use super::{wait_for_server, Project};
+use crate::helpers::{key, CTRL_N_KEY, ENTER_KEY, I_KEY};
+
+#[test]
+fn server_items_should_join_the_menu() {
+    let project = Project::new("\nx\n");
+    let (mut app, mut handler) = project.app();
+    for key in [I_KEY, key('f'), key('a'), CTRL_N_KEY] {
+        handler.handle_key_event(key, &mut app);
+    }
+
+    wait_for_server(&mut app);
+
+    let completion = app.completion().unwrap();
+    let labels: Vec<&str> =
+        completion.items.iter().map(|item| &*item.label).collect();
+    assert_eq!(labels, ["fake_fn", "fake_value"]);
+    assert_eq!(completion.items[0].detail.as_deref(), Some("fn"));
+}
+
+#[test]
+fn trigger_character_should_open_the_menu() {
+    let project = Project::new("\nx\n");
+    let (mut app, mut handler) = project.app();
+    // Trigger characters are known once the server has initialized.
+    wait_for_server(&mut app);
+    for key in [I_KEY, key('a'), key('.')] {
+        handler.handle_key_event(key, &mut app);
+    }
+
+    wait_for_server(&mut app);
+
+    assert_eq!(app.completion().unwrap().items.len(), 2);
+}
+
+#[test]
+fn server_snippet_should_expand() {
+    let project = Project::new("\nx\n");
+    let (mut app, mut handler) = project.app();
+    for key in [I_KEY, key('f'), CTRL_N_KEY] {
+        handler.handle_key_event(key, &mut app);
+    }
+    wait_for_server(&mut app);
+
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert_eq!(app.buffer.line(0).unwrap().to_string(), "fake_fn(arg, )\n");
+    assert_eq!(app.buffer.cursor, (8, 0).into());
+}
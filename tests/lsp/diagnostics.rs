@@ -0,0 +1,39 @@
+use super::{round_trip, Project};
+use crate::helpers::{type_text, ESC_KEY, I_KEY};
+use editor::diagnostic::{Pos, Severity};
+
+#[test]
+fn server_diagnostics_should_be_shown() {
+    let project = Project::new("let a\n// FIXME: b\n  TODO\n");
+    let (mut app, _) = project.app();
+
+    round_trip(&mut app);
+
+    let diagnostics = app.diagnostics().sorted();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].start, Pos::new(1, 3));
+    assert_eq!(diagnostics[0].end, Pos::new(1, 8));
+    assert_eq!(&*diagnostics[0].message, "FIXME left in");
+    assert_eq!(diagnostics[0].source.as_deref(), Some("fake"));
+    assert_eq!(diagnostics[1].severity, Severity::Warning);
+    assert_eq!(diagnostics[1].start, Pos::new(2, 2));
+}
+
+#[test]
+fn edits_should_update_diagnostics() {
+    let project = Project::new("let a\n");
+    let (mut app, mut handler) = project.app();
+    round_trip(&mut app);
+    assert!(app.diagnostics().is_empty());
+
+    handler.handle_key_event(I_KEY, &mut app);
+    type_text("TODO ", &mut app, &mut handler);
+    handler.handle_key_event(ESC_KEY, &mut app);
+    round_trip(&mut app);
+
+    let diagnostics = app.diagnostics().sorted();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].start, Pos::new(0, 0));
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+}
@@ -0,0 +1,26 @@
+use editor::lsp::{read_message, write_message, Error};
+use serde_json::json;
+
+#[test]
+fn message_should_round_trip() {
+    let message = json!({ "id": 1, "name": "a \"b\"\n", "list": [true, null] });
+    let mut framed = vec![];
+
+    write_message(&mut framed, &message).unwrap();
+
+    assert_eq!(read_message(&mut &framed[..]).unwrap(), Some(message))
+}
+
+#[test]
+fn end_of_stream_should_be_no_message() {
+    assert_eq!(read_message(&mut &b""[..]).unwrap(), None)
+}
+
+#[test]
+fn content_that_isnt_json_should_be_an_error() {
+    let framed = b"Content-Length: 5\r\n\r\n{\"a\":";
+
+    let result = read_message(&mut &framed[..]);
+
+    assert!(matches!(result, Err(Error::Json(_))))
+}
@@ -0,0 +1,118 @@
+use crate::helpers::wait_while;
+use editor::{app::App, buffer::Buffer, config::Config, handler::Handler};
+use serde_json::Value;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+use tempfile::TempDir;
+
+mod completion;
+mod diagnostics;
+mod messages;
+mod requests;
+mod sync;
+
+/// A project dir with `main.fake` open, served by the fake language server.
+struct Project {
+    dir: TempDir,
+}
+
+impl Project {
+    fn new(contents: &str) -> Self {
+        let project = Self {
+            dir: TempDir::new().unwrap(),
+        };
+        project.write("main.fake", contents);
+
+        project
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    fn write(&self, name: &str, contents: &str) {
+        fs::write(self.path(name), contents).unwrap();
+    }
+
+    /// App with the server started, passing it `args` on top of the log.
+    fn app_with_args(&self, args: &[&str]) -> (App, Handler) {
+        let mut all_args = vec!["--log", self.log_path_str()];
+        all_args.extend(args);
+        let args: Vec<String> =
+            all_args.iter().map(|arg| format!("{:?}", arg)).collect();
+        let (config, errors) = Config::from_toml(&format!(
+            "[lsp.fake]\ncommand = {:?}\nargs = [{}]\nextensions = [\"fake\"]",
+            fake_lsp(),
+            args.join(", "),
+        ));
+        assert!(errors.is_empty());
+
+        let path = self.path("main.fake").to_string_lossy().into_owned();
+        let mut app = App::new(Buffer::from_file(path).unwrap());
+        app.configure(config).unwrap();
+        app.start_lsp().unwrap();
+
+        (app, Handler::new())
+    }
+
+    fn app(&self) -> (App, Handler) {
+        self.app_with_args(&[])
+    }
+
+    fn log_path_str(&self) -> &'static str {
+        let path = self.path("messages.log").to_string_lossy().into_owned();
+        Box::leak(path.into_boxed_str())
+    }
+
+    /// Every message the server received, one per line.
+    fn log(&self) -> String {
+        fs::read_to_string(self.path("messages.log")).unwrap_or_default()
+    }
+
+    /// `log`, each line read back as JSON.
+    fn messages(&self) -> Vec<Value> {
+        self.log()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+/// The fake language server, built the first time it's needed, with the
+/// profile the tests were.
+fn fake_lsp() -> &'static Path {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    PATH.get_or_init(|| {
+        // Tests run from `<target>/<profile>/deps`.
+        let exe = env::current_exe().unwrap();
+        let profile_dir = exe.parent().and_then(Path::parent).unwrap();
+        let profile = match profile_dir.file_name().unwrap().to_str() {
+            Some("debug") => "dev",
+            profile => profile.unwrap(),
+        };
+        let status = Command::new(env!("CARGO"))
+            .args(["build", "--quiet", "--example", "fake-lsp"])
+            .args(["--profile", profile])
+            .status()
+            .unwrap();
+        assert!(status.success(), "building fake-lsp failed");
+
+        profile_dir.join("examples").join("fake-lsp")
+    })
+}
+
+/// Make sure the server has handled everything sent so far, by waiting on the
+/// answer to a request sent after it.
+fn round_trip(app: &mut App) {
+    app.hover().unwrap();
+    wait_for_server(app);
+}
+
+/// Wait for the server to answer every request, acting on the answers.
+fn wait_for_server(app: &mut App) {
+    wait_while(app, App::lsp_waiting, App::update_lsp);
+}
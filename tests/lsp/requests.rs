@@ -0,0 +1,140 @@
+use super::{round_trip, wait_for_server, Project};
+use crate::helpers::{
+    draw, key, press, type_text, BACKSPACE_KEY, DOWN_KEY, ENTER_KEY, ESC_KEY,
+    G_KEY, SPACE_KEY,
+};
+use editor::app::App;
+use std::fs;
+
+const SOURCE: &str = "fn greet\n\ncall greet\ngreet again  \n";
+
+fn text(app: &App) -> String {
+    (0..app.buffer.len_lines())
+        .map(|idx| app.buffer.line(idx).unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn definition_should_move_cursor() {
+    let project = Project::new(SOURCE);
+    let (mut app, mut handler) = project.app();
+    app.buffer.cursor = (6, 2).into();
+
+    press(&[G_KEY, key('d')], &mut app, &mut handler);
+    wait_for_server(&mut app);
+
+    assert_eq!((app.buffer.cursor.x, app.buffer.cursor.y), (3, 0))
+}
+
+#[test]
+fn definition_in_another_file_should_open_it() {
+    let project = Project::new(SOURCE);
+    project.write("other.fake", "use
+  fn greet
+");
+    let other = project.path("other.fake").to_string_lossy().into_owned();
+    let (mut app, mut handler) = project.app_with_args(&["--also", &other]);
+    app.buffer.cursor = (6, 2).into();
+
+    press(&[G_KEY, key('d')], &mut app, &mut handler);
+    wait_for_server(&mut app);
+
+    assert!(app.buffer.file_name().ends_with("other.fake"));
+    assert_eq!((app.buffer.cursor.x, app.buffer.cursor.y), (5, 1))
+}
+
+#[test]
+fn hover_should_show_popup() {
+    let project = Project::new(SOURCE);
+    let (mut app, mut handler) = project.app();
+
+    press(&[key('K')], &mut app, &mut handler);
+    wait_for_server(&mut app);
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let screen: String = (0..buffer.area.height)
+        .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+        .map(|(x, y)| buffer.get(x, y).symbol())
+        .collect();
+    assert!(screen.contains("hover"));
+    assert!(screen.contains("line: fn greet"));
+}
+
+#[test]
+fn popup_should_close_on_next_key() {
+    let project = Project::new(SOURCE);
+    let (mut app, mut handler) = project.app();
+    press(&[key('K')], &mut app, &mut handler);
+    wait_for_server(&mut app);
+
+    press(&[DOWN_KEY], &mut app, &mut handler);
+
+    assert!(app.popup().is_none())
+}
+
+#[test]
+fn references_should_list_locations() {
+    let project = Project::new(SOURCE);
+    let (mut app, mut handler) = project.app();
+    app.buffer.cursor = (3, 0).into();
+
+    press(&[G_KEY, key('r')], &mut app, &mut handler);
+    wait_for_server(&mut app);
+
+    let popup = app.popup().unwrap();
+    assert_eq!(&*popup.title, " references (3) ");
+    assert!(popup.lines[0].ends_with("main.fake:1:4"));
+    assert!(popup.lines[2].ends_with("main.fake:4:1"));
+}
+
+#[test]
+fn rename_should_edit_buffer_and_other_files() {
+    let project = Project::new(SOURCE);
+    project.write("other.fake", "use greet\n");
+    let other = project.path("other.fake").to_string_lossy().into_owned();
+    let (mut app, mut handler) = project.app_with_args(&["--also", &other]);
+    app.buffer.cursor = (3, 0).into();
+
+    press(&[SPACE_KEY, key('l'), key('r')], &mut app, &mut handler);
+    assert_eq!(app.prompt().unwrap().input, "greet");
+    press(&[BACKSPACE_KEY; "greet".len()], &mut app, &mut handler);
+    type_text("hi", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+    wait_for_server(&mut app);
+
+    assert!(app.prompt().is_none());
+    assert_eq!(text(&app), "fn hi\n\ncall hi\nhi again  ");
+    assert_eq!(fs::read_to_string(other).unwrap(), "use hi\n");
+}
+
+#[test]
+fn escape_should_cancel_prompt() {
+    let project = Project::new(SOURCE);
+    let (mut app, mut handler) = project.app();
+    app.buffer.cursor = (3, 0).into();
+
+    press(
+        &[SPACE_KEY, key('l'), key('r'), ESC_KEY],
+        &mut app,
+        &mut handler,
+    );
+    wait_for_server(&mut app);
+
+    assert!(app.prompt().is_none());
+    assert_eq!(text(&app), SOURCE.trim_end_matches('\n'));
+}
+
+#[test]
+fn format_should_apply_edits() {
+    let project = Project::new(SOURCE);
+    let (mut app, mut handler) = project.app();
+
+    press(&[SPACE_KEY, key('l'), key('f')], &mut app, &mut handler);
+    wait_for_server(&mut app);
+
+    assert_eq!(text(&app), "fn greet\n\ncall greet\ngreet again");
+    // The server is told about its own edits too.
+    round_trip(&mut app);
+    assert!(project.log().contains("didChange"));
+}
@@ -0,0 +1,173 @@
+use super::{round_trip, wait_for_server, Project};
+use crate::helpers::{A_KEY, DOWN_KEY, ESC_KEY, I_KEY};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use editor::{
+    app::App,
+    buffer::Buffer,
+    config::Config,
+    window::{Direction, Split},
+};
+use serde_json::json;
+
+const CAP_K_KEY: KeyEvent =
+    KeyEvent::new(KeyCode::Char('K'), KeyModifiers::NONE);
+
+#[test]
+fn open_should_send_buffer_contents() {
+    let project = Project::new("fn main\n");
+    let (mut app, _) = project.app();
+
+    round_trip(&mut app);
+
+    let log = project.log();
+    assert!(log.contains(r#""method":"textDocument/didOpen""#));
+    assert!(log.contains(r#""languageId":"fake""#));
+    assert!(log.contains(r#""text":"fn main\n""#));
+}
+
+#[test]
+fn edits_should_be_sent_as_ranges() {
+    let project = Project::new("one\ntwo\n");
+    let (mut app, mut handler) = project.app();
+
+    for key in [DOWN_KEY, I_KEY, A_KEY, ESC_KEY] {
+        handler.handle_key_event(key, &mut app);
+    }
+    round_trip(&mut app);
+
+    let change = project
+        .messages()
+        .into_iter()
+        .find(|message| message["method"] == "textDocument/didChange")
+        .unwrap();
+    assert_eq!(
+        change["params"]["contentChanges"],
+        json!([{
+            "range": {
+                "start": { "line": 1, "character": 0 },
+                "end": { "line": 1, "character": 0 },
+            },
+            "text": "a",
+        }])
+    );
+}
+
+#[test]
+fn server_should_see_edited_text() {
+    let project = Project::new("one\ntwo\n");
+    let (mut app, mut handler) = project.app();
+    for key in [DOWN_KEY, I_KEY, A_KEY, ESC_KEY] {
+        handler.handle_key_event(key, &mut app);
+    }
+    app.buffer.delete_line(0).unwrap();
+    app.buffer.cursor = (0, 0).into();
+
+    handler.handle_key_event(CAP_K_KEY, &mut app);
+    wait_for_server(&mut app);
+
+    let popup = app.popup().unwrap();
+    assert_eq!(popup.lines, ["`atwo`", "line: atwo"]);
+}
+
+#[test]
+fn server_messages_should_be_notified() {
+    let project = Project::new("one\n");
+    let (mut app, _) = project.app();
+
+    round_trip(&mut app);
+
+    assert!(app
+        .notifs()
+        .iter()
+        .any(|notif| notif.to_string().ends_with("fake-lsp ready")));
+}
+
+#[test]
+fn server_requests_should_be_answered() {
+    let project = Project::new("one\n");
+    let (mut app, _) = project.app();
+
+    // The request comes in during the first, the second sees the answer in.
+    round_trip(&mut app);
+    round_trip(&mut app);
+
+    assert!(project.messages().contains(&json!({
+        "jsonrpc": "2.0",
+        "id": "progress",
+        "result": null,
+    })));
+}
+
+#[test]
+fn file_without_server_should_warn() {
+    let project = Project::new("one\n");
+    project.write("notes.txt", "one\n");
+    let (config, _) = Config::from_toml(
+        "[lsp.fake]\ncommand = \"fake-lsp\"\nextensions = [\"fake\"]",
+    );
+    let path = project.path("notes.txt").to_string_lossy().into_owned();
+    let mut app = App::new(Buffer::from_file(path).unwrap());
+    app.configure(config).unwrap();
+
+    app.start_lsp().unwrap();
+    let err = app.hover().unwrap_err();
+
+    assert_eq!(err.to_string(), "No language server")
+}
+
+#[test]
+fn missing_server_should_fail_to_start() {
+    let project = Project::new("one\n");
+    let (config, _) = Config::from_toml(
+        "[lsp.fake]\ncommand = \"no-such-server\"\nextensions = [\"fake\"]",
+    );
+    let path = project.path("main.fake").to_string_lossy().into_owned();
+    let mut app = App::new(Buffer::from_file(path).unwrap());
+    app.configure(config).unwrap();
+
+    let err = app.start_lsp().unwrap_err();
+
+    assert!(err.to_string().starts_with("LSP error: could not start"))
+}
+
+#[test]
+fn opening_should_not_wait_for_the_server() {
+    let project = Project::new("one\n");
+    let (mut app, _) = project.app();
+
+    assert!(app.lsp_waiting());
+    round_trip(&mut app);
+
+    assert!(!app.lsp_waiting());
+    let methods: Vec<_> = project
+        .messages()
+        .into_iter()
+        .map(|message| message["method"].clone())
+        .collect();
+    assert_eq!(
+        methods[..3],
+        ["initialize", "initialized", "textDocument/didOpen"]
+    );
+}
+
+#[test]
+fn switching_windows_should_keep_the_server_and_its_diagnostics() {
+    let project = Project::new("// FIXME\n");
+    project.write("other.fake", "two\n");
+    let (mut app, _) = project.app();
+    round_trip(&mut app);
+    app.split_window(Split::Vertical);
+    app.open_file(&project.path("other.fake")).unwrap();
+    round_trip(&mut app);
+
+    app.focus_window(Direction::Left).unwrap();
+
+    assert_eq!(app.diagnostics().len(), 1);
+    round_trip(&mut app);
+    let started = project
+        .messages()
+        .iter()
+        .filter(|message| message["method"] == "initialize")
+        .count();
+    assert_eq!(started, 2);
+}
@@ -1,5 +1,24 @@
+mod completion;
+mod config;
+mod diagnostics;
+mod events;
+mod explorer;
+mod git;
+mod grep;
 mod helpers;
+mod history;
 mod io;
+mod keymap;
+mod keystrokes;
+mod line_numbers;
+mod logger;
+mod lsp;
 mod mode_change;
 mod movements;
+mod notifications;
+mod picker;
+mod signs;
+mod status_line;
 mod text_change;
+mod theme;
+mod window;
@@ -0,0 +1,97 @@
+use super::warn;
+use crate::helpers::{
+    app_default, draw, key, press, type_text, ESC_KEY, SPACE_KEY,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use editor::{app::App, handler::Handler};
+
+fn open_history(app: &mut App, handler: &mut Handler) {
+    handler.handle_key_event(SPACE_KEY, app);
+    type_text("n", app, handler);
+}
+
+fn screen(app: &App, handler: &Handler) -> String {
+    let terminal = draw(app, handler);
+    let buffer = terminal.backend().buffer();
+    (0..16)
+        .map(|y| {
+            (0..40)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn notified() -> (App, Handler) {
+    let (mut app, handler) = app_default();
+    warn(&mut app, "already at top");
+    warn(&mut app, "no more hunks");
+    warn(&mut app, "already at bottom");
+
+    (app, handler)
+}
+
+#[test]
+fn history_should_list_every_notification_newest_first() {
+    let (mut app, mut handler) = notified();
+
+    open_history(&mut app, &mut handler);
+
+    let texts: Vec<&str> = app
+        .history_matches()
+        .iter()
+        .map(|notif| notif.text())
+        .collect();
+    assert_eq!(
+        texts,
+        ["already at bottom", "no more hunks", "already at top"]
+    );
+    let screen = screen(&app, &handler);
+    assert!(screen.contains("notifications 3"));
+    assert!(screen.contains("no more hunks"));
+}
+
+#[test]
+fn typing_should_filter_the_history() {
+    let (mut app, mut handler) = notified();
+
+    open_history(&mut app, &mut handler);
+    for char in "ALREADY".chars() {
+        press(&[key(char)], &mut app, &mut handler);
+    }
+
+    let texts: Vec<&str> = app
+        .history_matches()
+        .iter()
+        .map(|notif| notif.text())
+        .collect();
+    assert_eq!(texts, ["already at bottom", "already at top"]);
+    assert!(!screen(&app, &handler).contains("no more hunks"));
+}
+
+#[test]
+fn clearing_should_empty_the_history() {
+    let (mut app, mut handler) = notified();
+
+    open_history(&mut app, &mut handler);
+    let ctrl_l = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL);
+    handler.handle_key_event(ctrl_l, &mut app);
+
+    assert!(app.notifs().is_empty());
+    assert!(app.current_notif().is_none());
+    assert!(app.history().is_some());
+}
+
+#[test]
+fn escape_should_close_the_history_and_keep_the_cursor() {
+    let (mut app, mut handler) = notified();
+    let cursor = app.buffer.cursor;
+
+    open_history(&mut app, &mut handler);
+    type_text("j", &mut app, &mut handler);
+    handler.handle_key_event(ESC_KEY, &mut app);
+
+    assert!(app.history().is_none());
+    assert_eq!(app.buffer.cursor, cursor);
+}
@@ -0,0 +1,81 @@
+use crate::helpers::{app_default, draw};
+use editor::app::{App, Notification, NotificationType};
+use ratatui::style::Color;
+use std::{thread, time::Duration};
+
+mod history;
+
+fn warn(app: &mut App, text: &str) {
+    app.push_notif(Notification::new(NotificationType::Warning, text));
+}
+
+/// The last row of the screen, where the status line is.
+fn status_line(app: &App, handler: &editor::handler::Handler) -> String {
+    let terminal = draw(app, handler);
+    let buffer = terminal.backend().buffer();
+    (0..40).map(|x| buffer.get(x, 15).symbol()).collect()
+}
+
+#[test]
+fn repeated_notifications_should_be_counted_as_one() {
+    let (mut app, handler) = app_default();
+
+    warn(&mut app, "already at top");
+    warn(&mut app, "already at top");
+    warn(&mut app, "already at top");
+
+    assert_eq!(app.notifs().len(), 1);
+    assert_eq!(app.current_notif().unwrap().count(), 3);
+    assert!(status_line(&app, &handler).contains("already at top ×3"));
+}
+
+#[test]
+fn repeats_should_be_kept_apart_when_dedup_is_off() {
+    let (mut app, _) = app_default();
+    let mut config = app.config().clone();
+    config.notifications.dedup = false;
+    app.configure(config).unwrap();
+
+    warn(&mut app, "already at top");
+    warn(&mut app, "already at top");
+
+    assert_eq!(app.notifs().len(), 2);
+    assert_eq!(app.current_notif().unwrap().count(), 1);
+}
+
+#[test]
+fn notifications_should_leave_the_status_line_after_the_timeout() {
+    let (mut app, handler) = app_default();
+    let mut config = app.config().clone();
+    config.notifications.timeout = 20;
+    app.configure(config).unwrap();
+
+    warn(&mut app, "already at top");
+    assert!(status_line(&app, &handler).contains("already at top"));
+    thread::sleep(Duration::from_millis(40));
+
+    assert!(app.current_notif().is_none());
+    assert!(!status_line(&app, &handler).contains("already at top"));
+    assert_eq!(app.notifs().len(), 1);
+}
+
+#[test]
+fn the_icon_should_be_colored_by_severity() {
+    let (mut app, handler) = app_default();
+
+    warn(&mut app, "already at top");
+
+    let terminal = draw(&app, &handler);
+    let buffer = terminal.backend().buffer();
+    let icon = (0..40)
+        .map(|x| buffer.get(x, 15))
+        .find(|cell| cell.symbol() == app.current_notif().unwrap().icon())
+        .unwrap();
+    assert_eq!(
+        icon.fg,
+        app.theme()
+            .notification(NotificationType::Warning)
+            .fg
+            .unwrap_or(Color::Reset)
+    );
+}
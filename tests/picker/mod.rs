@@ -0,0 +1,44 @@
+use crate::helpers::{self, wait_while};
+use editor::{app::App, handler::Handler};
+use std::path::Path;
+use tempfile::TempDir;
+
+mod open;
+mod rank;
+mod render;
+mod walk;
+
+/// A project of a few files, one of them ignored, with `README.md` open and
+/// the picker walking it.
+fn project() -> (TempDir, App, Handler) {
+    let (dir, mut app, handler) = helpers::project(&[
+        ("README.md", "# project\n"),
+        ("src/main.rs", "fn main() {\n    run();\n}\n"),
+        ("src/lib.rs", "pub fn run() {}\n"),
+        ("src/ui/widgets.rs", "struct Widget;\n"),
+        ("target/debug/out", "built\n"),
+        (".gitignore", "target/\n"),
+    ]);
+    app.open_picker(dir.path());
+
+    (dir, app, handler)
+}
+
+/// Pick up the files found until the walk is done.
+fn wait_for_walk(app: &mut App) {
+    wait_while(
+        app,
+        |app| app.picker().is_some_and(|picker| picker.walking()),
+        App::update_background,
+    );
+}
+
+fn matches(app: &App) -> Vec<String> {
+    let picker = app.picker().unwrap();
+
+    picker.matches().map(String::from).collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.to_string_lossy().into()
+}
@@ -0,0 +1,69 @@
+use super::{file_name, project, wait_for_walk};
+use crate::helpers::{type_text, ENTER_KEY, ESC_KEY, I_KEY};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[test]
+fn enter_should_open_the_selected_file() {
+    let (dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+    type_text("lib", &mut app, &mut handler);
+
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(app.picker().is_none());
+    assert_eq!(
+        app.buffer.file_name(),
+        file_name(&dir.path().join("src/lib.rs"))
+    );
+    assert_eq!(app.buffer.contents(), "pub fn run() {}\n");
+    assert_eq!(app.buffers().len(), 1);
+}
+
+#[test]
+fn reopening_a_file_should_keep_its_edits() {
+    let (dir, mut app, mut handler) = project();
+    let x_key = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE);
+    for key in [ESC_KEY, I_KEY, x_key, ESC_KEY] {
+        handler.handle_key_event(key, &mut app);
+    }
+    app.open_picker(dir.path());
+    wait_for_walk(&mut app);
+    type_text("lib", &mut app, &mut handler);
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    app.open_file(&dir.path().join("README.md")).unwrap();
+
+    assert_eq!(app.buffer.contents(), "x# project\n");
+    assert_eq!(app.buffers().len(), 1);
+    assert!(app.buffers()[0].file_name().ends_with("lib.rs"));
+}
+
+#[test]
+fn esc_should_close_the_picker() {
+    let (dir, mut app, mut handler) = project();
+
+    handler.handle_key_event(ESC_KEY, &mut app);
+
+    assert!(app.picker().is_none());
+    assert_eq!(
+        app.buffer.file_name(),
+        file_name(&dir.path().join("README.md"))
+    );
+}
+
+#[test]
+fn enter_without_match_should_warn() {
+    let (_dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+    type_text("zzz", &mut app, &mut handler);
+
+    handler.handle_key_event(ENTER_KEY, &mut app);
+
+    assert!(app.buffers().is_empty());
+    assert!(app
+        .notifs()
+        .last()
+        .unwrap()
+        .to_string()
+        .ends_with("no file selected"));
+}
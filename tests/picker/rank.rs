@@ -0,0 +1,49 @@
+use super::{matches, project, wait_for_walk};
+use crate::helpers::{type_text, BACKSPACE_KEY, DOWN_KEY};
+
+#[test]
+fn query_should_rank_best_match_first() {
+    let (_dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+
+    type_text("lib", &mut app, &mut handler);
+
+    assert_eq!(matches(&app), ["src/lib.rs"]);
+}
+
+#[test]
+fn query_should_match_fuzzily() {
+    let (_dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+
+    type_text("srs", &mut app, &mut handler);
+
+    assert_eq!(
+        matches(&app),
+        ["src/lib.rs", "src/main.rs", "src/ui/widgets.rs"]
+    );
+}
+
+#[test]
+fn backspace_should_widen_the_matches() {
+    let (_dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+    type_text("libz", &mut app, &mut handler);
+    assert!(matches(&app).is_empty());
+
+    handler.handle_key_event(BACKSPACE_KEY, &mut app);
+
+    assert_eq!(app.picker().unwrap().query(), "lib");
+    assert_eq!(matches(&app), ["src/lib.rs"]);
+}
+
+#[test]
+fn down_should_move_the_selection() {
+    let (_dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+    type_text("rs", &mut app, &mut handler);
+
+    handler.handle_key_event(DOWN_KEY, &mut app);
+
+    assert_eq!(app.picker().unwrap().selected(), Some("src/main.rs"));
+}
@@ -0,0 +1,44 @@
+use super::{project, wait_for_walk};
+use crate::helpers::{draw, type_text};
+
+fn screen(
+    app: &editor::app::App,
+    handler: &editor::handler::Handler,
+) -> Vec<String> {
+    let terminal = draw(app, handler);
+    let buffer = terminal.backend().buffer();
+
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn picker_should_show_the_query_and_matches() {
+    let (_dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+    type_text("main", &mut app, &mut handler);
+
+    let screen = screen(&app, &handler);
+
+    assert!(screen[1].contains(" files 1/4 "));
+    assert!(screen[2].contains("│> main "));
+    assert!(screen[3].contains("│src/main.rs"));
+}
+
+#[test]
+fn picker_should_preview_the_selected_file() {
+    let (_dir, mut app, mut handler) = project();
+    wait_for_walk(&mut app);
+    type_text("main", &mut app, &mut handler);
+
+    let screen = screen(&app, &handler);
+
+    assert!(screen[2].ends_with("│src/main.rs     │  "));
+    assert!(screen[3].contains("│fn main() {"));
+    assert!(screen[4].contains("│    run();"));
+}
@@ -0,0 +1,41 @@
+use super::{matches, project, wait_for_walk};
+
+#[test]
+fn walk_should_find_every_file() {
+    let (_dir, mut app, _) = project();
+
+    wait_for_walk(&mut app);
+
+    let mut found = matches(&app);
+    found.sort();
+    assert_eq!(
+        found,
+        [
+            "README.md",
+            "src/lib.rs",
+            "src/main.rs",
+            "src/ui/widgets.rs"
+        ]
+    );
+}
+
+#[test]
+fn walk_should_skip_ignored_and_hidden_files() {
+    let (_dir, mut app, _) = project();
+
+    wait_for_walk(&mut app);
+
+    let found = matches(&app);
+    assert!(!found.iter().any(|path| path.starts_with("target")));
+    assert!(!found.iter().any(|path| path.starts_with('.')));
+}
+
+#[test]
+fn walk_should_stop_when_done() {
+    let (_dir, mut app, _) = project();
+
+    wait_for_walk(&mut app);
+
+    assert!(!app.picker().unwrap().walking());
+    assert_eq!(app.picker().unwrap().len_files(), 4);
+}
@@ -0,0 +1,84 @@
+use crate::helpers::{
+    app_default, draw, B_KEY, CURSOR_ROW, DOWN_KEY, M_KEY, SPACE_KEY,
+};
+use editor::{app::App, config::Config, handler::Handler, sign::SignKind};
+
+fn sign_cell(app: &App, handler: &Handler) -> String {
+    let terminal = draw(app, handler);
+
+    terminal
+        .backend()
+        .buffer()
+        .get(0, CURSOR_ROW)
+        .symbol()
+        .to_string()
+}
+
+#[test]
+fn m_should_mark_cursor_line() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(M_KEY, &mut app);
+
+    assert!(app.markers().contains(0, SignKind::Mark));
+    assert_eq!(sign_cell(&app, &handler), "◆")
+}
+
+#[test]
+fn m_twice_should_unmark_cursor_line() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(M_KEY, &mut app);
+    handler.handle_key_event(M_KEY, &mut app);
+
+    assert!(!app.markers().contains(0, SignKind::Mark));
+    assert_eq!(sign_cell(&app, &handler), " ")
+}
+
+#[test]
+fn breakpoint_should_win_over_mark() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(M_KEY, &mut app);
+    handler.handle_key_event(SPACE_KEY, &mut app);
+    handler.handle_key_event(B_KEY, &mut app);
+
+    assert_eq!(sign_cell(&app, &handler), "◉")
+}
+
+#[test]
+fn sign_column_width_should_not_depend_on_signs() {
+    let (mut app, mut handler) = app_default();
+    let text_col = |app: &App, handler: &Handler| {
+        let terminal = draw(app, handler);
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.width)
+            .position(|x| buffer.get(x, CURSOR_ROW).symbol() == "┆")
+            .unwrap()
+    };
+    let before = text_col(&app, &handler);
+
+    handler.handle_key_event(M_KEY, &mut app);
+    handler.handle_key_event(DOWN_KEY, &mut app);
+
+    assert_eq!(text_col(&app, &handler), before)
+}
+
+#[test]
+fn sign_column_can_be_turned_off() {
+    let (mut app, mut handler) = app_default();
+    let (config, _) = Config::from_toml("[layout]\nsign_column = false");
+    app.configure(config).unwrap();
+
+    handler.handle_key_event(M_KEY, &mut app);
+
+    assert_eq!(sign_cell(&app, &handler), " ");
+    assert_eq!(
+        draw(&app, &handler)
+            .backend()
+            .buffer()
+            .get(1, CURSOR_ROW)
+            .symbol(),
+        "1"
+    )
+}
@@ -0,0 +1,40 @@
+use crate::helpers::{
+    app_default, press, DOWN_KEY, D_KEY, ENTER_KEY, I_KEY, M_KEY, UP_KEY,
+};
+use editor::sign::SignKind;
+
+#[test]
+fn inserting_a_line_above_a_mark_should_move_it_down() {
+    let (mut app, mut handler) = app_default();
+    press(&[DOWN_KEY, M_KEY, UP_KEY], &mut app, &mut handler);
+
+    press(&[I_KEY, ENTER_KEY], &mut app, &mut handler);
+
+    assert!(app.markers().contains(2, SignKind::Mark));
+    assert!(!app.markers().contains(1, SignKind::Mark));
+}
+
+#[test]
+fn deleting_a_line_above_a_mark_should_move_it_up() {
+    let (mut app, mut handler) = app_default();
+    press(
+        &[DOWN_KEY, DOWN_KEY, M_KEY, UP_KEY, UP_KEY],
+        &mut app,
+        &mut handler,
+    );
+
+    press(&[D_KEY, D_KEY], &mut app, &mut handler);
+
+    assert!(app.markers().contains(1, SignKind::Mark));
+    assert!(!app.markers().contains(2, SignKind::Mark));
+}
+
+#[test]
+fn deleting_a_marked_line_should_drop_its_mark() {
+    let (mut app, mut handler) = app_default();
+    press(&[M_KEY], &mut app, &mut handler);
+
+    press(&[D_KEY, D_KEY], &mut app, &mut handler);
+
+    assert!(!app.markers().contains(0, SignKind::Mark));
+}
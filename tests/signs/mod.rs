@@ -0,0 +1,3 @@
+mod column;
+mod lines;
+mod priority;
@@ -0,0 +1,38 @@
+use editor::sign::{Markers, SignColumn, SignKind, SignProvider};
+
+/// A provider with a sign on every line.
+struct EveryLine(SignKind);
+
+impl SignProvider for EveryLine {
+    fn sign(&self, _line_idx: usize) -> Option<SignKind> {
+        Some(self.0)
+    }
+}
+
+#[test]
+fn highest_priority_sign_should_be_shown() {
+    let git = EveryLine(SignKind::GitChanged);
+    let diagnostics = EveryLine(SignKind::DiagnosticError);
+    let signs = SignColumn::new().with(&git).with(&diagnostics);
+
+    assert_eq!(signs.sign(3), Some(SignKind::DiagnosticError))
+}
+
+#[test]
+fn provider_order_should_not_matter() {
+    let git = EveryLine(SignKind::GitChanged);
+    let diagnostics = EveryLine(SignKind::DiagnosticHint);
+    let signs = SignColumn::new().with(&diagnostics).with(&git);
+
+    assert_eq!(signs.sign(0), Some(SignKind::DiagnosticHint))
+}
+
+#[test]
+fn lines_without_signs_should_be_empty() {
+    let mut markers = Markers::default();
+    markers.toggle(2, SignKind::Mark);
+    let signs = SignColumn::new().with(&markers);
+
+    assert_eq!(signs.sign(1), None);
+    assert_eq!(signs.sign(2), Some(SignKind::Mark))
+}
@@ -0,0 +1,44 @@
+use super::{status_line, with_segments};
+use crate::helpers::{app_default, draw};
+use editor::config::Segment;
+
+#[test]
+fn least_useful_segments_should_be_dropped_first() {
+    let (app, handler) = app_default();
+
+    let line = status_line(&app, &handler, 60);
+
+    assert!(line.starts_with(" Normal  …"));
+    assert!(line.contains("basic.txt"));
+    assert!(line.ends_with(" 1:1 "));
+    assert!(!line.contains("utf-8"));
+    assert!(!line.contains("14%"));
+}
+
+#[test]
+fn long_file_names_should_be_shortened_from_the_left() {
+    let (mut app, handler) = app_default();
+    with_segments(&mut app, &[Segment::Mode, Segment::File], &[]);
+
+    let line = status_line(&app, &handler, 50);
+
+    // Shortened by as much as the message needs and no more.
+    assert!(line.starts_with(" Normal  …ock_files/basic.txt "));
+}
+
+#[test]
+fn segments_should_make_room_for_a_long_message() {
+    let (mut app, handler) = app_default();
+    app.push_notif(editor::app::Notification::new(
+        editor::app::NotificationType::Info,
+        "a notification long enough to push the rest",
+    ));
+
+    let terminal = draw(&app, &handler);
+    let buffer = terminal.backend().buffer();
+    let line: String = (0..40).map(|x| buffer.get(x, 15).symbol()).collect();
+
+    assert!(line.starts_with(" Normal "));
+    assert!(line.contains("a notification long"));
+    assert!(!line.contains("1:1"));
+}
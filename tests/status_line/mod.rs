@@ -0,0 +1,115 @@
+use crate::helpers::{app_default, I_KEY};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use editor::{
+    app::App,
+    buffer::Buffer,
+    config::{Config, Segment},
+    diagnostic::{Diagnostic, Pos, Severity},
+    handler::Handler,
+    ui,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use std::io::Write;
+
+mod fit;
+
+/// The status line of the app drawn `width` columns wide.
+fn status_line(app: &App, handler: &Handler, width: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, 16)).unwrap();
+    terminal
+        .draw(|frame| ui::render(app, handler, frame))
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..width).map(|x| buffer.get(x, 15).symbol()).collect()
+}
+
+fn with_segments(app: &mut App, left: &[Segment], right: &[Segment]) {
+    let mut config = Config::default();
+    config.status_line.left = left.to_vec();
+    config.status_line.right = right.to_vec();
+    app.configure(config).unwrap();
+}
+
+#[test]
+fn default_segments_should_show_the_file_and_position() {
+    let (app, handler) = app_default();
+
+    let line = status_line(&app, &handler, 100);
+
+    assert!(line.starts_with(" Normal  tests/helpers/mock_files/basic.txt "));
+    assert!(line.ends_with(" txt  utf-8  LF  1:1  14% "));
+}
+
+#[test]
+fn edited_file_should_be_marked_until_saved() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(file.as_file(), "text").unwrap();
+    let path = file.path().to_str().unwrap().to_string();
+    let mut app = App::new(Buffer::from_file(path.clone()).unwrap());
+    let mut handler = Handler::new();
+    with_segments(&mut app, &[Segment::File], &[]);
+    assert!(!status_line(&app, &handler, 100).contains("[+]"));
+
+    handler.handle_key_event(I_KEY, &mut app);
+    let key = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE);
+    handler.handle_key_event(key, &mut app);
+    assert!(status_line(&app, &handler, 100).contains(&format!("{} [+]", path)));
+
+    app.save().unwrap();
+    assert!(!status_line(&app, &handler, 100).contains("[+]"));
+}
+
+#[test]
+fn configured_segments_should_be_shown_in_order() {
+    let (mut app, handler) = app_default();
+    with_segments(
+        &mut app,
+        &[Segment::Lines],
+        &[Segment::Percentage, Segment::Mode],
+    );
+
+    let line = status_line(&app, &handler, 60);
+
+    assert!(line.starts_with(" 7 lines "));
+    assert!(line.ends_with(" 14%  Normal "));
+    assert!(!line.contains("basic.txt"));
+}
+
+#[test]
+fn diagnostics_should_be_counted_by_severity() {
+    let (mut app, handler) = app_default();
+    with_segments(&mut app, &[], &[Segment::Diagnostics]);
+    let diagnostic = |line: usize, severity: Severity| Diagnostic {
+        start: Pos::new(line, 0),
+        end: Pos::new(line, 1),
+        severity,
+        message: "problem".into(),
+        source: None,
+    };
+    app.set_diagnostics(
+        "check",
+        vec![
+            diagnostic(1, Severity::Error),
+            diagnostic(2, Severity::Error),
+            diagnostic(3, Severity::Hint),
+        ],
+    );
+
+    let line = status_line(&app, &handler, 60);
+
+    let (error, _) = app.theme().sign(Severity::Error.sign());
+    let (hint, _) = app.theme().sign(Severity::Hint.sign());
+    assert!(line.ends_with(&format!(" {}2 {}1 ", error, hint)));
+}
+
+#[test]
+fn crlf_files_should_show_their_line_ending() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    write!(file.as_file(), "one\r\ntwo\r\n").unwrap();
+    let path = file.path().to_str().unwrap().to_string();
+    let mut app = App::new(Buffer::from_file(path).unwrap());
+    let handler = Handler::new();
+    with_segments(&mut app, &[], &[Segment::LineEnding]);
+
+    assert!(status_line(&app, &handler, 60).ends_with(" CRLF "));
+}
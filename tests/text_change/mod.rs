@@ -1,2 +1,4 @@
 mod delete;
 mod insert_text;
+mod paste;
+mod undo;
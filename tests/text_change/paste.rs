@@ -0,0 +1,85 @@
+use crate::helpers::{
+    app_default, app_in_insert_mode, app_in_insert_mode_with_empty_file, key,
+    press, type_text, DOWN_KEY, ESC_KEY,
+};
+use crossterm::event::Event;
+use editor::app::PromptAction;
+
+#[test]
+fn paste_should_insert_the_text_before_the_cursor() {
+    let (mut app, mut handler) = app_in_insert_mode();
+
+    handler.handle_event(Event::Paste("abc".into()), &mut app);
+
+    assert_eq!(
+        app.buffer.line(0).unwrap().to_string(),
+        "abcPo:¢7i¢or l¢  a d.am soll!c7tudin.\n"
+    );
+    assert_eq!(app.buffer.cursor, (3, 0).into());
+}
+
+#[test]
+fn paste_should_keep_line_breaks_and_indents_as_they_are() {
+    let (mut app, mut handler) = app_in_insert_mode_with_empty_file();
+
+    handler
+        .handle_event(Event::Paste("fn main() {\n    x\n}".into()), &mut app);
+
+    assert_eq!(app.buffer.contents(), "fn main() {\n    x\n}\n");
+    assert_eq!(app.buffer.cursor, (1, 2).into());
+}
+
+#[test]
+fn paste_should_turn_carriage_returns_into_line_breaks() {
+    let (mut app, mut handler) = app_in_insert_mode_with_empty_file();
+
+    handler.handle_event(Event::Paste("one\r\ntwo\rthree".into()), &mut app);
+
+    assert_eq!(app.buffer.contents(), "one\ntwo\nthree\n");
+}
+
+#[test]
+fn paste_should_be_one_edit() {
+    let (mut app, mut handler) = app_in_insert_mode();
+    app.buffer.track_changes(true);
+
+    handler.handle_event(Event::Paste("a\nb\nc".into()), &mut app);
+
+    assert_eq!(app.buffer.take_changes().len(), 1);
+}
+
+#[test]
+fn paste_should_be_undone_in_one_step_apart_from_what_was_typed() {
+    let (mut app, mut handler) = app_in_insert_mode_with_empty_file();
+    type_text("x", &mut app, &mut handler);
+    handler.handle_event(Event::Paste("yz\nw".into()), &mut app);
+    press(&[ESC_KEY, key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "x\n");
+
+    press(&[key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "");
+}
+
+#[test]
+fn paste_in_normal_mode_should_be_ignored() {
+    let (mut app, mut handler) = app_default();
+    handler.handle_key_event(DOWN_KEY, &mut app);
+
+    handler.handle_event(Event::Paste("dd".into()), &mut app);
+
+    assert!(!app.buffer.is_modified());
+    assert_eq!(app.buffer.cursor, (0, 1).into());
+}
+
+#[test]
+fn paste_into_a_prompt_should_drop_line_breaks() {
+    let (mut app, mut handler) = app_default();
+    app.open_prompt(PromptAction::Grep, String::new());
+
+    handler.handle_event(Event::Paste("foo\nbar".into()), &mut app);
+
+    assert_eq!(app.prompt().unwrap().input, "foobar");
+    assert!(!app.buffer.is_modified());
+}
@@ -0,0 +1,112 @@
+use crate::helpers::{ctrl, key, press, project, type_text, ESC_KEY};
+
+#[test]
+fn u_should_undo_what_was_typed_in_insert_mode_in_one_go() {
+    let (_dir, mut app, mut handler) = project(&[("file.txt", "ab\n")]);
+    press(&[key('i')], &mut app, &mut handler);
+    type_text("xy", &mut app, &mut handler);
+    press(&[ESC_KEY, key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "ab\n");
+    assert!(!app.buffer.is_modified());
+    assert_eq!(app.buffer.cursor, (0, 0).into());
+}
+
+#[test]
+fn ctrl_r_should_redo_what_was_undone() {
+    let (_dir, mut app, mut handler) = project(&[("file.txt", "ab\n")]);
+    press(&[key('i')], &mut app, &mut handler);
+    type_text("xy", &mut app, &mut handler);
+    press(&[ESC_KEY, key('u'), ctrl('r')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "xyab\n");
+    assert!(app.buffer.is_modified());
+}
+
+#[test]
+fn u_should_undo_each_command_on_its_own() {
+    let (_dir, mut app, mut handler) = project(&[("file.txt", "a\nb\nc\n")]);
+    press(
+        &[key('d'), key('d'), key('d'), key('d')],
+        &mut app,
+        &mut handler,
+    );
+    assert_eq!(app.buffer.contents(), "c\n");
+
+    press(&[key('u')], &mut app, &mut handler);
+    assert_eq!(app.buffer.contents(), "b\nc\n");
+    press(&[key('u')], &mut app, &mut handler);
+    assert_eq!(app.buffer.contents(), "a\nb\nc\n");
+}
+
+#[test]
+fn u_should_take_a_count() {
+    let (_dir, mut app, mut handler) = project(&[("file.txt", "a\nb\nc\n")]);
+    press(
+        &[key('d'), key('d'), key('d'), key('d')],
+        &mut app,
+        &mut handler,
+    );
+
+    press(&[key('2'), key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "a\nb\nc\n");
+}
+
+#[test]
+fn editing_after_undo_should_start_a_new_branch() {
+    let (_dir, mut app, mut handler) = project(&[("file.txt", "ab\n")]);
+    press(&[key('i'), key('x'), ESC_KEY], &mut app, &mut handler);
+    press(
+        &[key('u'), key('i'), key('y'), ESC_KEY],
+        &mut app,
+        &mut handler,
+    );
+
+    press(&[ctrl('r')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "yab\n");
+    assert!(app
+        .notifs()
+        .last()
+        .unwrap()
+        .to_string()
+        .ends_with("already at newest change"));
+}
+
+#[test]
+fn g_minus_and_g_plus_should_go_through_undone_branches_in_time() {
+    let (_dir, mut app, mut handler) = project(&[("file.txt", "ab\n")]);
+    press(&[key('i'), key('x'), ESC_KEY], &mut app, &mut handler);
+    press(
+        &[key('u'), key('i'), key('y'), ESC_KEY],
+        &mut app,
+        &mut handler,
+    );
+
+    press(&[key('g'), key('-')], &mut app, &mut handler);
+    assert_eq!(app.buffer.contents(), "xab\n");
+    press(&[key('g'), key('-')], &mut app, &mut handler);
+    assert_eq!(app.buffer.contents(), "ab\n");
+    press(
+        &[key('g'), key('+'), key('g'), key('+')],
+        &mut app,
+        &mut handler,
+    );
+    assert_eq!(app.buffer.contents(), "yab\n");
+}
+
+#[test]
+fn u_without_edits_should_notify() {
+    let (_dir, mut app, mut handler) = project(&[("file.txt", "ab\n")]);
+
+    press(&[key('u')], &mut app, &mut handler);
+
+    assert_eq!(app.buffer.contents(), "ab\n");
+    assert!(app
+        .notifs()
+        .last()
+        .unwrap()
+        .to_string()
+        .ends_with("already at oldest change"));
+}
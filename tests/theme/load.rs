@@ -0,0 +1,70 @@
+use editor::{app::Mode, theme::Theme};
+use ratatui::style::{Color, Modifier, Style};
+
+#[test]
+fn builtin_themes_should_load() {
+    for name in Theme::builtin_names() {
+        assert!(Theme::builtin(name).is_ok(), "{} failed to load", name);
+    }
+}
+
+#[test]
+fn unknown_builtin_theme_should_error() {
+    assert!(Theme::builtin("does-not-exist").is_err())
+}
+
+#[test]
+fn partial_theme_should_inherit_from_default() {
+    let theme =
+        Theme::from_toml("partial", "[mode]\nnormal = { bg = \"magenta\" }")
+            .unwrap();
+
+    assert_eq!(
+        theme.mode(Mode::Normal),
+        Style::default().fg(Color::White).bg(Color::Magenta)
+    );
+    assert_eq!(
+        theme.mode(Mode::Insert),
+        Theme::default().mode(Mode::Insert)
+    );
+}
+
+#[test]
+fn theme_should_parse_hex_colors_and_modifiers() {
+    let theme = Theme::from_toml(
+        "hex",
+        "[cursor_line]\nfg = \"#102030\"\nmodifiers = [\"bold\"]",
+    )
+    .unwrap();
+
+    assert_eq!(
+        theme.cursor_line(),
+        Style::default()
+            .fg(Color::Rgb(16, 32, 48))
+            .bg(Color::Black)
+            .add_modifier(Modifier::BOLD)
+    );
+}
+
+#[test]
+fn invalid_color_should_error() {
+    let theme = Theme::from_toml("bad", "[cursor_line]\nfg = \"not-a-color\"");
+
+    assert!(theme.is_err())
+}
+
+#[test]
+fn whitespace_glyph_should_be_configurable() {
+    let theme =
+        Theme::from_toml("dots", "[whitespace]\nspace = \"•\"").unwrap();
+
+    assert_eq!(theme.space_glyph(), '•')
+}
+
+#[test]
+fn missing_scope_should_fall_back_to_parent() {
+    let theme = Theme::default();
+
+    assert_eq!(theme.scope("keyword.control"), theme.scope("keyword"));
+    assert_eq!(theme.scope("unthemed"), Style::default());
+}
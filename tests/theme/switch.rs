@@ -0,0 +1,31 @@
+use crate::helpers::{app_default, CTRL_T_KEY};
+
+#[test]
+fn ctrl_t_should_cycle_theme() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(CTRL_T_KEY, &mut app);
+
+    assert_eq!(app.theme().name(), "gruvbox")
+}
+
+#[test]
+fn ctrl_t_should_wrap_around_to_first_theme() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(CTRL_T_KEY, &mut app);
+    handler.handle_key_event(CTRL_T_KEY, &mut app);
+    handler.handle_key_event(CTRL_T_KEY, &mut app);
+
+    assert_eq!(app.theme().name(), "default")
+}
+
+#[test]
+fn ctrl_t_should_notify() {
+    let (mut app, mut handler) = app_default();
+
+    handler.handle_key_event(CTRL_T_KEY, &mut app);
+
+    let most_recent_notif = app.notifs().last().unwrap().to_string();
+    assert_eq!(most_recent_notif, "󰬐  theme gruvbox")
+}
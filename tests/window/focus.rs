@@ -0,0 +1,105 @@
+use super::{project, window_keys};
+use crate::helpers::{DOWN_KEY, ESC_KEY, I_KEY};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[test]
+fn split_should_focus_a_new_window_at_the_same_place() {
+    let (_dir, mut app, mut handler) = project();
+    handler.handle_key_event(DOWN_KEY, &mut app);
+
+    window_keys('s', &mut app, &mut handler);
+
+    assert_eq!(app.windows().len(), 2);
+    assert_eq!(app.windows().focused(), 1);
+    assert_eq!(app.buffer.cursor, (0, 1).into());
+}
+
+#[test]
+fn each_window_should_keep_its_cursor() {
+    let (_dir, mut app, mut handler) = project();
+    window_keys('v', &mut app, &mut handler);
+    handler.handle_key_event(DOWN_KEY, &mut app);
+    handler.handle_key_event(DOWN_KEY, &mut app);
+
+    window_keys('h', &mut app, &mut handler);
+    assert_eq!(app.windows().focused(), 0);
+    assert_eq!(app.buffer.cursor, (0, 0).into());
+
+    window_keys('l', &mut app, &mut handler);
+    assert_eq!(app.windows().focused(), 1);
+    assert_eq!(app.buffer.cursor, (0, 2).into());
+}
+
+#[test]
+fn edits_should_be_seen_from_every_window_onto_the_buffer() {
+    let (_dir, mut app, mut handler) = project();
+    window_keys('s', &mut app, &mut handler);
+    handler.handle_key_event(I_KEY, &mut app);
+    let x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE);
+    handler.handle_key_event(x, &mut app);
+    handler.handle_key_event(ESC_KEY, &mut app);
+
+    window_keys('k', &mut app, &mut handler);
+
+    assert_eq!(app.windows().focused(), 0);
+    assert_eq!(app.buffer.contents(), "xfirst\nsecond\nthird\n");
+}
+
+#[test]
+fn cursor_past_lines_deleted_elsewhere_should_be_kept_inside_the_text() {
+    let (_dir, mut app, mut handler) = project();
+    app.move_down(2).unwrap();
+    window_keys('s', &mut app, &mut handler);
+    app.move_up(2).unwrap();
+    app.delete_lines(2).unwrap();
+
+    window_keys('k', &mut app, &mut handler);
+
+    assert_eq!(app.buffer.cursor.y, 0);
+}
+
+#[test]
+fn windows_should_switch_to_the_buffer_they_show() {
+    let (dir, mut app, mut handler) = project();
+    window_keys('v', &mut app, &mut handler);
+    app.open_file(&dir.path().join("two.txt")).unwrap();
+
+    window_keys('h', &mut app, &mut handler);
+    assert!(app.buffer.file_name().ends_with("one.txt"));
+
+    window_keys('l', &mut app, &mut handler);
+    assert!(app.buffer.file_name().ends_with("two.txt"));
+}
+
+#[test]
+fn close_should_focus_the_window_taking_its_place() {
+    let (_dir, mut app, mut handler) = project();
+    window_keys('s', &mut app, &mut handler);
+
+    window_keys('q', &mut app, &mut handler);
+
+    assert_eq!(app.windows().len(), 1);
+    assert_eq!(app.windows().focused(), 0);
+}
+
+#[test]
+fn last_window_should_not_close() {
+    let (_dir, mut app, mut handler) = project();
+
+    window_keys('q', &mut app, &mut handler);
+
+    assert_eq!(app.windows().len(), 1);
+    let last = app.notifs().last().unwrap().to_string();
+    assert!(last.ends_with("can't close the last window"));
+}
+
+#[test]
+fn focus_without_a_window_there_should_warn() {
+    let (_dir, mut app, mut handler) = project();
+    window_keys('s', &mut app, &mut handler);
+
+    window_keys('l', &mut app, &mut handler);
+
+    let last = app.notifs().last().unwrap().to_string();
+    assert!(last.ends_with("no window there"));
+}
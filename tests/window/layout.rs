@@ -0,0 +1,109 @@
+use editor::window::{Direction, Split, Window, Windows};
+use ratatui::layout::Rect;
+
+const AREA: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 40,
+    height: 20,
+};
+
+fn windows() -> Windows {
+    Windows::new(Window::new("file.txt"))
+}
+
+#[test]
+fn horizontal_split_should_put_the_new_window_below() {
+    let mut windows = windows();
+
+    let id = windows.split(Split::Horizontal, Window::new("file.txt"));
+
+    assert_eq!(
+        windows.areas(AREA),
+        vec![(0, Rect::new(0, 0, 40, 10)), (id, Rect::new(0, 10, 40, 10))]
+    );
+}
+
+#[test]
+fn vertical_split_should_leave_a_column_between_the_windows() {
+    let mut windows = windows();
+
+    let id = windows.split(Split::Vertical, Window::new("file.txt"));
+
+    assert_eq!(
+        windows.areas(AREA),
+        vec![(0, Rect::new(0, 0, 20, 20)), (id, Rect::new(21, 0, 19, 20))]
+    );
+}
+
+#[test]
+fn closing_should_give_the_space_to_the_sibling_and_focus_it() {
+    let mut windows = windows();
+    let right = windows.split(Split::Vertical, Window::new("file.txt"));
+    windows.focus(right);
+    let below = windows.split(Split::Horizontal, Window::new("file.txt"));
+    windows.focus(below);
+
+    assert_eq!(windows.close(), Some(right));
+
+    assert_eq!(windows.len(), 2);
+    assert_eq!(windows.focused(), right);
+    assert_eq!(windows.areas(AREA)[1], (right, Rect::new(21, 0, 19, 20)));
+}
+
+#[test]
+fn last_window_should_not_close() {
+    let mut windows = windows();
+
+    assert_eq!(windows.close(), None);
+    assert_eq!(windows.len(), 1);
+}
+
+#[test]
+fn resize_should_move_the_nearest_split_of_the_kind() {
+    let mut windows = windows();
+    let right = windows.split(Split::Vertical, Window::new("file.txt"));
+    windows.focus(right);
+    windows.split(Split::Horizontal, Window::new("file.txt"));
+
+    assert!(windows.resize(Split::Vertical, 10));
+
+    // The right side grew, the split inside it is as it was.
+    let areas = windows.areas(AREA);
+    assert_eq!(areas[0].1.width, 16);
+    assert_eq!(areas[1].1.height, 10);
+}
+
+#[test]
+fn resize_should_keep_both_sides_on_screen() {
+    let mut windows = windows();
+    windows.split(Split::Horizontal, Window::new("file.txt"));
+
+    windows.resize(Split::Horizontal, 200);
+
+    assert_eq!(windows.areas(AREA)[1].1.height, 2);
+}
+
+#[test]
+fn resize_without_a_split_of_the_kind_should_fail() {
+    let mut windows = windows();
+    windows.split(Split::Horizontal, Window::new("file.txt"));
+
+    assert!(!windows.resize(Split::Vertical, 5));
+}
+
+#[test]
+fn neighbour_should_be_the_window_overlapping_the_most() {
+    // Left of one tall window on the right, and above one below it.
+    let mut windows = windows();
+    let right = windows.split(Split::Vertical, Window::new("file.txt"));
+    let below = windows.split(Split::Horizontal, Window::new("file.txt"));
+    windows.resize(Split::Horizontal, 20);
+
+    assert_eq!(windows.neighbour(Direction::Right), Some(right));
+    assert_eq!(windows.neighbour(Direction::Down), Some(below));
+    assert_eq!(windows.neighbour(Direction::Left), None);
+
+    windows.focus(right);
+    assert_eq!(windows.neighbour(Direction::Left), Some(0));
+}
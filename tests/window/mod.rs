@@ -0,0 +1,29 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use editor::{app::App, buffer::Buffer, handler::Handler};
+use std::fs;
+use tempfile::TempDir;
+
+mod focus;
+mod layout;
+mod render;
+
+/// A project of two files, with `one.txt` open.
+fn project() -> (TempDir, App, Handler) {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("one.txt"), "first\nsecond\nthird\n").unwrap();
+    fs::write(dir.path().join("two.txt"), "alpha\nbeta\n").unwrap();
+
+    let path = dir.path().join("one.txt");
+    let app =
+        App::new(Buffer::from_file(path.to_string_lossy().into()).unwrap());
+
+    (dir, app, Handler::new())
+}
+
+/// Press `<C-w>` then `key`.
+fn window_keys(key: char, app: &mut App, handler: &mut Handler) {
+    let ctrl_w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+    handler.handle_key_event(ctrl_w, app);
+    let key = KeyEvent::new(KeyCode::Char(key), KeyModifiers::NONE);
+    handler.handle_key_event(key, app);
+}
@@ -0,0 +1,60 @@
+use super::{project, window_keys};
+use crate::helpers::draw;
+
+#[test]
+fn vertical_split_should_show_the_buffer_on_both_sides() {
+    let (_dir, mut app, mut handler) = project();
+    window_keys('v', &mut app, &mut handler);
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let row = |y: u16, from: u16| -> String {
+        (from..from + 19)
+            .map(|x| buffer.get(x, y).symbol())
+            .collect()
+    };
+    let cursor_row = (0..16).find(|y| row(*y, 0).contains("first")).unwrap();
+    assert!(row(cursor_row, 20).contains("first"));
+}
+
+#[test]
+fn bars_should_name_the_files_and_show_which_window_is_focused() {
+    let (dir, mut app, mut handler) = project();
+    window_keys('s', &mut app, &mut handler);
+    app.open_file(&dir.path().join("two.txt")).unwrap();
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let row = |y: u16| -> String {
+        (0..40).map(|x| buffer.get(x, y).symbol()).collect()
+    };
+    let bars: Vec<u16> = (0..16).filter(|y| row(*y).contains(".txt")).collect();
+    assert_eq!(bars.len(), 2);
+    assert!(row(bars[0]).contains("one.txt"));
+    assert!(row(bars[1]).contains("two.txt"));
+    let focused = app.theme().mode(app.mode());
+    assert_eq!(buffer.get(0, bars[1]).bg, focused.bg.unwrap());
+    assert_ne!(buffer.get(0, bars[0]).bg, focused.bg.unwrap());
+}
+
+#[test]
+fn other_window_past_lines_deleted_should_be_drawn_on_the_last_line() {
+    let (_dir, mut app, mut handler) = project();
+    window_keys('v', &mut app, &mut handler);
+    app.move_down(2).unwrap();
+    window_keys('h', &mut app, &mut handler);
+    app.delete_lines(2).unwrap();
+
+    let terminal = draw(&app, &handler);
+
+    let buffer = terminal.backend().buffer();
+    let row = |y: u16, from: u16| -> String {
+        (from..from + 19)
+            .map(|x| buffer.get(x, y).symbol())
+            .collect()
+    };
+    let cursor_row = (0..16).find(|y| row(*y, 0).contains("third")).unwrap();
+    assert!(row(cursor_row, 20).contains("third"));
+}